@@ -0,0 +1,449 @@
+//! Derive macros for the `Encodeable` and `Decodeable` traits of oxicraft's `coding` module.
+//!
+//! `Decodeable` is implemented for every `std::io::Read`, so the types can be decoded from
+//! packets in memory as well as straight from a stream.
+//!
+//! Structs are (de)coded field by field in declaration order. String fields may carry a
+//! `#[max_length = N]` attribute which is checked while decoding.
+//!
+//! Fieldless enums are (de)coded by their discriminant, which either needs a `#[varint]`
+//! attribute or a `#[repr(..)]` with a primitive that is (de)codeable itself (e.g. `u8`).
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DataEnum, DataStruct, DeriveInput, Error,
+    Expr, ExprLit, Fields, Ident, Index, Lit, Meta, Result,
+};
+
+#[proc_macro_derive(Encodeable, attributes(varint))]
+pub fn derive_encodeable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand_encodeable(&input)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(Decodeable, attributes(varint, max_length))]
+pub fn derive_decodeable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand_decodeable(&input)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+/// How the discriminant of a fieldless enum is put on the wire.
+enum Discriminant {
+    Varint,
+    Repr(Ident),
+}
+
+fn expand_encodeable(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let (encode, byte_length) = match &input.data {
+        Data::Struct(data) => encode_struct(data),
+        Data::Enum(data) => encode_enum(input, data)?,
+        Data::Union(_) => {
+            return Err(Error::new(
+                Span::call_site(),
+                "Encodeable can not be derived for unions",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics crate::coding::Encodeable for #name #ty_generics #where_clause {
+            fn encode_into<__B: crate::coding::buffer::BufMut>(&self, buffer: &mut __B) {
+                #encode
+            }
+
+            fn byte_length(&self) -> usize {
+                #byte_length
+            }
+        }
+    })
+}
+
+fn encode_struct(data: &DataStruct) -> (TokenStream2, TokenStream2) {
+    let accessors = field_accessors(&data.fields);
+
+    if accessors.is_empty() {
+        return (quote!(let _ = buffer;), quote!(0));
+    }
+
+    let encode = quote! {
+        #(
+            crate::coding::Encodeable::encode_into(&self.#accessors, buffer);
+        )*
+    };
+
+    let accessors = field_accessors(&data.fields);
+
+    let byte_length = quote! {
+        0usize #(
+            + crate::coding::Encodeable::byte_length(&self.#accessors)
+        )*
+    };
+
+    (encode, byte_length)
+}
+
+fn encode_enum(input: &DeriveInput, data: &DataEnum) -> Result<(TokenStream2, TokenStream2)> {
+    let name = &input.ident;
+    let variants = unit_variants(data)?;
+    let discriminant = discriminant(input)?;
+
+    let repr = match &discriminant {
+        Discriminant::Varint => Ident::new("i32", Span::call_site()),
+        Discriminant::Repr(repr) => repr.clone(),
+    };
+
+    let arms = variants.iter().map(|variant| {
+        quote! {
+            #name::#variant => #name::#variant as #repr,
+        }
+    });
+
+    let value = match &discriminant {
+        Discriminant::Varint => quote! {
+            crate::coding::varint::Varint(match self { #( #arms )* })
+        },
+        Discriminant::Repr(_) => quote! {
+            match self { #( #arms )* }
+        },
+    };
+
+    let encode = quote! {
+        crate::coding::Encodeable::encode_into(&#value, buffer)
+    };
+
+    let byte_length = quote! {
+        crate::coding::Encodeable::byte_length(&#value)
+    };
+
+    Ok((encode, byte_length))
+}
+
+fn expand_decodeable(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    // the type is decoded from any reader
+    let mut generics = input.generics.clone();
+    generics.params.push(parse_quote!(__R: ::std::io::Read));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let decode = match &input.data {
+        Data::Struct(data) => decode_struct(name, data)?,
+        Data::Enum(data) => decode_enum(input, data)?,
+        Data::Union(_) => {
+            return Err(Error::new(
+                Span::call_site(),
+                "Decodeable can not be derived for unions",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics crate::coding::Decodeable<#name #ty_generics, crate::coding::error::ProtocolError>
+            for __R #where_clause
+        {
+            fn decode(&mut self) -> ::std::result::Result<#name #ty_generics, crate::coding::error::ProtocolError> {
+                #decode
+            }
+        }
+    })
+}
+
+fn decode_struct(name: &Ident, data: &DataStruct) -> Result<TokenStream2> {
+    let mut values = Vec::new();
+
+    for field in data.fields.iter() {
+        let ty = &field.ty;
+
+        values.push(match max_length(&field.attrs)? {
+            Some(max_length) => quote! {
+                crate::coding::string::ReadString::read_string(self, #max_length)?
+            },
+            None => quote! {
+                <Self as crate::coding::Decodeable<#ty, crate::coding::error::ProtocolError>>::decode(self)?
+            },
+        });
+    }
+
+    Ok(match &data.fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|field| &field.ident);
+            let values = values.iter();
+
+            quote! {
+                Ok(#name { #( #names: #values, )* })
+            }
+        }
+        Fields::Unnamed(_) => {
+            let values = values.iter();
+
+            quote! {
+                Ok(#name( #( #values, )* ))
+            }
+        }
+        Fields::Unit => quote! {
+            Ok(#name)
+        },
+    })
+}
+
+fn decode_enum(input: &DeriveInput, data: &DataEnum) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let variants = unit_variants(data)?;
+
+    let (raw_value, repr) = match discriminant(input)? {
+        Discriminant::Varint => (
+            quote! {
+                <Self as crate::coding::Decodeable<
+                    crate::coding::varint::Varint,
+                    crate::coding::error::ProtocolError,
+                >>::decode(self)?.0
+            },
+            Ident::new("i32", Span::call_site()),
+        ),
+        Discriminant::Repr(repr) => (
+            quote! {
+                <Self as crate::coding::Decodeable<#repr, crate::coding::error::ProtocolError>>::decode(self)?
+            },
+            repr,
+        ),
+    };
+
+    let checks = variants.iter().map(|variant| {
+        quote! {
+            if raw_value == #name::#variant as #repr {
+                return Ok(#name::#variant);
+            }
+        }
+    });
+
+    let type_name = name.to_string();
+
+    Ok(quote! {
+        let raw_value = #raw_value;
+
+        #( #checks )*
+
+        Err(crate::coding::error::ProtocolError::invalid_value(#type_name, raw_value))
+    })
+}
+
+/// Returns how the fields of a struct are accessed on `self`, in declaration order.
+fn field_accessors(fields: &Fields) -> Vec<TokenStream2> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match &field.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let index = Index::from(index);
+                quote!(#index)
+            }
+        })
+        .collect()
+}
+
+fn unit_variants(data: &DataEnum) -> Result<Vec<Ident>> {
+    data.variants
+        .iter()
+        .map(|variant| match variant.fields {
+            Fields::Unit => Ok(variant.ident.clone()),
+            _ => Err(Error::new(
+                variant.span(),
+                "only fieldless enums can be (de)coded by their discriminant",
+            )),
+        })
+        .collect()
+}
+
+fn discriminant(input: &DeriveInput) -> Result<Discriminant> {
+    for attribute in &input.attrs {
+        match &attribute.meta {
+            Meta::Path(path) if path.is_ident("varint") => return Ok(Discriminant::Varint),
+            Meta::List(list) if list.path.is_ident("repr") => {
+                // only the first argument is the primitive, e.g. `#[repr(u8, ..)]`
+                let mut repr = None;
+                let _ = list.parse_nested_meta(|meta| {
+                    if repr.is_none() {
+                        repr = meta.path.get_ident().cloned();
+                    }
+                    Ok(())
+                });
+
+                if let Some(repr) = repr {
+                    return Ok(Discriminant::Repr(repr));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Err(Error::new(
+        input.ident.span(),
+        "enums need either a #[varint] or a #[repr(..)] attribute to be (de)coded",
+    ))
+}
+
+fn max_length(attributes: &[Attribute]) -> Result<Option<u16>> {
+    for attribute in attributes {
+        if let Meta::NameValue(name_value) = &attribute.meta {
+            if !name_value.path.is_ident("max_length") {
+                continue;
+            }
+
+            let max_length = match &name_value.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(value),
+                    ..
+                }) => value.base10_parse::<u16>().ok(),
+                _ => None,
+            };
+
+            return match max_length {
+                Some(max_length) => Ok(Some(max_length)),
+                None => Err(Error::new(
+                    name_value.value.span(),
+                    "max_length needs to be an integer between 0 and 65535",
+                )),
+            };
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_decodeable, expand_encodeable};
+    use syn::{parse_quote, DeriveInput};
+
+    /// Expands the input with both derives, without whitespace to keep the checks readable.
+    fn expand(input: DeriveInput) -> (String, String) {
+        let compact = |tokens: proc_macro2::TokenStream| tokens.to_string().replace(' ', "");
+
+        (
+            compact(expand_encodeable(&input).unwrap()),
+            compact(expand_decodeable(&input).unwrap()),
+        )
+    }
+
+    fn error(input: DeriveInput) -> String {
+        expand_decodeable(&input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_varint_discriminant() {
+        let (encode, decode) = expand(parse_quote! {
+            #[varint]
+            enum ChatMode {
+                Enabled = 0,
+                Hidden = 2,
+            }
+        });
+
+        assert!(encode.contains(
+            "crate::coding::varint::Varint(matchself{ChatMode::Enabled=>ChatMode::Enabledasi32,\
+             ChatMode::Hidden=>ChatMode::Hiddenasi32,})"
+        ));
+        assert!(decode.contains("crate::coding::varint::Varint"));
+        assert!(decode.contains("ifraw_value==ChatMode::Hiddenasi32{returnOk(ChatMode::Hidden);}"));
+        assert!(decode.contains("invalid_value(\"ChatMode\",raw_value)"));
+    }
+
+    #[test]
+    fn test_repr_discriminant() {
+        let (encode, decode) = expand(parse_quote! {
+            #[derive(Clone, Copy)]
+            #[repr(u8)]
+            enum Gamemode {
+                Survival = 0,
+                Creative = 1,
+            }
+        });
+
+        assert!(encode.contains("matchself{Gamemode::Survival=>Gamemode::Survivalasu8,"));
+        assert!(!encode.contains("Varint"));
+        assert!(
+            decode.contains("crate::coding::Decodeable<u8,crate::coding::error::ProtocolError>")
+        );
+        assert!(
+            decode.contains("ifraw_value==Gamemode::Creativeasu8{returnOk(Gamemode::Creative);}")
+        );
+    }
+
+    #[test]
+    fn test_max_length() {
+        let (encode, decode) = expand(parse_quote! {
+            struct LoginStart {
+                #[max_length = 16]
+                username: MinecraftString,
+                protocol_version: Varint,
+            }
+        });
+
+        assert!(encode.contains("crate::coding::Encodeable::encode_into(&self.username,buffer);"));
+        assert!(decode
+            .contains("username:crate::coding::string::ReadString::read_string(self,16u16)?,"));
+        assert!(decode.contains("protocol_version:<Selfascrate::coding::Decodeable<Varint,"));
+    }
+
+    #[test]
+    fn test_invalid_attributes() {
+        let mappings: Vec<(DeriveInput, &str)> = vec![
+            (
+                parse_quote! {
+                    enum Difficulty { Peaceful }
+                },
+                "enums need either a #[varint] or a #[repr(..)] attribute to be (de)coded",
+            ),
+            (
+                parse_quote! {
+                    #[varint]
+                    enum Packet { Ping(i64) }
+                },
+                "only fieldless enums can be (de)coded by their discriminant",
+            ),
+            (
+                parse_quote! {
+                    struct Handshake {
+                        #[max_length = 65536]
+                        server_address: MinecraftString,
+                    }
+                },
+                "max_length needs to be an integer between 0 and 65535",
+            ),
+            (
+                parse_quote! {
+                    struct Handshake {
+                        #[max_length = "255"]
+                        server_address: MinecraftString,
+                    }
+                },
+                "max_length needs to be an integer between 0 and 65535",
+            ),
+            (
+                parse_quote! {
+                    union Value { int: i32 }
+                },
+                "Decodeable can not be derived for unions",
+            ),
+        ];
+
+        for (input, message) in mappings {
+            assert_eq!(message, error(input));
+        }
+    }
+}
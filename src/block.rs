@@ -0,0 +1,486 @@
+//! The registry of blocks and their states, which maps names like
+//! `minecraft:oak_stairs[facing=north,half=top]` to the ids of the global palette and back.
+//!
+//! It is loaded from `data/blocks.json`, the blocks report of the vanilla data generator
+//! (`java -cp minecraft_server.1.13.2.jar net.minecraft.data.Main --reports`).
+use crate::chunk::BlockState;
+use crate::coding::error::ProtocolError;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+const VANILLA_REPORT: &str = include_str!("../data/blocks.json");
+
+/// Blocks without a namespace belong to vanilla, like in commands.
+const DEFAULT_NAMESPACE: &str = "minecraft";
+
+/// A block as listed in the blocks report.
+#[derive(Deserialize)]
+struct ReportBlock {
+    #[serde(default)]
+    properties: BTreeMap<String, Vec<String>>,
+    states: Vec<ReportState>,
+}
+
+#[derive(Deserialize)]
+struct ReportState {
+    #[serde(default)]
+    properties: BTreeMap<String, String>,
+    id: BlockState,
+    #[serde(default)]
+    default: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Property {
+    name: String,
+    values: Vec<String>,
+}
+
+/// A block and the range of ids its states occupy.
+///
+/// Vanilla numbers the states of a block consecutively, going through the values of its
+/// properties like through the digits of a number, with the property sorted last by name
+/// changing fastest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    name: String,
+    properties: Vec<Property>,
+    first_state: BlockState,
+    default_state: BlockState,
+}
+
+impl Block {
+    /// The namespaced name, e.g. `minecraft:grass_block`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn default_state(&self) -> BlockState {
+        self.default_state
+    }
+
+    pub fn states(&self) -> Range<BlockState> {
+        let count: usize = self
+            .properties
+            .iter()
+            .map(|property| property.values.len())
+            .product();
+
+        self.first_state..self.first_state + count as BlockState
+    }
+
+    /// Returns the state with the given property values, using the values of the default
+    /// state for all properties that are not given.
+    pub fn state<'a, I>(&self, properties: I) -> Result<BlockState, ProtocolError>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut indices = self.value_indices(self.default_state);
+
+        for (name, value) in properties {
+            let position = self
+                .properties
+                .iter()
+                .position(|property| property.name == name)
+                .ok_or_else(|| {
+                    ProtocolError::invalid_value(
+                        "block property",
+                        format!("{} of {}", name, self.name),
+                    )
+                })?;
+
+            indices[position] = self.properties[position]
+                .values
+                .iter()
+                .position(|candidate| candidate == value)
+                .ok_or_else(|| {
+                    ProtocolError::invalid_value(
+                        "block property",
+                        format!("{}={} of {}", name, value, self.name),
+                    )
+                })?;
+        }
+
+        Ok(self.state_of_indices(&indices))
+    }
+
+    /// Returns the property values of the given state, which has to be one of this block.
+    pub fn properties_of(&self, state: BlockState) -> Vec<(&str, &str)> {
+        self.properties
+            .iter()
+            .zip(self.value_indices(state))
+            .map(|(property, index)| (property.name.as_str(), property.values[index].as_str()))
+            .collect()
+    }
+
+    fn value_indices(&self, state: BlockState) -> Vec<usize> {
+        debug_assert!(self.states().contains(&state));
+
+        let mut offset = usize::from(state - self.first_state);
+        let mut indices = vec![0; self.properties.len()];
+
+        for (index, property) in indices.iter_mut().zip(&self.properties).rev() {
+            *index = offset % property.values.len();
+            offset /= property.values.len();
+        }
+
+        indices
+    }
+
+    fn state_of_indices(&self, indices: &[usize]) -> BlockState {
+        let offset = indices
+            .iter()
+            .zip(&self.properties)
+            .fold(0, |offset, (index, property)| {
+                offset * property.values.len() + index
+            });
+
+        self.first_state + offset as BlockState
+    }
+}
+
+#[derive(Debug)]
+pub struct BlockRegistry {
+    /// Sorted by their first state, so the block of a state can be searched.
+    blocks: Vec<Block>,
+    by_name: HashMap<String, usize>,
+}
+
+impl BlockRegistry {
+    /// The registry of the blocks report that is checked into the repository.
+    pub fn vanilla() -> &'static BlockRegistry {
+        static VANILLA: OnceLock<BlockRegistry> = OnceLock::new();
+
+        VANILLA.get_or_init(|| {
+            BlockRegistry::from_report(VANILLA_REPORT).expect("the vanilla blocks report is valid")
+        })
+    }
+
+    /// Reads a blocks report of the data generator, checking that the states of each block
+    /// are numbered the way vanilla does.
+    pub fn from_report(json: &str) -> Result<BlockRegistry, ProtocolError> {
+        let report: BTreeMap<String, ReportBlock> = serde_json::from_str(json)
+            .map_err(|error| ProtocolError::invalid_value("blocks report", error))?;
+
+        let mut blocks = Vec::with_capacity(report.len());
+
+        for (name, report_block) in report {
+            let invalid = |reason: &str| {
+                ProtocolError::invalid_value("blocks report", format!("{} {}", name, reason))
+            };
+
+            let first_state = report_block
+                .states
+                .iter()
+                .map(|state| state.id)
+                .min()
+                .ok_or_else(|| invalid("has no states"))?;
+            let default_state = match report_block
+                .states
+                .iter()
+                .filter(|state| state.default)
+                .collect::<Vec<_>>()[..]
+            {
+                [state] => state.id,
+                _ => return Err(invalid("does not have exactly one default state")),
+            };
+
+            let block = Block {
+                name: name.clone(),
+                properties: report_block
+                    .properties
+                    .into_iter()
+                    .map(|(name, values)| Property { name, values })
+                    .collect(),
+                first_state,
+                default_state,
+            };
+
+            if block.states().len() != report_block.states.len()
+                || !block.states().contains(&default_state)
+            {
+                return Err(invalid(
+                    "does not have a state for each combination of values",
+                ));
+            }
+
+            for state in &report_block.states {
+                let properties = state
+                    .properties
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_str()));
+
+                if state.properties.len() != block.properties.len()
+                    || block.state(properties)? != state.id
+                {
+                    return Err(invalid(&format!("has an unexpected id {}", state.id)));
+                }
+            }
+
+            blocks.push(block);
+        }
+
+        blocks.sort_by_key(|block| block.first_state);
+
+        for pair in blocks.windows(2) {
+            if pair[0].states().end > pair[1].first_state {
+                return Err(ProtocolError::invalid_value(
+                    "blocks report",
+                    format!("{} overlaps {}", pair[0].name, pair[1].name),
+                ));
+            }
+        }
+
+        let by_name = blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block)| (block.name.clone(), index))
+            .collect();
+
+        Ok(BlockRegistry { blocks, by_name })
+    }
+
+    /// Returns the block of the given name, which defaults to the `minecraft` namespace.
+    pub fn block(&self, name: &str) -> Option<&Block> {
+        let index = if name.contains(':') {
+            self.by_name.get(name)
+        } else {
+            self.by_name.get(&format!("{}:{}", DEFAULT_NAMESPACE, name))
+        };
+
+        index.map(|&index| &self.blocks[index])
+    }
+
+    /// Returns the block the given state belongs to.
+    pub fn block_of(&self, state: BlockState) -> Option<&Block> {
+        let index = match self
+            .blocks
+            .binary_search_by_key(&state, |block| block.first_state)
+        {
+            Ok(index) => index,
+            Err(0) => return None,
+            Err(index) => index - 1,
+        };
+
+        Some(&self.blocks[index]).filter(|block| block.states().contains(&state))
+    }
+
+    /// Parses a block state like `oak_stairs[facing=north,half=top]`, where all properties
+    /// that are not given have the value of the default state.
+    pub fn parse(&self, block_state: &str) -> Result<BlockState, ProtocolError> {
+        let (name, properties) = match block_state.find('[') {
+            Some(start) if block_state.ends_with(']') => (
+                &block_state[..start],
+                &block_state[start + 1..block_state.len() - 1],
+            ),
+            Some(_) => return Err(ProtocolError::invalid_value("block state", block_state)),
+            None => (block_state, ""),
+        };
+
+        let block = self
+            .block(name)
+            .ok_or_else(|| ProtocolError::invalid_value("block", name))?;
+
+        let properties = properties
+            .split(',')
+            .filter(|property| !property.is_empty())
+            .map(|property| {
+                let mut parts = property.splitn(2, '=');
+
+                match (parts.next(), parts.next()) {
+                    (Some(name), Some(value)) => Ok((name.trim(), value.trim())),
+                    _ => Err(ProtocolError::invalid_value("block property", property)),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        block.state(properties)
+    }
+
+    /// Formats the given state like `minecraft:grass_block[snowy=false]`, the way
+    /// [`parse`](BlockRegistry::parse) reads it.
+    pub fn name_of(&self, state: BlockState) -> Option<String> {
+        let block = self.block_of(state)?;
+        let properties = block.properties_of(state);
+
+        if properties.is_empty() {
+            return Some(block.name.clone());
+        }
+
+        let properties: Vec<String> = properties
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+
+        Some(format!("{}[{}]", block.name, properties.join(",")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockRegistry;
+    use crate::chunk::BlockState;
+    use crate::coding::error::ProtocolError;
+
+    #[test]
+    fn test_vanilla_states() {
+        let blocks = BlockRegistry::vanilla();
+
+        let mappings: Vec<(&str, BlockState)> = vec![
+            ("minecraft:air", 0),
+            ("minecraft:stone", 1),
+            ("minecraft:grass_block[snowy=true]", 8),
+            ("minecraft:grass_block[snowy=false]", 9),
+            ("minecraft:dirt", 10),
+            ("minecraft:bedrock", 33),
+            ("minecraft:water[level=0]", 34),
+            ("minecraft:lava[level=15]", 65),
+            ("minecraft:oak_log[axis=y]", 73),
+            ("minecraft:stripped_oak_log[axis=x]", 105),
+            ("minecraft:oak_leaves[distance=7,persistent=false]", 157),
+            ("minecraft:dispenser[facing=north,triggered=false]", 234),
+            (
+                "minecraft:note_block[instrument=xylophone,note=24,powered=false]",
+                747,
+            ),
+            (
+                "minecraft:oak_stairs[facing=north,half=top,shape=straight,waterlogged=false]",
+                1650,
+            ),
+            (
+                "minecraft:redstone_wire[east=none,north=none,power=15,south=none,west=none]",
+                3048,
+            ),
+            ("minecraft:void_air", 8591),
+            ("minecraft:cave_air", 8592),
+            ("minecraft:structure_block[mode=data]", 8598),
+        ];
+
+        for (name, state) in mappings {
+            assert_eq!(state, blocks.parse(name).unwrap(), "{}", name);
+            assert_eq!(Some(name.to_owned()), blocks.name_of(state));
+        }
+    }
+
+    #[test]
+    fn test_parse_defaults() {
+        let blocks = BlockRegistry::vanilla();
+
+        assert_eq!(9, blocks.parse("grass_block").unwrap());
+        assert_eq!(9, blocks.parse("minecraft:grass_block[]").unwrap());
+        assert_eq!(
+            233,
+            blocks
+                .parse("minecraft:dispenser[ triggered = true ]")
+                .unwrap()
+        );
+        assert_eq!(
+            1650,
+            blocks.parse("oak_stairs[facing=north,half=top]").unwrap()
+        );
+        assert_eq!(
+            Some(9),
+            blocks
+                .block("grass_block")
+                .map(|block| block.default_state())
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let blocks = BlockRegistry::vanilla();
+
+        let mappings = vec![
+            (
+                "minecraft:unknown",
+                ProtocolError::invalid_value("block", "minecraft:unknown"),
+            ),
+            (
+                "stone[",
+                ProtocolError::invalid_value("block state", "stone["),
+            ),
+            (
+                "stone[snowy=true]",
+                ProtocolError::invalid_value("block property", "snowy of minecraft:stone"),
+            ),
+            (
+                "grass_block[snowy=maybe]",
+                ProtocolError::invalid_value(
+                    "block property",
+                    "snowy=maybe of minecraft:grass_block",
+                ),
+            ),
+            (
+                "grass_block[snowy]",
+                ProtocolError::invalid_value("block property", "snowy"),
+            ),
+        ];
+
+        for (block_state, error) in mappings {
+            assert_eq!(Err(error), blocks.parse(block_state), "{}", block_state);
+        }
+    }
+
+    #[test]
+    fn test_block_of() {
+        let blocks = BlockRegistry::vanilla();
+
+        assert_eq!("minecraft:air", blocks.block_of(0).unwrap().name());
+        assert_eq!("minecraft:grass_block", blocks.block_of(8).unwrap().name());
+        assert_eq!("minecraft:note_block", blocks.block_of(500).unwrap().name());
+        assert_eq!(
+            "minecraft:structure_block",
+            blocks.block_of(8598).unwrap().name()
+        );
+        assert_eq!(None, blocks.block_of(8599));
+        assert_eq!(None, blocks.name_of(8599));
+    }
+
+    #[test]
+    fn test_all_states_round_trip() {
+        let blocks = BlockRegistry::vanilla();
+
+        for state in 0..8599 {
+            let name = blocks.name_of(state).unwrap();
+
+            assert_eq!(state, blocks.parse(&name).unwrap(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_invalid_report() {
+        let mappings = vec![
+            (r#"{"a:b": {"states": []}}"#, "a:b has no states"),
+            (
+                r#"{"a:b": {"states": [{"id": 0}]}}"#,
+                "a:b does not have exactly one default state",
+            ),
+            (
+                r#"{"a:b": {"properties": {"c": ["d", "e"]}, "states": [
+                    {"properties": {"c": "d"}, "id": 0, "default": true}
+                ]}}"#,
+                "a:b does not have a state for each combination of values",
+            ),
+            (
+                r#"{"a:b": {"properties": {"c": ["d", "e"]}, "states": [
+                    {"properties": {"c": "e"}, "id": 0, "default": true},
+                    {"properties": {"c": "d"}, "id": 1}
+                ]}}"#,
+                "a:b has an unexpected id 0",
+            ),
+            (
+                r#"{"a:b": {"states": [{"id": 0, "default": true}]},
+                    "a:c": {"states": [{"id": 0, "default": true}]}}"#,
+                "a:b overlaps a:c",
+            ),
+        ];
+
+        for (json, reason) in mappings {
+            assert_eq!(
+                ProtocolError::invalid_value("blocks report", reason),
+                BlockRegistry::from_report(json).unwrap_err()
+            );
+        }
+    }
+}
@@ -0,0 +1,614 @@
+use crate::coding::nbt::Compound;
+use std::io;
+use std::mem;
+
+/// A piece of formatted text, as it is sent to the client in JSON.
+///
+/// See https://wiki.vg/Chat for the format. Components are built with the constructors for
+/// their content and the methods for their style, e.g.
+/// `ChatComponent::text("Hello").color(Color::Gold).append(ChatComponent::text("!"))`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ChatComponent {
+    #[serde(flatten)]
+    pub content: Content,
+    #[serde(flatten)]
+    pub style: Style,
+    /// Components shown after this one, which inherit its style.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra: Vec<ChatComponent>,
+}
+
+/// What a component shows, which is resolved by the client except for plain text.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Content {
+    Text {
+        text: String,
+    },
+    /// A translation key of the client, e.g. `chat.type.text`, with the components filled into
+    /// its placeholders.
+    Translate {
+        translate: String,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        with: Vec<ChatComponent>,
+    },
+    Score {
+        score: Score,
+    },
+    /// An entity selector, e.g. `@p`, which is resolved to the names of the entities.
+    Selector {
+        selector: String,
+    },
+    /// The key the client has bound to a control, e.g. `key.inventory`.
+    Keybind {
+        keybind: String,
+    },
+}
+
+/// The score of an entity in an objective of the scoreboard.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Score {
+    /// The name of the player, or the UUID of the entity.
+    pub name: String,
+    pub objective: String,
+    /// Shown instead of the actual score, if given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// How a component is shown, everything that is not set is inherited from the parent.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Style {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub underlined: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strikethrough: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub obfuscated: Option<bool>,
+    /// Inserted into the chat input when the component is shift-clicked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insertion: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub click_event: Option<ClickEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hover_event: Option<HoverEvent>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Color {
+    Black,
+    DarkBlue,
+    DarkGreen,
+    DarkAqua,
+    DarkRed,
+    DarkPurple,
+    Gold,
+    Gray,
+    DarkGray,
+    Blue,
+    Green,
+    Aqua,
+    Red,
+    LightPurple,
+    Yellow,
+    White,
+    /// The default color of the client.
+    Reset,
+}
+
+impl Color {
+    const CODES: [(char, Color); 16] = [
+        ('0', Color::Black),
+        ('1', Color::DarkBlue),
+        ('2', Color::DarkGreen),
+        ('3', Color::DarkAqua),
+        ('4', Color::DarkRed),
+        ('5', Color::DarkPurple),
+        ('6', Color::Gold),
+        ('7', Color::Gray),
+        ('8', Color::DarkGray),
+        ('9', Color::Blue),
+        ('a', Color::Green),
+        ('b', Color::Aqua),
+        ('c', Color::Red),
+        ('d', Color::LightPurple),
+        ('e', Color::Yellow),
+        ('f', Color::White),
+    ];
+
+    /// Returns the color of a legacy formatting code, e.g. `c` for `§c`.
+    pub fn from_code(code: char) -> Option<Color> {
+        let code = code.to_ascii_lowercase();
+
+        Color::CODES
+            .iter()
+            .find(|(color_code, _)| *color_code == code)
+            .map(|(_, color)| *color)
+    }
+
+    /// Returns the legacy formatting code of the color, `r` for `Reset`.
+    pub fn code(self) -> char {
+        Color::CODES
+            .iter()
+            .find(|(_, color)| *color == self)
+            .map_or('r', |(code, _)| *code)
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "action", content = "value", rename_all = "snake_case")]
+pub enum ClickEvent {
+    OpenUrl(String),
+    /// Sends the command as the player, which needs to start with a slash.
+    RunCommand(String),
+    /// Replaces the chat input with the text.
+    SuggestCommand(String),
+    /// Turns to the page of a book.
+    ChangePage(u32),
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "action", content = "value")]
+pub enum HoverEvent {
+    #[serde(rename = "show_text")]
+    Text(Box<ChatComponent>),
+    /// The item as SNBT, e.g. `{id:"minecraft:stone",Count:1b}`.
+    #[serde(rename = "show_item")]
+    Item(String),
+    /// The entity as SNBT, with its `name`, `type` and `id`.
+    #[serde(rename = "show_entity")]
+    Entity(String),
+}
+
+impl HoverEvent {
+    pub fn show_text(text: ChatComponent) -> HoverEvent {
+        HoverEvent::Text(Box::new(text))
+    }
+
+    pub fn show_item(item: &Compound) -> HoverEvent {
+        HoverEvent::Item(item.to_string())
+    }
+
+    pub fn show_entity(entity: &Compound) -> HoverEvent {
+        HoverEvent::Entity(entity.to_string())
+    }
+}
+
+impl ChatComponent {
+    pub fn new(content: Content) -> ChatComponent {
+        ChatComponent {
+            content,
+            style: Style::default(),
+            extra: vec![],
+        }
+    }
+
+    pub fn text<S: Into<String>>(text: S) -> ChatComponent {
+        ChatComponent::new(Content::Text { text: text.into() })
+    }
+
+    pub fn translate<S: Into<String>>(key: S, with: Vec<ChatComponent>) -> ChatComponent {
+        ChatComponent::new(Content::Translate {
+            translate: key.into(),
+            with,
+        })
+    }
+
+    pub fn score<N: Into<String>, O: Into<String>>(name: N, objective: O) -> ChatComponent {
+        ChatComponent::new(Content::Score {
+            score: Score {
+                name: name.into(),
+                objective: objective.into(),
+                value: None,
+            },
+        })
+    }
+
+    pub fn selector<S: Into<String>>(selector: S) -> ChatComponent {
+        ChatComponent::new(Content::Selector {
+            selector: selector.into(),
+        })
+    }
+
+    pub fn keybind<S: Into<String>>(keybind: S) -> ChatComponent {
+        ChatComponent::new(Content::Keybind {
+            keybind: keybind.into(),
+        })
+    }
+
+    pub fn color(mut self, color: Color) -> ChatComponent {
+        self.style.color = Some(color);
+        self
+    }
+
+    pub fn bold(mut self, bold: bool) -> ChatComponent {
+        self.style.bold = Some(bold);
+        self
+    }
+
+    pub fn italic(mut self, italic: bool) -> ChatComponent {
+        self.style.italic = Some(italic);
+        self
+    }
+
+    pub fn underlined(mut self, underlined: bool) -> ChatComponent {
+        self.style.underlined = Some(underlined);
+        self
+    }
+
+    pub fn strikethrough(mut self, strikethrough: bool) -> ChatComponent {
+        self.style.strikethrough = Some(strikethrough);
+        self
+    }
+
+    pub fn obfuscated(mut self, obfuscated: bool) -> ChatComponent {
+        self.style.obfuscated = Some(obfuscated);
+        self
+    }
+
+    pub fn insertion<S: Into<String>>(mut self, insertion: S) -> ChatComponent {
+        self.style.insertion = Some(insertion.into());
+        self
+    }
+
+    pub fn on_click(mut self, event: ClickEvent) -> ChatComponent {
+        self.style.click_event = Some(event);
+        self
+    }
+
+    pub fn on_hover(mut self, event: HoverEvent) -> ChatComponent {
+        self.style.hover_event = Some(event);
+        self
+    }
+
+    /// Adds a component that is shown after this one.
+    pub fn append(mut self, child: ChatComponent) -> ChatComponent {
+        self.extra.push(child);
+        self
+    }
+
+    pub fn to_json(&self) -> io::Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Converts text with legacy formatting codes, e.g. `§cRed §lbold`, into components.
+    ///
+    /// Like in vanilla, a color code resets the formatting and `§r` resets everything.
+    /// Unknown codes are kept as they are.
+    pub fn from_legacy(legacy: &str) -> ChatComponent {
+        let mut components = vec![];
+        let mut text = String::new();
+        let mut style = Style::default();
+        let mut characters = legacy.chars().peekable();
+
+        while let Some(character) = characters.next() {
+            let code = match characters.peek() {
+                Some(&code) if character == '§' => code,
+                _ => {
+                    text.push(character);
+                    continue;
+                }
+            };
+
+            let next_style = match code.to_ascii_lowercase() {
+                'k' => Style {
+                    obfuscated: Some(true),
+                    ..style.clone()
+                },
+                'l' => Style {
+                    bold: Some(true),
+                    ..style.clone()
+                },
+                'm' => Style {
+                    strikethrough: Some(true),
+                    ..style.clone()
+                },
+                'n' => Style {
+                    underlined: Some(true),
+                    ..style.clone()
+                },
+                'o' => Style {
+                    italic: Some(true),
+                    ..style.clone()
+                },
+                'r' => Style::default(),
+                code => match Color::from_code(code) {
+                    Some(color) => Style {
+                        color: Some(color),
+                        ..Style::default()
+                    },
+                    None => {
+                        text.push(character);
+                        continue;
+                    }
+                },
+            };
+
+            characters.next();
+
+            if !text.is_empty() {
+                let mut component = ChatComponent::text(mem::take(&mut text));
+                component.style = style;
+                components.push(component);
+            }
+
+            style = next_style;
+        }
+
+        if !text.is_empty() {
+            let mut component = ChatComponent::text(text);
+            component.style = style;
+            components.push(component);
+        }
+
+        if components.len() == 1 {
+            return components.remove(0);
+        }
+
+        ChatComponent {
+            extra: components,
+            ..ChatComponent::text("")
+        }
+    }
+
+    /// Converts the components into text with legacy formatting codes, e.g. for clients
+    /// before 1.7.
+    ///
+    /// Only the colors and formatting survive, and only plain text can be shown as it is.
+    /// Translations, scores, selectors and keybinds are replaced by their key, value or
+    /// selector, as they are resolved by the client.
+    pub fn to_legacy(&self) -> String {
+        let mut legacy = String::new();
+        self.push_legacy(
+            LegacyFormat::default(),
+            &mut LegacyFormat::default(),
+            &mut legacy,
+        );
+
+        legacy
+    }
+
+    fn push_legacy(&self, parent: LegacyFormat, current: &mut LegacyFormat, legacy: &mut String) {
+        let format = parent.inherit(&self.style);
+
+        let text = match &self.content {
+            Content::Text { text } => text,
+            Content::Translate { translate, .. } => translate,
+            Content::Score { score } => score.value.as_ref().unwrap_or(&score.name),
+            Content::Selector { selector } => selector,
+            Content::Keybind { keybind } => keybind,
+        };
+
+        if !text.is_empty() {
+            if format != *current {
+                format.push_codes(*current, legacy);
+                *current = format;
+            }
+
+            legacy.push_str(text);
+        }
+
+        for child in &self.extra {
+            child.push_legacy(format, current, legacy);
+        }
+    }
+}
+
+/// The part of a style that can be expressed with legacy formatting codes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct LegacyFormat {
+    color: Option<Color>,
+    obfuscated: bool,
+    bold: bool,
+    strikethrough: bool,
+    underlined: bool,
+    italic: bool,
+}
+
+impl LegacyFormat {
+    fn inherit(self, style: &Style) -> LegacyFormat {
+        LegacyFormat {
+            color: style.color.or(self.color),
+            obfuscated: style.obfuscated.unwrap_or(self.obfuscated),
+            bold: style.bold.unwrap_or(self.bold),
+            strikethrough: style.strikethrough.unwrap_or(self.strikethrough),
+            underlined: style.underlined.unwrap_or(self.underlined),
+            italic: style.italic.unwrap_or(self.italic),
+        }
+    }
+
+    /// Pushes the codes to change from the current format to this one.
+    fn push_codes(self, current: LegacyFormat, legacy: &mut String) {
+        let current_formatting = current.formatting();
+        let formatting = self.formatting();
+
+        // formatting can only be added, otherwise it needs a color code to reset it first
+        let is_added = self.color == current.color
+            && current_formatting
+                .iter()
+                .zip(formatting.iter())
+                .all(|((current, _), (enabled, _))| !current || *enabled);
+
+        if !is_added {
+            legacy.push('§');
+            legacy.push(self.color.map_or('r', Color::code));
+        }
+
+        for (index, (enabled, code)) in formatting.iter().enumerate() {
+            if *enabled && !(is_added && current_formatting[index].0) {
+                legacy.push('§');
+                legacy.push(*code);
+            }
+        }
+    }
+
+    fn formatting(self) -> [(bool, char); 5] {
+        [
+            (self.obfuscated, 'k'),
+            (self.bold, 'l'),
+            (self.strikethrough, 'm'),
+            (self.underlined, 'n'),
+            (self.italic, 'o'),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChatComponent, ClickEvent, Color, HoverEvent};
+    use crate::coding::nbt::Compound;
+
+    #[test]
+    fn test_text_to_json() {
+        let mappings = vec![
+            ("", r#"{"text":""}"#),
+            ("Timed out", r#"{"text":"Timed out"}"#),
+            ("\"quoted\"", r#"{"text":"\"quoted\""}"#),
+        ];
+
+        for (text, expected) in mappings {
+            assert_eq!(expected, ChatComponent::text(text).to_json().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_components_to_json() {
+        let item: Compound = r#"{id:"minecraft:stone",Count:1b}"#.parse().unwrap();
+
+        let mappings = vec![
+            (
+                ChatComponent::translate(
+                    "chat.type.text",
+                    vec![ChatComponent::text("Steve"), ChatComponent::text("Hi")],
+                ),
+                r#"{"translate":"chat.type.text","with":[{"text":"Steve"},{"text":"Hi"}]}"#,
+            ),
+            (
+                ChatComponent::translate("multiplayer.disconnect.server_shutdown", vec![]),
+                r#"{"translate":"multiplayer.disconnect.server_shutdown"}"#,
+            ),
+            (
+                ChatComponent::score("Steve", "kills"),
+                r#"{"score":{"name":"Steve","objective":"kills"}}"#,
+            ),
+            (ChatComponent::selector("@p"), r#"{"selector":"@p"}"#),
+            (
+                ChatComponent::keybind("key.inventory"),
+                r#"{"keybind":"key.inventory"}"#,
+            ),
+            (
+                ChatComponent::text("Warning")
+                    .color(Color::DarkRed)
+                    .bold(true)
+                    .italic(false)
+                    .underlined(true)
+                    .strikethrough(true)
+                    .obfuscated(false),
+                r#"{"text":"Warning","color":"dark_red","bold":true,"italic":false,"underlined":true,"strikethrough":true,"obfuscated":false}"#,
+            ),
+            (
+                ChatComponent::text("Click me")
+                    .insertion("/help")
+                    .on_click(ClickEvent::RunCommand("/help".to_owned()))
+                    .on_hover(HoverEvent::show_text(
+                        ChatComponent::text("Shows the help").color(Color::Gray),
+                    )),
+                r#"{"text":"Click me","insertion":"/help","clickEvent":{"action":"run_command","value":"/help"},"hoverEvent":{"action":"show_text","value":{"text":"Shows the help","color":"gray"}}}"#,
+            ),
+            (
+                ChatComponent::text("Next page").on_click(ClickEvent::ChangePage(2)),
+                r#"{"text":"Next page","clickEvent":{"action":"change_page","value":2}}"#,
+            ),
+            (
+                ChatComponent::text("Stone").on_hover(HoverEvent::show_item(&item)),
+                r#"{"text":"Stone","hoverEvent":{"action":"show_item","value":"{id:\"minecraft:stone\",Count:1b}"}}"#,
+            ),
+            (
+                ChatComponent::text("Hello ")
+                    .color(Color::Gold)
+                    .append(ChatComponent::text("world").color(Color::Reset))
+                    .append(ChatComponent::keybind("key.jump")),
+                r#"{"text":"Hello ","color":"gold","extra":[{"text":"world","color":"reset"},{"keybind":"key.jump"}]}"#,
+            ),
+        ];
+
+        for (component, expected) in mappings {
+            assert_eq!(expected, component.to_json().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_from_legacy() {
+        let mappings = vec![
+            ("", ChatComponent::text("")),
+            ("Oxi", ChatComponent::text("Oxi")),
+            ("§aOxi", ChatComponent::text("Oxi").color(Color::Green)),
+            (
+                "§l§CBold? No, red",
+                ChatComponent::text("Bold? No, red").color(Color::Red),
+            ),
+            (
+                "A §nB§r C",
+                ChatComponent::text("")
+                    .append(ChatComponent::text("A "))
+                    .append(ChatComponent::text("B").underlined(true))
+                    .append(ChatComponent::text(" C")),
+            ),
+            (
+                "§6§lGold §obold italic",
+                ChatComponent::text("")
+                    .append(ChatComponent::text("Gold ").color(Color::Gold).bold(true))
+                    .append(
+                        ChatComponent::text("bold italic")
+                            .color(Color::Gold)
+                            .bold(true)
+                            .italic(true),
+                    ),
+            ),
+            // unknown and incomplete codes are text
+            ("§x§", ChatComponent::text("§x§")),
+        ];
+
+        for (legacy, expected) in mappings {
+            assert_eq!(expected, ChatComponent::from_legacy(legacy), "{}", legacy);
+        }
+    }
+
+    #[test]
+    fn test_to_legacy() {
+        let mappings = vec![
+            (ChatComponent::text("§aOxi"), "§aOxi"),
+            (ChatComponent::text("Oxi").color(Color::Green), "§aOxi"),
+            (
+                ChatComponent::text("Hello ")
+                    .color(Color::Gold)
+                    .bold(true)
+                    .append(ChatComponent::text("world").bold(false))
+                    .append(ChatComponent::text("!").color(Color::Reset)),
+                "§6§lHello §6world§r§l!",
+            ),
+            (
+                ChatComponent::text("")
+                    .append(ChatComponent::text("A ").italic(true))
+                    .append(ChatComponent::translate("key.jump", vec![]))
+                    .append(ChatComponent::score("Steve", "kills")),
+                "§oA §rkey.jumpSteve",
+            ),
+        ];
+
+        for (component, expected) in mappings {
+            assert_eq!(expected, component.to_legacy());
+        }
+
+        let legacy = "§6§lGold §obold italic§r plain §cred";
+        assert_eq!(legacy, ChatComponent::from_legacy(legacy).to_legacy());
+    }
+}
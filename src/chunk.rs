@@ -0,0 +1,583 @@
+//! Chunks are the columns of 16x256x16 blocks the world is sent and stored in.
+//!
+//! Each chunk consists of 16 sections of 16x16x16 blocks, which are encoded as described in
+//! https://wiki.vg/Chunk_Format for the Chunk Data packet.
+mod packed_array;
+
+pub use self::packed_array::PackedArray;
+
+use crate::coding::buffer::BufMut;
+use crate::coding::error::ProtocolError;
+use crate::coding::int::MinecraftInt;
+use crate::coding::long::Long;
+use crate::coding::nbt::Compound;
+use crate::coding::varint::Varint;
+use crate::coding::Encodeable;
+use crate::location::Location;
+use std::collections::HashMap;
+
+/// The id of a block state in the global palette, e.g. 9 for a grass block that is not snowy.
+pub type BlockState = u16;
+
+pub const AIR: BlockState = 0;
+
+/// The number of blocks along each axis of a section, and along x and z of a chunk.
+pub const SECTION_WIDTH: usize = 16;
+pub const SECTIONS_PER_CHUNK: usize = 16;
+pub const CHUNK_HEIGHT: usize = SECTION_WIDTH * SECTIONS_PER_CHUNK;
+pub const BLOCKS_PER_SECTION: usize = SECTION_WIDTH * SECTION_WIDTH * SECTION_WIDTH;
+
+/// Enough bits for every block state of 1.13.2, used if a section does not fit a palette.
+pub const GLOBAL_BITS_PER_BLOCK: u8 = 14;
+/// Sections with palettes use at least 4 and at most 8 bits per block.
+const MIN_BITS_PER_BLOCK: u8 = 4;
+const MAX_PALETTE_BITS_PER_BLOCK: u8 = 8;
+
+pub const MAX_LIGHT: u8 = 15;
+
+/// The biome ids of vanilla, of which only a few are used so far.
+pub type Biome = MinecraftInt;
+
+pub const PLAINS: Biome = 1;
+
+/// The biomes by their namespaced name, without the variants of 1.13.2 from id 129 on.
+const BIOMES: [(&str, Biome); 52] = [
+    ("minecraft:ocean", 0),
+    ("minecraft:plains", 1),
+    ("minecraft:desert", 2),
+    ("minecraft:mountains", 3),
+    ("minecraft:forest", 4),
+    ("minecraft:taiga", 5),
+    ("minecraft:swamp", 6),
+    ("minecraft:river", 7),
+    ("minecraft:nether", 8),
+    ("minecraft:the_end", 9),
+    ("minecraft:frozen_ocean", 10),
+    ("minecraft:frozen_river", 11),
+    ("minecraft:snowy_tundra", 12),
+    ("minecraft:snowy_mountains", 13),
+    ("minecraft:mushroom_fields", 14),
+    ("minecraft:mushroom_field_shore", 15),
+    ("minecraft:beach", 16),
+    ("minecraft:desert_hills", 17),
+    ("minecraft:wooded_hills", 18),
+    ("minecraft:taiga_hills", 19),
+    ("minecraft:mountain_edge", 20),
+    ("minecraft:jungle", 21),
+    ("minecraft:jungle_hills", 22),
+    ("minecraft:jungle_edge", 23),
+    ("minecraft:deep_ocean", 24),
+    ("minecraft:stone_shore", 25),
+    ("minecraft:snowy_beach", 26),
+    ("minecraft:birch_forest", 27),
+    ("minecraft:birch_forest_hills", 28),
+    ("minecraft:dark_forest", 29),
+    ("minecraft:snowy_taiga", 30),
+    ("minecraft:snowy_taiga_hills", 31),
+    ("minecraft:giant_tree_taiga", 32),
+    ("minecraft:giant_tree_taiga_hills", 33),
+    ("minecraft:wooded_mountains", 34),
+    ("minecraft:savanna", 35),
+    ("minecraft:savanna_plateau", 36),
+    ("minecraft:badlands", 37),
+    ("minecraft:wooded_badlands_plateau", 38),
+    ("minecraft:badlands_plateau", 39),
+    ("minecraft:small_end_islands", 40),
+    ("minecraft:end_midlands", 41),
+    ("minecraft:end_highlands", 42),
+    ("minecraft:end_barrens", 43),
+    ("minecraft:warm_ocean", 44),
+    ("minecraft:lukewarm_ocean", 45),
+    ("minecraft:cold_ocean", 46),
+    ("minecraft:deep_warm_ocean", 47),
+    ("minecraft:deep_lukewarm_ocean", 48),
+    ("minecraft:deep_cold_ocean", 49),
+    ("minecraft:deep_frozen_ocean", 50),
+    ("minecraft:the_void", 127),
+];
+
+/// Returns the id of the biome with the given name, which defaults to the `minecraft`
+/// namespace.
+pub fn biome_by_name(name: &str) -> Option<Biome> {
+    let name = if name.contains(':') {
+        name.to_owned()
+    } else {
+        format!("minecraft:{}", name)
+    };
+
+    BIOMES
+        .iter()
+        .find(|(candidate, _)| *candidate == name)
+        .map(|&(_, biome)| biome)
+}
+
+/// Light levels of 0 to 15 of every block of a section, stored in 4 bits each.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NibbleArray {
+    bytes: Vec<u8>,
+}
+
+impl NibbleArray {
+    /// Creates an array with the same level for every block.
+    pub fn filled(level: u8) -> NibbleArray {
+        debug_assert!(level <= MAX_LIGHT);
+
+        NibbleArray {
+            bytes: vec![level << 4 | level; BLOCKS_PER_SECTION / 2],
+        }
+    }
+
+    /// Wraps the levels of a section read from a file, two in each byte.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<NibbleArray, ProtocolError> {
+        if bytes.len() != BLOCKS_PER_SECTION / 2 {
+            return Err(ProtocolError::invalid_value(
+                "light",
+                format!(
+                    "of {} bytes, expected {}",
+                    bytes.len(),
+                    BLOCKS_PER_SECTION / 2
+                ),
+            ));
+        }
+
+        Ok(NibbleArray { bytes })
+    }
+
+    #[cfg(test)]
+    pub fn get(&self, index: usize) -> u8 {
+        (self.bytes[index / 2] >> (index % 2 * 4)) & 0x0F
+    }
+
+    pub fn set(&mut self, index: usize, level: u8) {
+        debug_assert!(level <= MAX_LIGHT);
+
+        let shift = index % 2 * 4;
+        let byte = &mut self.bytes[index / 2];
+
+        *byte = (*byte & !(0x0F << shift)) | (level << shift);
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// 16x16x16 blocks with their light.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkSection {
+    blocks: Vec<BlockState>,
+    pub block_light: NibbleArray,
+    /// Only sent in dimensions with a sky, i.e. the overworld.
+    pub sky_light: NibbleArray,
+}
+
+impl Default for ChunkSection {
+    /// A section full of air, lit by the sky.
+    fn default() -> ChunkSection {
+        ChunkSection {
+            blocks: vec![AIR; BLOCKS_PER_SECTION],
+            block_light: NibbleArray::filled(0),
+            sky_light: NibbleArray::filled(MAX_LIGHT),
+        }
+    }
+}
+
+impl ChunkSection {
+    /// Creates a section lit by the sky, with the blocks in the order of `index`.
+    pub fn from_blocks(blocks: Vec<BlockState>) -> ChunkSection {
+        assert_eq!(BLOCKS_PER_SECTION, blocks.len());
+
+        ChunkSection {
+            blocks,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the index of a block in the arrays, with the coordinates relative to the section.
+    pub fn index(x: usize, y: usize, z: usize) -> usize {
+        debug_assert!(x < SECTION_WIDTH && y < SECTION_WIDTH && z < SECTION_WIDTH);
+
+        (y * SECTION_WIDTH + z) * SECTION_WIDTH + x
+    }
+
+    pub fn block(&self, x: usize, y: usize, z: usize) -> BlockState {
+        self.blocks[ChunkSection::index(x, y, z)]
+    }
+
+    pub fn set_block(&mut self, x: usize, y: usize, z: usize, block: BlockState) {
+        self.blocks[ChunkSection::index(x, y, z)] = block;
+    }
+
+    /// Whether the section contains nothing but air, so it does not need to be sent.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.iter().all(|&block| block == AIR)
+    }
+
+    /// Returns the distinct block states in the order they appear in.
+    fn palette(&self) -> Vec<BlockState> {
+        let mut palette = vec![];
+
+        for &block in &self.blocks {
+            if !palette.contains(&block) {
+                palette.push(block);
+
+                // too many for a palette, the global one is used instead
+                if palette.len() > 1 << MAX_PALETTE_BITS_PER_BLOCK {
+                    break;
+                }
+            }
+        }
+
+        palette
+    }
+
+    /// Encodes the section like the Chunk Data packet expects it.
+    ///
+    /// Sections with at most 256 distinct block states map them to indices into a palette
+    /// that is sent along, others use the ids of the global palette.
+    pub fn encode_into<B: BufMut>(&self, buffer: &mut B, has_sky_light: bool) {
+        let palette = self.palette();
+        let palette_bits = (usize::BITS - (palette.len() - 1).leading_zeros()) as u8;
+
+        let mut blocks;
+
+        if palette_bits <= MAX_PALETTE_BITS_PER_BLOCK {
+            let indices: HashMap<BlockState, u64> = palette
+                .iter()
+                .enumerate()
+                .map(|(index, &block)| (block, index as u64))
+                .collect();
+
+            blocks = PackedArray::new(palette_bits.max(MIN_BITS_PER_BLOCK), BLOCKS_PER_SECTION);
+            for (index, block) in self.blocks.iter().enumerate() {
+                blocks.set(index, indices[block]);
+            }
+
+            buffer.put_u8(blocks.bits());
+            Varint(palette.len() as i32).encode_into(buffer);
+            for &block in &palette {
+                Varint(i32::from(block)).encode_into(buffer);
+            }
+        } else {
+            blocks = PackedArray::new(GLOBAL_BITS_PER_BLOCK, BLOCKS_PER_SECTION);
+            for (index, &block) in self.blocks.iter().enumerate() {
+                blocks.set(index, u64::from(block));
+            }
+
+            // the global palette is not sent
+            buffer.put_u8(blocks.bits());
+        }
+
+        Varint(blocks.longs().len() as i32).encode_into(buffer);
+        for &long in blocks.longs() {
+            (long as Long).encode_into(buffer);
+        }
+
+        buffer.put_slice(self.block_light.as_bytes());
+
+        if has_sky_light {
+            buffer.put_slice(self.sky_light.as_bytes());
+        }
+    }
+}
+
+/// A column of 16 sections, from y 0 to 255.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    pub x: i32,
+    pub z: i32,
+    sections: Vec<Option<ChunkSection>>,
+    /// the biome of every column, indexed by `z * 16 + x`
+    biomes: Vec<Biome>,
+    /// e.g. chests or signs, with their position in `x`, `y` and `z`
+    pub block_entities: Vec<Compound>,
+}
+
+impl Chunk {
+    /// Creates a chunk full of air in the plains.
+    pub fn new(x: i32, z: i32) -> Chunk {
+        Chunk {
+            x,
+            z,
+            sections: vec![None; SECTIONS_PER_CHUNK],
+            biomes: vec![PLAINS; SECTION_WIDTH * SECTION_WIDTH],
+            block_entities: vec![],
+        }
+    }
+
+    /// Returns the coordinates of the chunk containing the location.
+    pub fn coordinates_of(location: &Location) -> (i32, i32) {
+        (location.x >> 4, location.z >> 4)
+    }
+
+    pub fn section(&self, index: usize) -> Option<&ChunkSection> {
+        self.sections[index].as_ref()
+    }
+
+    /// Returns the section, creating an empty one if it does not exist yet.
+    pub fn section_mut(&mut self, index: usize) -> &mut ChunkSection {
+        self.sections[index].get_or_insert_with(ChunkSection::default)
+    }
+
+    /// Returns the block at coordinates relative to the chunk.
+    pub fn block(&self, x: usize, y: usize, z: usize) -> BlockState {
+        match self.section(y / SECTION_WIDTH) {
+            Some(section) => section.block(x, y % SECTION_WIDTH, z),
+            None => AIR,
+        }
+    }
+
+    pub fn set_block(&mut self, x: usize, y: usize, z: usize, block: BlockState) {
+        self.section_mut(y / SECTION_WIDTH)
+            .set_block(x, y % SECTION_WIDTH, z, block);
+    }
+
+    #[cfg(test)]
+    pub fn biome(&self, x: usize, z: usize) -> Biome {
+        self.biomes[z * SECTION_WIDTH + x]
+    }
+
+    pub fn set_biome(&mut self, x: usize, z: usize, biome: Biome) {
+        self.biomes[z * SECTION_WIDTH + x] = biome;
+    }
+
+    /// Lights every block from the sky down to the highest block of its column.
+    ///
+    /// Every block but air is treated as opaque, so this is only a rough approximation of
+    /// vanilla's light.
+    pub fn compute_sky_light(&mut self) {
+        for x in 0..SECTION_WIDTH {
+            for z in 0..SECTION_WIDTH {
+                let mut level = MAX_LIGHT;
+
+                for y in (0..CHUNK_HEIGHT).rev() {
+                    if self.block(x, y, z) != AIR {
+                        level = 0;
+                    }
+
+                    if let Some(section) = &mut self.sections[y / SECTION_WIDTH] {
+                        let index = ChunkSection::index(x, y % SECTION_WIDTH, z);
+                        section.sky_light.set(index, level);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the bit mask of the sections that are sent, bit 0 being the lowest section.
+    pub fn primary_bit_mask(&self) -> i32 {
+        self.sections
+            .iter()
+            .enumerate()
+            .filter(|(_, section)| section.as_ref().is_some_and(|section| !section.is_empty()))
+            .fold(0, |mask, (index, _)| mask | 1 << index)
+    }
+
+    /// Encodes the sections of the bit mask and the biomes, as sent for a full chunk.
+    pub fn encode_data_into<B: BufMut>(&self, buffer: &mut B, has_sky_light: bool) {
+        let mask = self.primary_bit_mask();
+
+        for (index, section) in self.sections.iter().enumerate() {
+            if let Some(section) = section {
+                if mask & 1 << index != 0 {
+                    section.encode_into(buffer, has_sky_light);
+                }
+            }
+        }
+
+        for biome in &self.biomes {
+            biome.encode_into(buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{biome_by_name, Chunk, ChunkSection, NibbleArray, AIR, BLOCKS_PER_SECTION, PLAINS};
+    use crate::coding::buffer::ReadCursor;
+    use crate::coding::error::ProtocolError;
+    use crate::coding::long::Long;
+    use crate::coding::unsigned_byte::MinecraftUnsignedByte;
+    use crate::coding::varint::Varint;
+    use crate::coding::Decodeable;
+    use crate::location::Location;
+
+    const STONE: u16 = 1;
+    const GRASS_BLOCK: u16 = 9;
+
+    /// Reads the header of an encoded section, returning the bits per block, the palette
+    /// and the number of longs.
+    fn read_section_header(cursor: &mut ReadCursor) -> (u8, Vec<i32>, usize) {
+        let bits: MinecraftUnsignedByte = cursor.decode().unwrap();
+
+        let mut palette = vec![];
+        if bits <= 8 {
+            let length: Varint = cursor.decode().unwrap();
+            for _ in 0..length.0 {
+                let Varint(block) = cursor.decode().unwrap();
+                palette.push(block);
+            }
+        }
+
+        let Varint(longs) = cursor.decode().unwrap();
+
+        (bits, palette, longs as usize)
+    }
+
+    #[test]
+    fn test_nibble_array() {
+        let mut array = NibbleArray::filled(15);
+        array.set(0, 1);
+        array.set(3, 0);
+
+        assert_eq!(1, array.get(0));
+        assert_eq!(15, array.get(1));
+        assert_eq!(0, array.get(3));
+        // the first block is in the low nibble
+        assert_eq!(&[0xF1, 0x0F], &array.as_bytes()[..2]);
+
+        assert_eq!(
+            Ok(array.clone()),
+            NibbleArray::from_bytes(array.as_bytes().to_vec())
+        );
+        assert_eq!(
+            Err(ProtocolError::invalid_value(
+                "light",
+                "of 3 bytes, expected 2048"
+            )),
+            NibbleArray::from_bytes(vec![0; 3])
+        );
+    }
+
+    #[test]
+    fn test_encode_section_with_palette() {
+        let mut section = ChunkSection::default();
+        section.set_block(1, 0, 0, STONE);
+        section.set_block(0, 1, 0, GRASS_BLOCK);
+
+        let mut buffer = vec![];
+        section.encode_into(&mut buffer, true);
+
+        let mut cursor = ReadCursor::new(&buffer);
+        let (bits, palette, longs) = read_section_header(&mut cursor);
+
+        assert_eq!(4, bits);
+        assert_eq!(vec![0, 1, 9], palette);
+        assert_eq!(256, longs);
+
+        // air, stone, then air up to the first block of the next layer
+        let first: Long = cursor.decode().unwrap();
+        assert_eq!(0x10, first);
+
+        let mut second_layer = cursor.read_remaining()[(256 / 16 - 1) * 8..].to_vec();
+        second_layer.truncate(8);
+        assert_eq!(vec![0, 0, 0, 0, 0, 0, 0, 0x02], second_layer);
+
+        // block light and sky light follow the longs
+        assert_eq!(
+            buffer.len(),
+            1 + 4 + 2 + 256 * 8 + BLOCKS_PER_SECTION / 2 * 2
+        );
+
+        let mut without_sky_light = vec![];
+        section.encode_into(&mut without_sky_light, false);
+        assert_eq!(
+            buffer.len() - BLOCKS_PER_SECTION / 2,
+            without_sky_light.len()
+        );
+    }
+
+    #[test]
+    fn test_encode_section_with_global_palette() {
+        let mut section = ChunkSection::default();
+        for block in 0..300 {
+            section.set_block(block % 16, block / 256, block / 16 % 16, block as u16);
+        }
+
+        let mut buffer = vec![];
+        section.encode_into(&mut buffer, false);
+
+        let mut cursor = ReadCursor::new(&buffer);
+        let (bits, palette, longs) = read_section_header(&mut cursor);
+
+        assert_eq!(14, bits);
+        assert!(palette.is_empty());
+        assert_eq!(14 * 64, longs);
+
+        // the ids 0 to 3 in 14 bits each, and the lower 8 bits of the 4 that spans two longs
+        let first: Long = cursor.decode().unwrap();
+        assert_eq!(4 << 56 | 3 << 42 | 2 << 28 | 1 << 14, first);
+    }
+
+    #[test]
+    fn test_chunk_blocks_and_sections() {
+        let mut chunk = Chunk::new(1, -1);
+        assert_eq!(0, chunk.primary_bit_mask());
+        assert_eq!(AIR, chunk.block(0, 100, 0));
+
+        chunk.set_block(15, 255, 15, STONE);
+        chunk.set_block(0, 17, 0, STONE);
+        // sections that only contain air are not sent
+        chunk.set_block(0, 40, 0, STONE);
+        chunk.set_block(0, 40, 0, AIR);
+
+        assert_eq!(STONE, chunk.block(15, 255, 15));
+        assert_eq!(STONE, chunk.section(1).unwrap().block(0, 1, 0));
+        assert_eq!(1 << 15 | 1 << 1, chunk.primary_bit_mask());
+        assert_eq!(PLAINS, chunk.biome(3, 4));
+
+        let mut data = vec![];
+        chunk.encode_data_into(&mut data, true);
+
+        let section_length = 1 + 3 + 2 + 256 * 8 + 4096;
+        assert_eq!(2 * section_length + 256 * 4, data.len());
+        // the biomes are at the end
+        assert_eq!(vec![0, 0, 0, 1], data[data.len() - 4..].to_vec());
+    }
+
+    #[test]
+    fn test_compute_sky_light() {
+        let mut chunk = Chunk::new(0, 0);
+        chunk.set_block(0, 3, 0, GRASS_BLOCK);
+        chunk.set_block(0, 20, 1, STONE);
+        chunk.compute_sky_light();
+
+        let section = chunk.section(0).unwrap();
+        assert_eq!(15, section.sky_light.get(ChunkSection::index(0, 4, 0)));
+        assert_eq!(0, section.sky_light.get(ChunkSection::index(0, 3, 0)));
+        assert_eq!(0, section.sky_light.get(ChunkSection::index(0, 0, 0)));
+        // shadowed by the stone in the section above
+        assert_eq!(0, section.sky_light.get(ChunkSection::index(0, 15, 1)));
+        assert_eq!(15, section.sky_light.get(ChunkSection::index(1, 0, 1)));
+    }
+
+    #[test]
+    fn test_chunk_coordinates() {
+        let mappings = vec![
+            ((0, 0), (0, 0)),
+            ((15, 16), (0, 1)),
+            ((-1, -16), (-1, -1)),
+            ((-17, 33), (-2, 2)),
+        ];
+
+        for ((x, z), expected) in mappings {
+            assert_eq!(expected, Chunk::coordinates_of(&Location { x, y: 64, z }));
+        }
+    }
+
+    #[test]
+    fn test_biome_by_name() {
+        let mappings = vec![
+            ("minecraft:ocean", Some(0)),
+            ("plains", Some(PLAINS)),
+            ("minecraft:badlands_plateau", Some(39)),
+            ("minecraft:deep_frozen_ocean", Some(50)),
+            ("the_void", Some(127)),
+            ("minecraft:unknown", None),
+        ];
+
+        for (name, biome) in mappings {
+            assert_eq!(biome, biome_by_name(name), "{}", name);
+        }
+    }
+}
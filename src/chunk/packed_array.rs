@@ -0,0 +1,184 @@
+use crate::coding::error::ProtocolError;
+
+/// Values of a fixed number of bits packed into longs, as used for the blocks of a section.
+///
+/// Values start at the least significant bit and may span two longs, like vanilla does
+/// since 1.13.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackedArray {
+    bits: u8,
+    length: usize,
+    longs: Vec<u64>,
+}
+
+impl PackedArray {
+    /// Creates an array of `length` zeros with `bits` bits each, which has to be 1 to 32.
+    pub fn new(bits: u8, length: usize) -> PackedArray {
+        assert!((1..=32).contains(&bits), "{} bits per value", bits);
+
+        PackedArray {
+            bits,
+            length,
+            longs: vec![0; PackedArray::long_count(bits, length)],
+        }
+    }
+
+    /// Wraps longs read from the network or a file, which have to be exactly enough for
+    /// `length` values.
+    pub fn from_longs(
+        bits: u8,
+        length: usize,
+        longs: Vec<u64>,
+    ) -> Result<PackedArray, ProtocolError> {
+        if !(1..=32).contains(&bits) {
+            return Err(ProtocolError::invalid_value("bits per value", bits));
+        }
+
+        let expected = PackedArray::long_count(bits, length);
+
+        if longs.len() != expected {
+            return Err(ProtocolError::invalid_value(
+                "packed array",
+                format!("of {} longs, expected {}", longs.len(), expected),
+            ));
+        }
+
+        Ok(PackedArray {
+            bits,
+            length,
+            longs,
+        })
+    }
+
+    fn long_count(bits: u8, length: usize) -> usize {
+        (length * usize::from(bits)).div_ceil(64)
+    }
+
+    pub fn bits(&self) -> u8 {
+        self.bits
+    }
+
+    pub fn longs(&self) -> &[u64] {
+        &self.longs
+    }
+
+    fn mask(&self) -> u64 {
+        (1 << self.bits) - 1
+    }
+
+    pub fn get(&self, index: usize) -> u64 {
+        assert!(index < self.length, "index {} of {}", index, self.length);
+
+        let bit = index * usize::from(self.bits);
+        let (long, offset) = (bit / 64, bit % 64);
+
+        let mut value = self.longs[long] >> offset;
+
+        if offset + usize::from(self.bits) > 64 {
+            value |= self.longs[long + 1] << (64 - offset);
+        }
+
+        value & self.mask()
+    }
+
+    pub fn set(&mut self, index: usize, value: u64) {
+        assert!(index < self.length, "index {} of {}", index, self.length);
+        debug_assert!(
+            value <= self.mask(),
+            "{} has more than {} bits",
+            value,
+            self.bits
+        );
+
+        let mask = self.mask();
+        let bit = index * usize::from(self.bits);
+        let (long, offset) = (bit / 64, bit % 64);
+
+        self.longs[long] = (self.longs[long] & !(mask << offset)) | (value << offset);
+
+        if offset + usize::from(self.bits) > 64 {
+            let shift = 64 - offset;
+
+            self.longs[long + 1] = (self.longs[long + 1] & !(mask >> shift)) | (value >> shift);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..self.length).map(move |index| self.get(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PackedArray;
+    use crate::coding::error::ProtocolError;
+
+    /// The example of https://wiki.vg/Chunk_Format, where values span two longs.
+    fn example() -> (Vec<u64>, Vec<u64>) {
+        (
+            vec![0x7020_8631_4841_8841, 0x8B10_18A7_260F_68C8],
+            vec![
+                1, 2, 2, 3, 4, 4, 5, 6, 6, 4, 8, 0, 7, 4, 3, 13, 15, 16, 9, 14, 10, 12, 0, 2,
+            ],
+        )
+    }
+
+    #[test]
+    fn test_get_values() {
+        let (longs, values) = example();
+        let array = PackedArray::from_longs(5, values.len(), longs).unwrap();
+
+        assert_eq!(values, array.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_set_values() {
+        let (longs, values) = example();
+        let mut array = PackedArray::new(5, values.len());
+
+        for (index, value) in values.iter().enumerate() {
+            array.set(index, *value);
+        }
+
+        // the example has garbage in the 8 bits after the last value
+        assert_eq!(longs[0], array.longs()[0]);
+        assert_eq!(longs[1] & ((1 << 56) - 1), array.longs()[1]);
+
+        // overwriting clears the old bits, also in the second long
+        array.set(12, 0b11111);
+        array.set(12, 0b00100);
+        assert_eq!(4, array.get(12));
+        assert_eq!(3, array.get(14));
+    }
+
+    #[test]
+    fn test_from_invalid_longs() {
+        assert_eq!(
+            Err(ProtocolError::invalid_value(
+                "packed array",
+                "of 1 longs, expected 2"
+            )),
+            PackedArray::from_longs(5, 24, vec![0])
+        );
+        assert_eq!(
+            Err(ProtocolError::invalid_value("bits per value", 0)),
+            PackedArray::from_longs(0, 24, vec![])
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(bits in 1u8..=32, values: Vec<u32>) {
+            let mut array = PackedArray::new(bits, values.len());
+            let mask = (1u64 << bits) - 1;
+
+            for (index, value) in values.iter().enumerate() {
+                array.set(index, u64::from(*value) & mask);
+            }
+
+            for (index, value) in values.iter().enumerate() {
+                prop_assert_eq!(u64::from(*value) & mask, array.get(index));
+            }
+        }
+    }
+}
@@ -3,9 +3,10 @@ use crate::coding::signed_byte::MinecraftSignedByte;
 use crate::coding::string::MinecraftString;
 use crate::coding::unsigned_byte::MinecraftUnsignedByte;
 
-#[derive(Debug)]
+#[derive(Debug, Decodeable)]
 pub struct ClientSettings {
-    /// guarrented to always be lowercase
+    /// e.g. `en_us`, lowercased by the player once received
+    #[max_length = 16]
     pub locale: MinecraftString,
     pub render_distance: MinecraftSignedByte,
     pub chat_mode: ChatMode,
@@ -14,15 +15,125 @@ pub struct ClientSettings {
     pub main_hand: MainHand,
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Decodeable)]
+#[varint]
 pub enum ChatMode {
     Enabled = 0x00,
     CommandsOnly = 0x01,
     Hidden = 0x02,
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Decodeable)]
+#[varint]
 pub enum MainHand {
     Left = 0x00,
     Right = 0x01,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ChatMode, ClientSettings, MainHand};
+    use crate::coding::buffer::ReadCursor;
+    use crate::coding::error::ProtocolError;
+    use crate::coding::Decodeable;
+
+    #[test]
+    fn test_decoding_client_settings() {
+        let mut data = vec![5, 101, 110, 95, 85, 83]; // "en_US"
+        data.extend(vec![0x0C, 0x01, 0x01, 0x7F, 0x00]);
+
+        let client_settings: ClientSettings = ReadCursor::new(&data).decode().unwrap();
+
+        assert_eq!("en_US", client_settings.locale);
+        assert_eq!(12, client_settings.render_distance);
+        assert_eq!(ChatMode::CommandsOnly, client_settings.chat_mode);
+        assert!(client_settings.chat_colors_enabled);
+        assert_eq!(0x7F, client_settings.skin_parts_displayed);
+        assert_eq!(MainHand::Left, client_settings.main_hand);
+    }
+
+    #[test]
+    fn test_decoding_client_settings_with_long_locale() {
+        let mut data = vec![17];
+        data.extend(b"en_us_and_longer_");
+        data.extend(vec![0x0C, 0x01, 0x01, 0x7F, 0x00]);
+
+        let actual: Result<ClientSettings, ProtocolError> = ReadCursor::new(&data).decode();
+
+        assert_eq!(
+            Err(ProtocolError::TooLong {
+                type_name: "String",
+                length: 17,
+                max_length: 16,
+            }),
+            actual.map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_decoding_chat_mode() {
+        let mappings: Vec<(ChatMode, Vec<u8>)> = vec![
+            (ChatMode::Enabled, vec![0x00]),
+            (ChatMode::CommandsOnly, vec![0x01, 0x02]),
+            (ChatMode::Hidden, vec![0x02]),
+        ];
+
+        for mapping in mappings {
+            let actual: ChatMode = ReadCursor::new(&mapping.1).decode().unwrap();
+
+            assert_eq!(mapping.0, actual);
+        }
+    }
+
+    #[test]
+    fn test_decoding_chat_mode_err() {
+        let mappings: Vec<Vec<u8>> = vec![vec![0x42], vec![0x07, 0x03]];
+
+        for mapping in mappings {
+            let expected = ProtocolError::invalid_value("ChatMode", mapping[0]);
+
+            let actual: Result<ChatMode, ProtocolError> = ReadCursor::new(&mapping).decode();
+
+            assert_eq!(Err(expected), actual);
+        }
+    }
+
+    #[test]
+    fn test_decoding_chat_mode_err_empty() {
+        let actual: Result<ChatMode, ProtocolError> = ReadCursor::new(&[]).decode();
+
+        assert_eq!(Err(ProtocolError::unexpected_eof("Varint")), actual);
+    }
+
+    #[test]
+    fn test_decoding_main_hand() {
+        let mappings: Vec<(MainHand, Vec<u8>)> =
+            vec![(MainHand::Left, vec![0x00]), (MainHand::Right, vec![0x01])];
+
+        for mapping in mappings {
+            let actual: MainHand = ReadCursor::new(&mapping.1).decode().unwrap();
+
+            assert_eq!(mapping.0, actual);
+        }
+    }
+
+    #[test]
+    fn test_decoding_main_hand_err() {
+        let mappings: Vec<Vec<u8>> = vec![vec![0x42], vec![0x05]];
+
+        for mapping in mappings {
+            let expected = ProtocolError::invalid_value("MainHand", mapping[0]);
+
+            let actual: Result<MainHand, ProtocolError> = ReadCursor::new(&mapping).decode();
+
+            assert_eq!(Err(expected), actual);
+        }
+    }
+
+    #[test]
+    fn test_decoding_main_hand_err_empty() {
+        let actual: Result<MainHand, ProtocolError> = ReadCursor::new(&[]).decode();
+
+        assert_eq!(Err(ProtocolError::unexpected_eof("Varint")), actual);
+    }
+}
@@ -1,5 +1,38 @@
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Decodeable)]
+#[varint]
 pub enum ClientStatus {
     PerformRespawn = 0x00,
-    RequestStats = 0x01
-}
\ No newline at end of file
+    RequestStats = 0x01,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClientStatus;
+    use crate::coding::buffer::ReadCursor;
+    use crate::coding::error::ProtocolError;
+    use crate::coding::Decodeable;
+
+    #[test]
+    fn test_decoding() {
+        let mappings: Vec<(ClientStatus, Vec<u8>)> = vec![
+            (ClientStatus::PerformRespawn, vec![0x00]),
+            (ClientStatus::RequestStats, vec![0x01]),
+        ];
+
+        for mapping in mappings {
+            let actual: ClientStatus = ReadCursor::new(&mapping.1).decode().unwrap();
+
+            assert_eq!(mapping.0, actual);
+        }
+    }
+
+    #[test]
+    fn test_decoding_err() {
+        let actual: Result<ClientStatus, ProtocolError> = ReadCursor::new(&[0x02]).decode();
+
+        assert_eq!(
+            "Invalid value 2 for ClientStatus!",
+            actual.unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use super::error::ProtocolError;
+use std::io::{self, Read};
+use std::mem;
+
+/// A buffer values are encoded into, without allocating for every single value.
+pub trait BufMut {
+    fn put_u8(&mut self, byte: u8);
+
+    fn put_slice(&mut self, bytes: &[u8]);
+}
+
+impl BufMut for Vec<u8> {
+    #[inline]
+    fn put_u8(&mut self, byte: u8) {
+        self.push(byte);
+    }
+
+    #[inline]
+    fn put_slice(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+}
+
+/// Writes into a slice of known size, advancing it. Panics if the slice is too small.
+impl BufMut for &mut [u8] {
+    #[inline]
+    fn put_u8(&mut self, byte: u8) {
+        self.put_slice(&[byte]);
+    }
+
+    #[inline]
+    fn put_slice(&mut self, bytes: &[u8]) {
+        let (head, tail) = mem::take(self).split_at_mut(bytes.len());
+        head.copy_from_slice(bytes);
+        *self = tail;
+    }
+}
+
+/// Reads the bytes values are decoded from, out of any reader.
+///
+/// Running out of bytes is reported as `ProtocolError::UnexpectedEof`, other errors of the
+/// reader are passed through.
+pub trait ReadBytes: Read {
+    /// Reads a single byte, `type_name` describes what is read in case it is missing.
+    fn read_u8(&mut self, type_name: &'static str) -> Result<u8, ProtocolError> {
+        let [byte] = self.read_fixed(type_name)?;
+
+        Ok(byte)
+    }
+
+    /// Reads a fixed number of bytes, e.g. to decode big-endian numbers.
+    fn read_fixed<const N: usize>(
+        &mut self,
+        type_name: &'static str,
+    ) -> Result<[u8; N], ProtocolError> {
+        let mut array = [0; N];
+        self.read_exact(&mut array)
+            .map_err(|error| ProtocolError::from_io(error, type_name))?;
+
+        Ok(array)
+    }
+
+    /// Reads the next `length` bytes.
+    ///
+    /// The buffer grows with the bytes actually read, so a bogus length sent by the client
+    /// does not allocate that much memory up front.
+    fn read_bytes(
+        &mut self,
+        length: usize,
+        type_name: &'static str,
+    ) -> Result<Vec<u8>, ProtocolError> {
+        let mut bytes = Vec::new();
+        (&mut *self).take(length as u64).read_to_end(&mut bytes)?;
+
+        if bytes.len() < length {
+            return Err(ProtocolError::unexpected_eof(type_name));
+        }
+
+        Ok(bytes)
+    }
+}
+
+impl<R: Read + ?Sized> ReadBytes for R {}
+
+/// A cursor over contiguous bytes in memory, e.g. the data of a received packet.
+#[derive(Debug)]
+pub struct ReadCursor<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ReadCursor<'a> {
+    pub fn new(bytes: &'a [u8]) -> ReadCursor<'a> {
+        ReadCursor { bytes }
+    }
+
+    /// Returns how many bytes are left to read.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Borrows all bytes that are left.
+    pub fn read_remaining(&mut self) -> &'a [u8] {
+        mem::take(&mut self.bytes)
+    }
+}
+
+impl Read for ReadCursor<'_> {
+    #[inline]
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.bytes.read(buffer)
+    }
+
+    /// Unlike the implementation for slices, nothing is consumed if there are not enough bytes.
+    #[inline]
+    fn read_exact(&mut self, buffer: &mut [u8]) -> io::Result<()> {
+        if self.bytes.len() < buffer.len() {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        let (head, tail) = self.bytes.split_at(buffer.len());
+        buffer.copy_from_slice(head);
+        self.bytes = tail;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BufMut, ReadBytes, ReadCursor};
+    use crate::coding::error::ProtocolError;
+
+    #[test]
+    fn test_read_cursor() {
+        let bytes = [1, 2, 3, 4, 5, 6];
+        let mut cursor = ReadCursor::new(&bytes);
+
+        assert_eq!(Ok(1), cursor.read_u8("byte"));
+        assert_eq!(Ok(vec![2, 3]), cursor.read_bytes(2, "bytes"));
+        assert_eq!(Ok([4, 5]), cursor.read_fixed::<2>("array"));
+        assert_eq!(1, cursor.remaining());
+
+        // nothing is consumed if there are not enough bytes
+        assert_eq!(
+            Err(ProtocolError::unexpected_eof("array")),
+            cursor.read_fixed::<2>("array")
+        );
+        assert_eq!(&[6], cursor.read_remaining());
+
+        assert!(cursor.is_empty());
+        assert_eq!(
+            Err(ProtocolError::unexpected_eof("byte")),
+            cursor.read_u8("byte")
+        );
+    }
+
+    #[test]
+    fn test_read_bytes_from_any_reader() {
+        let mut reader: &[u8] = &[1, 2, 3];
+
+        assert_eq!(Ok(vec![1, 2]), reader.read_bytes(2, "bytes"));
+        assert_eq!(
+            Err(ProtocolError::unexpected_eof("bytes")),
+            reader.read_bytes(usize::MAX, "bytes")
+        );
+    }
+
+    #[test]
+    fn test_write_into_slice() {
+        let mut bytes = [0; 4];
+
+        let mut buffer = &mut bytes[1..];
+        buffer.put_u8(1);
+        buffer.put_slice(&[2, 3]);
+        assert!(buffer.is_empty());
+
+        assert_eq!([0, 1, 2, 3], bytes);
+    }
+}
@@ -0,0 +1,66 @@
+use super::buffer::{BufMut, ReadBytes};
+use super::error::ProtocolError;
+use super::varint::Varint;
+use super::{Decodeable, Encodeable};
+use std::io::Read;
+
+/// An array of bytes which is prefixed with its length as a varint.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ByteArray(pub Vec<u8>);
+
+impl<R: Read> Decodeable<ByteArray, ProtocolError> for R {
+    fn decode(&mut self) -> Result<ByteArray, ProtocolError> {
+        let length: Varint = self.decode()?;
+
+        if length.0 < 0 {
+            return Err(ProtocolError::invalid_value("byte array length", length));
+        }
+
+        Ok(ByteArray(self.read_bytes(length.0 as usize, "byte array")?))
+    }
+}
+
+impl Encodeable for ByteArray {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        Varint(self.0.len() as i32).encode_into(buffer);
+        buffer.put_slice(&self.0);
+    }
+
+    fn byte_length(&self) -> usize {
+        Varint(self.0.len() as i32).encoded_length() + self.0.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ByteArray;
+    use crate::coding::buffer::ReadCursor;
+    use crate::coding::{Decodeable, Encodeable};
+
+    #[test]
+    fn test_byte_array() {
+        let mappings: Vec<(ByteArray, Vec<u8>)> = vec![
+            (ByteArray(vec![]), vec![0]),
+            (ByteArray(vec![1, 2, 3]), vec![3, 1, 2, 3]),
+            (ByteArray(vec![0xFF; 128]), {
+                let mut bytes = vec![0x80, 0x01];
+                bytes.extend(vec![0xFF; 128]);
+                bytes
+            }),
+        ];
+
+        for (byte_array, bytes) in mappings {
+            assert_eq!(bytes.clone(), byte_array.encode());
+
+            let actual: ByteArray = ReadCursor::new(&bytes).decode().unwrap();
+            assert_eq!(byte_array, actual);
+        }
+    }
+
+    #[test]
+    fn test_decode_byte_array_longer_than_data() {
+        let actual: Result<ByteArray, _> = ReadCursor::new(&[4, 1, 2, 3]).decode();
+
+        assert!(actual.is_err());
+    }
+}
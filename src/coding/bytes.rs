@@ -0,0 +1,35 @@
+use super::buffer::BufMut;
+use super::error::ProtocolError;
+use super::{Decodeable, Encodeable};
+use std::io::Read;
+
+/// Raw bytes without a length prefix, which make up the rest of a packet.
+impl Encodeable for [u8] {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        buffer.put_slice(self);
+    }
+
+    fn byte_length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl Encodeable for Vec<u8> {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        buffer.put_slice(self);
+    }
+
+    fn byte_length(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Reads everything until the end of the reader, which is supposed to hold a single packet.
+impl<R: Read> Decodeable<Vec<u8>, ProtocolError> for R {
+    fn decode(&mut self) -> Result<Vec<u8>, ProtocolError> {
+        let mut bytes = Vec::new();
+        self.read_to_end(&mut bytes)?;
+
+        Ok(bytes)
+    }
+}
@@ -1,64 +1,76 @@
+use super::buffer::{BufMut, ReadBytes};
+use super::error::ProtocolError;
 use super::{Decodeable, Encodeable};
-use std::collections::VecDeque;
-use std::f64;
-use std::io;
+use std::io::Read;
 
 pub type MinecraftDouble = f64;
 
-impl Decodeable<MinecraftDouble, io::Error> for VecDeque<u8> {
-    fn decode(&mut self) -> Result<MinecraftDouble, io::Error> {
-        let mut temp: u64 = 0;
-
-        #[inline(always)]
-        fn get_byte_or_fail(vector: &mut VecDeque<u8>) -> Result<u8, io::Error> {
-            let value = vector.pop_front();
+impl<R: Read> Decodeable<MinecraftDouble, ProtocolError> for R {
+    fn decode(&mut self) -> Result<MinecraftDouble, ProtocolError> {
+        Ok(f64::from_be_bytes(self.read_fixed("double")?))
+    }
+}
 
-            if value.is_some() {
-                Ok(value.unwrap())
-            } else {
-                Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "Not enough bytes to decode a MinecraftDouble(f64)!",
-                ))
-            }
-        };
+impl Encodeable for MinecraftDouble {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        buffer.put_slice(&self.to_be_bytes());
+    }
 
-        for _ in 1..=3 {
-            let byte = u64::from(get_byte_or_fail(self)?);
-            temp += byte;
-            temp <<= 8;
-        }
+    fn byte_length(&self) -> usize {
+        8
+    }
+}
 
-        // add remaining byte without the shift
-        let byte = u64::from(get_byte_or_fail(self)?);
-        temp += byte;
+#[cfg(test)]
+mod tests {
+    use super::MinecraftDouble;
+    use super::{Decodeable, Encodeable};
+    use crate::coding::buffer::ReadCursor;
+    use crate::coding::error::ProtocolError;
 
-        let result: MinecraftDouble = f64::from_bits(temp);
-        Ok(result)
+    fn mappings() -> Vec<(MinecraftDouble, Vec<u8>)> {
+        vec![
+            (100f64, vec![0x40, 0x59, 0, 0, 0, 0, 0, 0]),
+            (65f64, vec![0x40, 0x50, 0x40, 0, 0, 0, 0, 0]),
+            (-10f64, vec![0xC0, 0x24, 0, 0, 0, 0, 0, 0]),
+            (0.5f64, vec![0x3F, 0xE0, 0, 0, 0, 0, 0, 0]),
+            (0f64, vec![0, 0, 0, 0, 0, 0, 0, 0]),
+        ]
     }
-}
 
-impl Encodeable for MinecraftDouble {
-    fn encode(&self) -> VecDeque<u8> {
-        let mut result: VecDeque<u8> = VecDeque::with_capacity(8);
-        let mut value = f64::to_bits(*self);
+    #[test]
+    fn test_read_double_on_vec() {
+        for mapping in mappings() {
+            let actual: MinecraftDouble = ReadCursor::new(&mapping.1).decode().unwrap();
 
-        for _ in 1..=7 {
-            let byte = (value & 0b1111_1111) as u8;
-            value >>= 8;
-            result.push_front(byte);
+            assert_eq!(mapping.0, actual);
         }
+    }
+
+    #[test]
+    fn test_write_double_to_vec() {
+        for mapping in mappings() {
+            assert_eq!(mapping.1, mapping.0.encode());
+        }
+    }
 
-        // add remaining byte without shifting
-        let byte = (value & 0b1111_1111) as u8;
+    #[test]
+    fn test_read_double_too_short() {
+        let actual: Result<MinecraftDouble, ProtocolError> =
+            ReadCursor::new(&[0x40, 0x59, 0, 0]).decode();
 
-        result.push_front(byte);
-        result
+        assert_eq!(Err(ProtocolError::unexpected_eof("double")), actual);
     }
 
-    fn byte_length(&self) -> u8 {
-        8
+    proptest! {
+        #[test]
+        fn test_round_trip(value: MinecraftDouble) {
+            let encoded = value.encode();
+            let actual: MinecraftDouble = ReadCursor::new(&encoded).decode().unwrap();
+
+            prop_assert_eq!(value.to_be_bytes().to_vec(), encoded);
+            // compare the bits, as NaN is not equal to itself
+            prop_assert_eq!(value.to_bits(), actual.to_bits());
+        }
     }
 }
-
-// TODO: Add tests with valid values.
@@ -0,0 +1,220 @@
+use crate::connection::ConnectionState;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Describes why data sent by the client could not be decoded.
+///
+/// Decoders return this instead of panicking, as the data comes straight from the network.
+#[derive(Debug)]
+pub enum ProtocolError {
+    /// The data ended before a complete value could be read.
+    UnexpectedEof { type_name: &'static str },
+    /// A value was read completely, but is not allowed.
+    InvalidValue {
+        type_name: &'static str,
+        value: String,
+    },
+    /// A value is longer than allowed.
+    TooLong {
+        type_name: &'static str,
+        length: usize,
+        max_length: usize,
+    },
+    /// The client sent a packet that is not valid in the state of the connection.
+    UnexpectedPacket {
+        state: ConnectionState,
+        packet_id: i32,
+    },
+    /// Reading from the underlying reader failed, e.g. because the connection was reset.
+    Io(io::Error),
+}
+
+impl ProtocolError {
+    pub fn unexpected_eof(type_name: &'static str) -> ProtocolError {
+        ProtocolError::UnexpectedEof { type_name }
+    }
+
+    pub fn invalid_value<V: fmt::Display>(type_name: &'static str, value: V) -> ProtocolError {
+        ProtocolError::InvalidValue {
+            type_name,
+            value: value.to_string(),
+        }
+    }
+
+    /// Wraps an error of the reader a value of type `type_name` was read from.
+    pub fn from_io(error: io::Error, type_name: &'static str) -> ProtocolError {
+        match error.kind() {
+            io::ErrorKind::UnexpectedEof => ProtocolError::unexpected_eof(type_name),
+            _ => ProtocolError::Io(error),
+        }
+    }
+}
+
+impl PartialEq for ProtocolError {
+    fn eq(&self, other: &ProtocolError) -> bool {
+        match (self, other) {
+            (
+                ProtocolError::UnexpectedEof { type_name },
+                ProtocolError::UnexpectedEof { type_name: other },
+            ) => type_name == other,
+            (
+                ProtocolError::InvalidValue { type_name, value },
+                ProtocolError::InvalidValue {
+                    type_name: other_type_name,
+                    value: other_value,
+                },
+            ) => type_name == other_type_name && value == other_value,
+            (
+                ProtocolError::TooLong {
+                    type_name,
+                    length,
+                    max_length,
+                },
+                ProtocolError::TooLong {
+                    type_name: other_type_name,
+                    length: other_length,
+                    max_length: other_max_length,
+                },
+            ) => {
+                type_name == other_type_name
+                    && length == other_length
+                    && max_length == other_max_length
+            }
+            (
+                ProtocolError::UnexpectedPacket { state, packet_id },
+                ProtocolError::UnexpectedPacket {
+                    state: other_state,
+                    packet_id: other_packet_id,
+                },
+            ) => state == other_state && packet_id == other_packet_id,
+            // io errors can not be compared, so their kind has to do
+            (ProtocolError::Io(error), ProtocolError::Io(other)) => error.kind() == other.kind(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProtocolError::UnexpectedEof { type_name } => {
+                write!(f, "Not enough bytes to decode a {}!", type_name)
+            }
+            ProtocolError::InvalidValue { type_name, value } => {
+                write!(f, "Invalid value {} for {}!", value, type_name)
+            }
+            ProtocolError::TooLong {
+                type_name,
+                length,
+                max_length,
+            } => write!(
+                f,
+                "{} of length {} exceeds the maximum length of {}!",
+                type_name, length, max_length
+            ),
+            ProtocolError::UnexpectedPacket { state, packet_id } => write!(
+                f,
+                "Packet {:#X} is not expected in state {:?}.",
+                packet_id, state
+            ),
+            ProtocolError::Io(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for ProtocolError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProtocolError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ProtocolError {
+    fn from(error: io::Error) -> ProtocolError {
+        ProtocolError::Io(error)
+    }
+}
+
+impl From<ProtocolError> for io::Error {
+    fn from(error: ProtocolError) -> io::Error {
+        let kind = match error {
+            ProtocolError::Io(error) => return error,
+            // the client closed the connection, or sent a truncated packet
+            ProtocolError::UnexpectedEof { .. } => io::ErrorKind::UnexpectedEof,
+            ProtocolError::TooLong { .. } => io::ErrorKind::InvalidInput,
+            _ => io::ErrorKind::InvalidData,
+        };
+
+        io::Error::new(kind, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProtocolError;
+    use crate::connection::ConnectionState;
+    use std::io;
+
+    #[test]
+    fn test_convert_to_io_error() {
+        let mappings = vec![
+            (
+                ProtocolError::unexpected_eof("Varint"),
+                io::ErrorKind::UnexpectedEof,
+                "Not enough bytes to decode a Varint!",
+            ),
+            (
+                ProtocolError::invalid_value("boolean", 2),
+                io::ErrorKind::InvalidData,
+                "Invalid value 2 for boolean!",
+            ),
+            (
+                ProtocolError::TooLong {
+                    type_name: "String",
+                    length: 17,
+                    max_length: 16,
+                },
+                io::ErrorKind::InvalidInput,
+                "String of length 17 exceeds the maximum length of 16!",
+            ),
+            (
+                ProtocolError::UnexpectedPacket {
+                    state: ConnectionState::Status,
+                    packet_id: 0x05,
+                },
+                io::ErrorKind::InvalidData,
+                "Packet 0x5 is not expected in state Status.",
+            ),
+            (
+                ProtocolError::Io(io::Error::new(
+                    io::ErrorKind::ConnectionReset,
+                    "Connection reset by peer",
+                )),
+                io::ErrorKind::ConnectionReset,
+                "Connection reset by peer",
+            ),
+        ];
+
+        for (error, kind, message) in mappings {
+            let error: io::Error = error.into();
+
+            assert_eq!(kind, error.kind());
+            assert_eq!(message, error.to_string());
+        }
+    }
+
+    #[test]
+    fn test_convert_from_io_error() {
+        assert_eq!(
+            ProtocolError::unexpected_eof("long"),
+            ProtocolError::from_io(io::ErrorKind::UnexpectedEof.into(), "long")
+        );
+        assert_eq!(
+            ProtocolError::Io(io::ErrorKind::TimedOut.into()),
+            ProtocolError::from_io(io::ErrorKind::TimedOut.into(), "long")
+        );
+    }
+}
@@ -1,63 +1,22 @@
+use super::buffer::{BufMut, ReadBytes};
+use super::error::ProtocolError;
 use super::{Decodeable, Encodeable};
-use std::collections::VecDeque;
-use std::f32;
-use std::io;
-use std::u32;
+use std::io::Read;
 
 pub type MinecraftFloat = f32;
 
-impl Decodeable<MinecraftFloat, io::Error> for VecDeque<u8> {
-    fn decode(&mut self) -> Result<MinecraftFloat, io::Error> {
-        let mut temp: u32 = 0;
-
-        #[inline(always)]
-        fn get_byte_or_fail(vector: &mut VecDeque<u8>) -> Result<u8, io::Error> {
-            let value = vector.pop_front();
-
-            if value.is_some() {
-                Ok(value.unwrap())
-            } else {
-                Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "Not enough bytes to decode a MinecraftFloat(f32)!",
-                ))
-            }
-        };
-
-        for _ in 1..=3 {
-            let byte = u32::from(get_byte_or_fail(self)?);
-            temp += byte;
-            temp <<= 8;
-        }
-
-        // add remaining byte without the shift
-        let byte = u32::from(get_byte_or_fail(self)?);
-        temp += byte;
-
-        let result: MinecraftFloat = f32::from_bits(temp);
-        Ok(result)
+impl<R: Read> Decodeable<MinecraftFloat, ProtocolError> for R {
+    fn decode(&mut self) -> Result<MinecraftFloat, ProtocolError> {
+        Ok(f32::from_be_bytes(self.read_fixed("float")?))
     }
 }
 
 impl Encodeable for MinecraftFloat {
-    fn encode(&self) -> VecDeque<u8> {
-        let mut result: VecDeque<u8> = VecDeque::with_capacity(4);
-        let mut value = f32::to_bits(*self);
-
-        for _ in 1..=3 {
-            let byte = (value & 0b1111_1111) as u8;
-            value >>= 8;
-            result.push_front(byte);
-        }
-
-        // add remaining byte without shifting
-        let byte = (value & 0b1111_1111) as u8;
-
-        result.push_front(byte);
-        result
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        buffer.put_slice(&self.to_be_bytes());
     }
 
-    fn byte_length(&self) -> u8 {
+    fn byte_length(&self) -> usize {
         4
     }
 }
@@ -66,7 +25,7 @@ impl Encodeable for MinecraftFloat {
 mod tests {
     use super::MinecraftFloat;
     use super::{Decodeable, Encodeable};
-    use std::collections::VecDeque;
+    use crate::coding::buffer::ReadCursor;
 
     #[test]
     fn test_read_float_on_vec() {
@@ -78,7 +37,7 @@ mod tests {
         ];
 
         for mapping in mappings {
-            let actual: MinecraftFloat = VecDeque::from(mapping.1).decode().unwrap();
+            let actual: MinecraftFloat = ReadCursor::new(&mapping.1).decode().unwrap();
 
             assert_eq!(mapping.0, actual);
         }
@@ -93,7 +52,19 @@ mod tests {
         ];
 
         for mapping in mappings {
-            assert_eq!(VecDeque::from(mapping.1), mapping.0.encode());
+            assert_eq!(mapping.1, mapping.0.encode());
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(value: MinecraftFloat) {
+            let encoded = value.encode();
+            let actual: MinecraftFloat = ReadCursor::new(&encoded).decode().unwrap();
+
+            prop_assert_eq!(value.to_be_bytes().to_vec(), encoded);
+            // compare the bits, as NaN is not equal to itself
+            prop_assert_eq!(value.to_bits(), actual.to_bits());
         }
     }
 }
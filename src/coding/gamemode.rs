@@ -1,24 +1,30 @@
-use super::Encodeable;
-use std::collections::VecDeque;
-
+#[derive(Debug, Clone, Copy, PartialEq, Encodeable)]
+#[repr(u8)]
 pub enum Gamemode {
     Survival = 0x00,
     Creative = 0x01,
     Adventure = 0x02,
+    Spectator = 0x03,
     Hardcore = 0x08,
 }
 
-impl Encodeable for Gamemode {
-    fn encode(&self) -> VecDeque<u8> {
-        VecDeque::from(vec![match self {
-            Gamemode::Survival => 0x00,
-            Gamemode::Creative => 0x01,
-            Gamemode::Adventure => 0x02,
-            Gamemode::Hardcore => 0x08,
-        }])
-    }
+#[cfg(test)]
+mod tests {
+    use super::Gamemode;
+    use crate::coding::Encodeable;
+
+    #[test]
+    fn test_encoding() {
+        let mappings: Vec<(Gamemode, Vec<u8>)> = vec![
+            (Gamemode::Survival, vec![0x00]),
+            (Gamemode::Creative, vec![0x01]),
+            (Gamemode::Adventure, vec![0x02]),
+            (Gamemode::Spectator, vec![0x03]),
+            (Gamemode::Hardcore, vec![0x08]),
+        ];
 
-    fn byte_length(&self) -> u8 {
-        1
+        for mapping in mappings {
+            assert_eq!(mapping.1, mapping.0.encode());
+        }
     }
 }
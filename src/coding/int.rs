@@ -1,27 +1,74 @@
-use super::Encodeable;
-use std::collections::VecDeque;
+use super::buffer::{BufMut, ReadBytes};
+use super::error::ProtocolError;
+use super::{Decodeable, Encodeable};
+use std::io::Read;
 
 pub type MinecraftInt = i32;
 
+impl<R: Read> Decodeable<MinecraftInt, ProtocolError> for R {
+    fn decode(&mut self) -> Result<MinecraftInt, ProtocolError> {
+        Ok(i32::from_be_bytes(self.read_fixed("int")?))
+    }
+}
+
 impl Encodeable for MinecraftInt {
-    fn encode(&self) -> VecDeque<u8> {
-        // an int is 4 bytes long
-        let mut result: VecDeque<u8> = VecDeque::with_capacity(4);
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        buffer.put_slice(&self.to_be_bytes());
+    }
 
-        let mut value = *self;
+    fn byte_length(&self) -> usize {
+        4
+    }
+}
 
-        for _ in 1..=4 {
-            let temp = value & 0b1111;
+#[cfg(test)]
+mod tests {
+    use super::{Decodeable, Encodeable, MinecraftInt};
+    use crate::coding::buffer::ReadCursor;
+    use crate::coding::error::ProtocolError;
+
+    fn mappings() -> Vec<(MinecraftInt, Vec<u8>)> {
+        vec![
+            (0, vec![0, 0, 0, 0]),
+            (1, vec![0, 0, 0, 1]),
+            (-1, vec![0xFF, 0xFF, 0xFF, 0xFF]),
+            (25565, vec![0, 0, 0x63, 0xDD]),
+            (-0x8000_0000, vec![0x80, 0, 0, 0]), // lowest possible value
+            (0x7FFF_FFFF, vec![0x7F, 0xFF, 0xFF, 0xFF]), // highest possible value
+        ]
+    }
 
-            value >>= 4;
+    #[test]
+    fn test_decoding() {
+        for mapping in mappings() {
+            let actual: MinecraftInt = ReadCursor::new(&mapping.1).decode().unwrap();
 
-            result.push_back(temp as u8);
+            assert_eq!(mapping.0, actual);
         }
+    }
+
+    #[test]
+    fn test_decoding_err() {
+        let actual: Result<MinecraftInt, ProtocolError> = ReadCursor::new(&[0, 0, 0]).decode();
 
-        result
+        assert_eq!(Err(ProtocolError::unexpected_eof("int")), actual);
     }
 
-    fn byte_length(&self) -> u8 {
-        4
+    #[test]
+    fn test_encoding() {
+        for mapping in mappings() {
+            assert_eq!(mapping.1, mapping.0.encode());
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(value: MinecraftInt) {
+            let encoded = value.encode();
+            let actual: MinecraftInt = ReadCursor::new(&encoded).decode().unwrap();
+
+            prop_assert_eq!(value.to_be_bytes().to_vec(), encoded);
+            prop_assert_eq!(value, actual);
+        }
     }
 }
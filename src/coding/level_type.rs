@@ -1,7 +1,8 @@
+use super::buffer::BufMut;
 use super::Encodeable;
-use std::collections::VecDeque;
 use std::fmt;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum LevelType {
     Default,
     Flat,
@@ -25,11 +26,11 @@ impl fmt::Display for LevelType {
 }
 
 impl Encodeable for LevelType {
-    fn encode(&self) -> VecDeque<u8> {
-        self.to_string().encode()
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        self.to_string().encode_into(buffer)
     }
 
-    fn byte_length(&self) -> u8 {
+    fn byte_length(&self) -> usize {
         11 + 7 // Max string size + Varint length of the string (prefixed when encoded)
     }
 }
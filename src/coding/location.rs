@@ -1,39 +1,31 @@
+use super::buffer::BufMut;
+use super::error::ProtocolError;
 use super::{Decodeable, Encodeable};
 use crate::coding::long::Long;
 use crate::location::Location;
-use std::collections::VecDeque;
 use std::i64;
-use std::io;
+use std::io::Read;
 
-impl Decodeable<Location, io::Error> for VecDeque<u8> {
-    fn decode(&mut self) -> Result<Location, io::Error> {
+impl<R: Read> Decodeable<Location, ProtocolError> for R {
+    fn decode(&mut self) -> Result<Location, ProtocolError> {
         let raw_value: Long = self.decode()?;
 
         let x = raw_value >> 38;
 
         if x < -33_554_432 || x > 33_554_431 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "x-coordinate not in valid range!",
-            ));
+            return Err(ProtocolError::invalid_value("x-coordinate", x));
         }
 
         let y = (raw_value >> 26) & 0xFFF;
 
         if y < -2048 || y > 2047 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "y-coordinate not in valid range!",
-            ));
+            return Err(ProtocolError::invalid_value("y-coordinate", y));
         }
 
         let z = raw_value << 38 >> 38;
 
         if z < -33_554_432 || z > 33_554_431 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "z-coordinate not in valid range!",
-            ));
+            return Err(ProtocolError::invalid_value("z-coordinate", z));
         }
 
         Ok(Location {
@@ -45,15 +37,15 @@ impl Decodeable<Location, io::Error> for VecDeque<u8> {
 }
 
 impl Encodeable for Location {
-    fn encode(&self) -> VecDeque<u8> {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
         let value: Long = (i64::from(self.x & 0x03FF_FFFF) << 38)
             | (i64::from(self.y & 0xFFF) << 26)
             | i64::from(self.z & 0x03FF_FFFF);
 
-        value.encode()
+        value.encode_into(buffer)
     }
 
-    fn byte_length(&self) -> u8 {
+    fn byte_length(&self) -> usize {
         8
     }
 }
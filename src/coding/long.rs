@@ -1,62 +1,22 @@
+use super::buffer::{BufMut, ReadBytes};
+use super::error::ProtocolError;
 use super::{Decodeable, Encodeable};
-use std::collections::VecDeque;
-use std::i64;
-use std::io;
+use std::io::Read;
 
 pub type Long = i64;
 
-impl Decodeable<Long, io::Error> for VecDeque<u8> {
-    fn decode(&mut self) -> Result<Long, io::Error> {
-        let mut temp: u64 = 0;
-
-        #[inline(always)]
-        fn get_byte_or_fail(vector: &mut VecDeque<u8>) -> Result<u8, io::Error> {
-            let value = vector.pop_front();
-
-            if value.is_some() {
-                Ok(value.unwrap())
-            } else {
-                Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "Not enough bytes to decode a long(i64)!",
-                ))
-            }
-        };
-
-        for _ in 1..=7 {
-            let byte = u64::from(get_byte_or_fail(self)?);
-            temp += byte;
-            temp <<= 8;
-        }
-
-        // add remaining byte without the shift
-        let byte = u64::from(get_byte_or_fail(self)?);
-        temp += byte;
-
-        let result: Long = temp as Long;
-        Ok(result)
+impl<R: Read> Decodeable<Long, ProtocolError> for R {
+    fn decode(&mut self) -> Result<Long, ProtocolError> {
+        Ok(i64::from_be_bytes(self.read_fixed("long")?))
     }
 }
 
 impl Encodeable for Long {
-    fn encode(&self) -> VecDeque<u8> {
-        let mut result: VecDeque<u8> = VecDeque::with_capacity(8);
-        // max long value: -9223372036854775808 / +9223372036854775807
-        let mut value = *self as u64;
-
-        for _ in 1..=7 {
-            let byte = (value & 0b1111_1111) as u8;
-            value >>= 8;
-            result.push_front(byte);
-        }
-
-        // add remaining byte without shifting
-        let byte = (value & 0b1111_1111) as u8;
-        result.push_front(byte);
-        result
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        buffer.put_slice(&self.to_be_bytes());
     }
 
-    fn byte_length(&self) -> u8 {
+    fn byte_length(&self) -> usize {
         8
     }
 }
@@ -65,7 +25,7 @@ impl Encodeable for Long {
 mod tests {
     use super::Long;
     use super::{Decodeable, Encodeable};
-    use std::collections::VecDeque;
+    use crate::coding::buffer::ReadCursor;
 
     #[test]
     fn test_read_long_on_vec() {
@@ -83,7 +43,7 @@ mod tests {
         ];
 
         for mapping in mappings {
-            let actual: Long = VecDeque::from(mapping.1).decode().unwrap();
+            let actual: Long = ReadCursor::new(&mapping.1).decode().unwrap();
 
             assert_eq!(mapping.0, actual);
         }
@@ -104,7 +64,18 @@ mod tests {
         ];
 
         for mapping in mappings {
-            assert_eq!(VecDeque::from(mapping.1), mapping.0.encode());
+            assert_eq!(mapping.1, mapping.0.encode());
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(value: Long) {
+            let encoded = value.encode();
+            let actual: Long = ReadCursor::new(&encoded).decode().unwrap();
+
+            prop_assert_eq!(value.to_be_bytes().to_vec(), encoded);
+            prop_assert_eq!(value, actual);
         }
     }
 
@@ -113,14 +84,14 @@ mod tests {
     fn bench_decoding(b: &mut Bencher) {
         b.iter(|| {
             let input: Vec<u8> = vec![128, 0, 0, 0, 0, 0, 0, 0];
-            black_box::<Long>(VecDeque::from(input).decode().unwrap());
+            black_box::<Long>(ReadCursor::new(&input).decode().unwrap());
         });
     }
     #[bench]
     fn bench_encoding(b: &mut Bencher) {
         b.iter(|| {
             let input: Long = -0x7FFFFFFFFFFFFFFF;
-            black_box::<VecDeque<u8>>(input.encode());
+            black_box::<Vec<u8>>(input.encode());
         });
     }
 }
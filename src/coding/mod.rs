@@ -1,27 +1,29 @@
-use std::collections::VecDeque;
+use self::buffer::BufMut;
 
 pub mod boolean;
-pub mod chat_mode;
-pub mod client_settings;
-pub mod client_status;
-pub mod difficulty;
-pub mod dimension;
+pub mod buffer;
+pub mod byte_array;
+pub mod bytes;
 pub mod double;
+pub mod error;
 pub mod float;
 pub mod gamemode;
 pub mod int;
 pub mod level_type;
 pub mod location;
 pub mod long;
-pub mod main_hand;
+pub mod nbt;
 pub mod plugin_message;
 pub mod short;
 pub mod signed_byte;
 pub mod string;
 pub mod unsigned_byte;
+pub mod uuid;
 pub mod varint;
-pub mod vec_deque;
+pub mod varlong;
 
+/// Implemented for the sources values are decoded from. The decoders of this module work with
+/// any `std::io::Read`, so they are shared by sockets, files and bytes in memory.
 pub trait Decodeable<T, E> {
     /// Decodes from the Minecraft format into the type T, optionally returning
     /// an error
@@ -29,9 +31,17 @@ pub trait Decodeable<T, E> {
 }
 
 pub trait Encodeable {
-    /// Encodes from the Rust type into the Minecraft format(an array of bytes)
-    fn encode(&self) -> VecDeque<u8>;
+    /// Encodes from the Rust type into the Minecraft format, appending it to the buffer.
+    fn encode_into<B: BufMut>(&self, buffer: &mut B);
 
     /// Returns a guess on how much space is needed to encode it to improve allocations.
-    fn byte_length(&self) -> u8;
+    fn byte_length(&self) -> usize;
+
+    /// Encodes into a new buffer, which is allocated once.
+    fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(self.byte_length());
+        self.encode_into(&mut buffer);
+
+        buffer
+    }
 }
@@ -0,0 +1,734 @@
+//! Named Binary Tag, the format Minecraft stores structured data in, e.g. in `level.dat`,
+//! chunks and item slots.
+//!
+//! See https://wiki.vg/NBT for the binary format. Tags can be printed and parsed as SNBT for
+//! debugging, and converted from and to Rust types with `ser::to_tag` and `de::from_tag`.
+pub mod de;
+// the server does not save anything yet, only the tests round-trip structs through tags
+#[allow(dead_code)]
+pub mod ser;
+mod snbt;
+
+use super::buffer::{BufMut, ReadBytes};
+use super::double::MinecraftDouble;
+use super::error::ProtocolError;
+use super::float::MinecraftFloat;
+use super::int::MinecraftInt;
+use super::long::Long;
+use super::short::{Short, UnsignedShort};
+use super::signed_byte::MinecraftSignedByte;
+use super::{Decodeable, Encodeable};
+use flate2::read::{GzDecoder, ZlibDecoder};
+use std::collections::HashSet;
+use std::io::Read;
+use std::vec;
+
+/// How deep compounds and lists may be nested, as enforced by vanilla.
+pub const MAX_DEPTH: usize = 512;
+
+const END_ID: u8 = 0;
+const COMPOUND_ID: u8 = 10;
+
+/// A single value of an NBT document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    /// All elements of a list have to be of the same type.
+    List(Vec<Tag>),
+    Compound(Compound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    /// Returns the id the type of this tag is prefixed with in the binary format.
+    pub fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(_) => 9,
+            Tag::Compound(_) => COMPOUND_ID,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12,
+        }
+    }
+
+    /// Returns the name of the type of this tag, as used by the documentation of the format.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Tag::Byte(_) => "TAG_Byte",
+            Tag::Short(_) => "TAG_Short",
+            Tag::Int(_) => "TAG_Int",
+            Tag::Long(_) => "TAG_Long",
+            Tag::Float(_) => "TAG_Float",
+            Tag::Double(_) => "TAG_Double",
+            Tag::ByteArray(_) => "TAG_Byte_Array",
+            Tag::String(_) => "TAG_String",
+            Tag::List(_) => "TAG_List",
+            Tag::Compound(_) => "TAG_Compound",
+            Tag::IntArray(_) => "TAG_Int_Array",
+            Tag::LongArray(_) => "TAG_Long_Array",
+        }
+    }
+
+    fn write_payload<B: BufMut>(&self, buffer: &mut B) {
+        match self {
+            Tag::Byte(value) => value.encode_into(buffer),
+            Tag::Short(value) => value.encode_into(buffer),
+            Tag::Int(value) => value.encode_into(buffer),
+            Tag::Long(value) => value.encode_into(buffer),
+            Tag::Float(value) => value.encode_into(buffer),
+            Tag::Double(value) => value.encode_into(buffer),
+            Tag::ByteArray(values) => {
+                (values.len() as MinecraftInt).encode_into(buffer);
+
+                for value in values {
+                    value.encode_into(buffer);
+                }
+            }
+            Tag::String(value) => write_string(value, buffer),
+            Tag::List(tags) => {
+                let element_id = tags.first().map_or(END_ID, Tag::id);
+                debug_assert!(
+                    tags.iter().all(|tag| tag.id() == element_id),
+                    "All elements of a list have to be of the same type."
+                );
+
+                buffer.put_u8(element_id);
+                (tags.len() as MinecraftInt).encode_into(buffer);
+
+                for tag in tags {
+                    tag.write_payload(buffer);
+                }
+            }
+            Tag::Compound(compound) => compound.write_payload(buffer),
+            Tag::IntArray(values) => {
+                (values.len() as MinecraftInt).encode_into(buffer);
+
+                for value in values {
+                    value.encode_into(buffer);
+                }
+            }
+            Tag::LongArray(values) => {
+                (values.len() as MinecraftInt).encode_into(buffer);
+
+                for value in values {
+                    value.encode_into(buffer);
+                }
+            }
+        }
+    }
+
+    fn payload_length(&self) -> usize {
+        match self {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) | Tag::Float(_) => 4,
+            Tag::Long(_) | Tag::Double(_) => 8,
+            Tag::ByteArray(values) => 4 + values.len(),
+            Tag::String(value) => 2 + modified_utf8_length(value),
+            Tag::List(tags) => 5 + tags.iter().map(Tag::payload_length).sum::<usize>(),
+            Tag::Compound(compound) => compound.payload_length(),
+            Tag::IntArray(values) => 4 + values.len() * 4,
+            Tag::LongArray(values) => 4 + values.len() * 8,
+        }
+    }
+
+    fn read_payload<R: Read>(reader: &mut R, id: u8, depth: usize) -> Result<Tag, ProtocolError> {
+        if depth > MAX_DEPTH {
+            return Err(ProtocolError::TooLong {
+                type_name: "NBT nesting",
+                length: depth,
+                max_length: MAX_DEPTH,
+            });
+        }
+
+        Ok(match id {
+            1 => Tag::Byte(reader.decode()?),
+            2 => Tag::Short(reader.decode()?),
+            3 => Tag::Int(reader.decode()?),
+            4 => Tag::Long(reader.decode()?),
+            5 => Tag::Float(reader.decode()?),
+            6 => Tag::Double(reader.decode()?),
+            7 => {
+                let length = read_length(reader, "TAG_Byte_Array")?;
+                let bytes = reader.read_bytes(length, "TAG_Byte_Array")?;
+
+                Tag::ByteArray(bytes.into_iter().map(|byte| byte as i8).collect())
+            }
+            8 => Tag::String(read_string(reader)?),
+            9 => {
+                let element_id = reader.read_u8("TAG_List")?;
+                let length = read_length(reader, "TAG_List")?;
+
+                if element_id == END_ID && length > 0 {
+                    return Err(ProtocolError::invalid_value(
+                        "TAG_List element type",
+                        END_ID,
+                    ));
+                }
+
+                let mut tags = Vec::new();
+
+                for _ in 0..length {
+                    tags.push(Tag::read_payload(reader, element_id, depth + 1)?);
+                }
+
+                Tag::List(tags)
+            }
+            COMPOUND_ID => Tag::Compound(Compound::read_payload(reader, depth + 1)?),
+            11 => {
+                let length = read_length(reader, "TAG_Int_Array")?;
+                let mut values = Vec::new();
+
+                for _ in 0..length {
+                    values.push(reader.decode()?);
+                }
+
+                Tag::IntArray(values)
+            }
+            12 => {
+                let length = read_length(reader, "TAG_Long_Array")?;
+                let mut values = Vec::new();
+
+                for _ in 0..length {
+                    values.push(reader.decode()?);
+                }
+
+                Tag::LongArray(values)
+            }
+            _ => return Err(ProtocolError::invalid_value("NBT tag type", id)),
+        })
+    }
+}
+
+macro_rules! impl_from_for_tag {
+    ( $( $type: ty => $variant: ident ),* ) => {
+        $(
+            impl From<$type> for Tag {
+                fn from(value: $type) -> Tag {
+                    Tag::$variant(value)
+                }
+            }
+        )*
+    }
+}
+
+impl_from_for_tag!(
+    MinecraftSignedByte => Byte,
+    Short => Short,
+    MinecraftInt => Int,
+    Long => Long,
+    MinecraftFloat => Float,
+    MinecraftDouble => Double,
+    String => String,
+    Compound => Compound
+);
+
+impl From<&str> for Tag {
+    fn from(value: &str) -> Tag {
+        Tag::String(value.to_owned())
+    }
+}
+
+/// Named tags of any type, in the order they were inserted.
+///
+/// The order does not matter when comparing compounds, but is kept when they are written.
+#[derive(Debug, Clone, Default)]
+pub struct Compound {
+    entries: Vec<(String, Tag)>,
+}
+
+impl Compound {
+    pub fn new() -> Compound {
+        Compound::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        self.entries
+            .iter()
+            .find(|(entry_name, _)| entry_name == name)
+            .map(|(_, tag)| tag)
+    }
+
+    /// Sets the tag with the given name, keeping its position if it exists already.
+    pub fn insert<N: Into<String>, T: Into<Tag>>(&mut self, name: N, tag: T) {
+        let name = name.into();
+        let tag = tag.into();
+
+        match self
+            .entries
+            .iter_mut()
+            .find(|(entry_name, _)| *entry_name == name)
+        {
+            Some(entry) => entry.1 = tag,
+            None => self.entries.push((name, tag)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Tag)> {
+        self.entries.iter().map(|(name, tag)| (name.as_str(), tag))
+    }
+
+    fn write_payload<B: BufMut>(&self, buffer: &mut B) {
+        for (name, tag) in &self.entries {
+            buffer.put_u8(tag.id());
+            write_string(name, buffer);
+            tag.write_payload(buffer);
+        }
+
+        buffer.put_u8(END_ID);
+    }
+
+    fn payload_length(&self) -> usize {
+        self.entries
+            .iter()
+            .map(|(name, tag)| 3 + modified_utf8_length(name) + tag.payload_length())
+            .sum::<usize>()
+            + 1
+    }
+
+    fn read_payload<R: Read>(reader: &mut R, depth: usize) -> Result<Compound, ProtocolError> {
+        let mut entries = Vec::new();
+
+        loop {
+            let id = reader.read_u8("TAG_Compound")?;
+
+            if id == END_ID {
+                break;
+            }
+
+            let name = read_string(reader)?;
+            let tag = Tag::read_payload(reader, id, depth)?;
+
+            entries.push((name, tag));
+        }
+
+        // looking up each name while reading would be quadratic in the number of tags, so
+        // only compounds that repeat a name are built with `insert`, keeping the last tag
+        let mut names = HashSet::with_capacity(entries.len());
+
+        if entries.iter().all(|(name, _)| names.insert(name.as_str())) {
+            Ok(Compound { entries })
+        } else {
+            Ok(entries.into_iter().collect())
+        }
+    }
+}
+
+impl PartialEq for Compound {
+    fn eq(&self, other: &Compound) -> bool {
+        self.len() == other.len() && self.iter().all(|(name, tag)| other.get(name) == Some(tag))
+    }
+}
+
+impl IntoIterator for Compound {
+    type Item = (String, Tag);
+    type IntoIter = vec::IntoIter<(String, Tag)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<N: Into<String>, T: Into<Tag>> std::iter::FromIterator<(N, T)> for Compound {
+    fn from_iter<I: IntoIterator<Item = (N, T)>>(iter: I) -> Compound {
+        let mut compound = Compound::new();
+
+        for (name, tag) in iter {
+            compound.insert(name, tag);
+        }
+
+        compound
+    }
+}
+
+/// A complete NBT document, which is a compound with a name (that is empty most of the time).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Nbt {
+    pub name: String,
+    pub root: Compound,
+}
+
+impl Nbt {
+    pub fn new(name: &str, root: Compound) -> Nbt {
+        Nbt {
+            name: name.to_owned(),
+            root,
+        }
+    }
+
+    /// Reads a gzip compressed document, like `level.dat` or player data.
+    pub fn from_gzip_reader<R: Read>(reader: R) -> Result<Nbt, ProtocolError> {
+        GzDecoder::new(reader).decode()
+    }
+
+    /// Reads a zlib compressed document, like a chunk stored in a region file.
+    pub fn from_zlib_reader<R: Read>(reader: R) -> Result<Nbt, ProtocolError> {
+        ZlibDecoder::new(reader).decode()
+    }
+
+    /// Writes a gzip compressed document, which only tests do as nothing is saved yet.
+    #[cfg(test)]
+    pub fn write_gzip<W: std::io::Write>(&self, writer: W) -> std::io::Result<()> {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(writer, Compression::default());
+        encoder.write_all(&self.encode())?;
+        encoder.finish()?;
+
+        Ok(())
+    }
+
+    /// Writes a zlib compressed document, which only tests do as nothing is saved yet.
+    #[cfg(test)]
+    pub fn write_zlib<W: std::io::Write>(&self, writer: W) -> std::io::Result<()> {
+        use flate2::{write::ZlibEncoder, Compression};
+        use std::io::Write;
+
+        let mut encoder = ZlibEncoder::new(writer, Compression::default());
+        encoder.write_all(&self.encode())?;
+        encoder.finish()?;
+
+        Ok(())
+    }
+}
+
+impl Encodeable for Nbt {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        buffer.put_u8(COMPOUND_ID);
+        write_string(&self.name, buffer);
+        self.root.write_payload(buffer);
+    }
+
+    fn byte_length(&self) -> usize {
+        3 + modified_utf8_length(&self.name) + self.root.payload_length()
+    }
+}
+
+impl<R: Read> Decodeable<Nbt, ProtocolError> for R {
+    fn decode(&mut self) -> Result<Nbt, ProtocolError> {
+        let id = self.read_u8("NBT")?;
+
+        if id != COMPOUND_ID {
+            return Err(ProtocolError::invalid_value("NBT root tag type", id));
+        }
+
+        let name = read_string(self)?;
+        let root = Compound::read_payload(self, 1)?;
+
+        Ok(Nbt { name, root })
+    }
+}
+
+fn read_length<R: Read>(reader: &mut R, type_name: &'static str) -> Result<usize, ProtocolError> {
+    let length: MinecraftInt = reader.decode()?;
+
+    if length < 0 {
+        return Err(ProtocolError::invalid_value(type_name, length));
+    }
+
+    Ok(length as usize)
+}
+
+/// Strings are encoded in Java's modified UTF-8, prefixed with their length in bytes.
+///
+/// It differs from UTF-8 in encoding the null character with two bytes and characters outside
+/// of the BMP as surrogate pairs, with three bytes for each half.
+fn write_string<B: BufMut>(value: &str, buffer: &mut B) {
+    let length = modified_utf8_length(value);
+    debug_assert!(
+        length <= usize::from(UnsignedShort::MAX),
+        "NBT strings may not be longer than 65535 bytes."
+    );
+
+    (length as UnsignedShort).encode_into(buffer);
+
+    for code_unit in value.encode_utf16() {
+        match code_unit {
+            0x0001..=0x007F => buffer.put_u8(code_unit as u8),
+            0x0000 | 0x0080..=0x07FF => buffer.put_slice(&[
+                0xC0 | (code_unit >> 6) as u8,
+                0x80 | (code_unit & 0x3F) as u8,
+            ]),
+            _ => buffer.put_slice(&[
+                0xE0 | (code_unit >> 12) as u8,
+                0x80 | ((code_unit >> 6) & 0x3F) as u8,
+                0x80 | (code_unit & 0x3F) as u8,
+            ]),
+        }
+    }
+}
+
+fn modified_utf8_length(value: &str) -> usize {
+    value
+        .encode_utf16()
+        .map(|code_unit| match code_unit {
+            0x0001..=0x007F => 1,
+            0x0000 | 0x0080..=0x07FF => 2,
+            _ => 3,
+        })
+        .sum()
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String, ProtocolError> {
+    let length: UnsignedShort = reader.decode()?;
+    let bytes = reader.read_bytes(usize::from(length), "TAG_String")?;
+
+    let invalid = || ProtocolError::invalid_value("modified UTF-8 string", format!("{:?}", bytes));
+
+    let mut code_units = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter();
+
+    while let Some(&first) = iter.next() {
+        let mut continuation = || match iter.next() {
+            Some(&byte) if byte & 0xC0 == 0x80 => Ok(u16::from(byte & 0x3F)),
+            _ => Err(invalid()),
+        };
+
+        code_units.push(match first {
+            0x01..=0x7F => u16::from(first),
+            0xC0..=0xDF => (u16::from(first & 0x1F) << 6) | continuation()?,
+            0xE0..=0xEF => {
+                (u16::from(first & 0x0F) << 12) | (continuation()? << 6) | continuation()?
+            }
+            _ => return Err(invalid()),
+        });
+    }
+
+    String::from_utf16(&code_units).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Compound, Nbt, Tag};
+    use crate::coding::buffer::ReadCursor;
+    use crate::coding::error::ProtocolError;
+    use crate::coding::{Decodeable, Encodeable};
+
+    /// `hello_world.nbt` of the original specification.
+    fn hello_world() -> (Nbt, Vec<u8>) {
+        let mut root = Compound::new();
+        root.insert("name", "Bananrama");
+
+        let mut bytes = vec![0x0A, 0x00, 0x0B];
+        bytes.extend(b"hello world");
+        bytes.extend(vec![0x08, 0x00, 0x04]);
+        bytes.extend(b"name");
+        bytes.extend(vec![0x00, 0x09]);
+        bytes.extend(b"Bananrama");
+        bytes.push(0x00);
+
+        (Nbt::new("hello world", root), bytes)
+    }
+
+    fn all_tags() -> Nbt {
+        let mut nested = Compound::new();
+        nested.insert("byte", -1i8);
+        nested.insert("empty", Compound::new());
+
+        let root = vec![
+            ("byte", Tag::Byte(-128)),
+            ("short", Tag::Short(-32768)),
+            ("int", Tag::Int(0x0102_0304)),
+            ("long", Tag::Long(-0x0102_0304_0506_0708)),
+            ("float", Tag::Float(0.5)),
+            ("double", Tag::Double(-12.25)),
+            ("byte array", Tag::ByteArray(vec![-1, 0, 1])),
+            ("string", Tag::String("Hello, NBT!".to_owned())),
+            ("empty list", Tag::List(vec![])),
+            ("list", Tag::List(vec![Tag::Short(1), Tag::Short(2)])),
+            (
+                "nested list",
+                Tag::List(vec![Tag::List(vec![Tag::Int(1)]), Tag::List(vec![])]),
+            ),
+            ("compound", Tag::Compound(nested)),
+            ("int array", Tag::IntArray(vec![i32::MIN, 0, 42])),
+            ("long array", Tag::LongArray(vec![i64::MAX, -1])),
+        ]
+        .into_iter()
+        .collect();
+
+        Nbt::new("", root)
+    }
+
+    #[test]
+    fn test_hello_world() {
+        let (nbt, bytes) = hello_world();
+
+        assert_eq!(bytes, nbt.encode());
+        assert_eq!(bytes.len(), nbt.byte_length());
+
+        let actual: Nbt = ReadCursor::new(&bytes).decode().unwrap();
+        assert_eq!(nbt, actual);
+    }
+
+    #[test]
+    fn test_round_trip_all_tags() {
+        let nbt = all_tags();
+        let bytes = nbt.encode();
+
+        assert_eq!(bytes.len(), nbt.byte_length());
+
+        let actual: Nbt = ReadCursor::new(&bytes).decode().unwrap();
+        assert_eq!(nbt, actual);
+    }
+
+    #[test]
+    fn test_compressed_round_trip() {
+        let nbt = all_tags();
+
+        let mut gzip = Vec::new();
+        nbt.write_gzip(&mut gzip).unwrap();
+        // the magic number of gzip
+        assert_eq!(vec![0x1F, 0x8B], gzip[..2].to_vec());
+        assert_eq!(nbt, Nbt::from_gzip_reader(&gzip[..]).unwrap());
+
+        let mut zlib = Vec::new();
+        nbt.write_zlib(&mut zlib).unwrap();
+        assert_eq!(0x78, zlib[0]);
+        assert_eq!(nbt, Nbt::from_zlib_reader(&zlib[..]).unwrap());
+    }
+
+    #[test]
+    fn test_modified_utf8() {
+        let mappings: Vec<(&str, Vec<u8>)> = vec![
+            ("a", vec![0x00, 0x01, 0x61]),
+            ("\0", vec![0x00, 0x02, 0xC0, 0x80]),
+            ("§", vec![0x00, 0x02, 0xC2, 0xA7]),
+            ("日", vec![0x00, 0x03, 0xE6, 0x97, 0xA5]),
+            // a surrogate pair, instead of the four bytes of UTF-8
+            ("😀", vec![0x00, 0x06, 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]),
+        ];
+
+        for (string, bytes) in mappings {
+            let mut encoded = Vec::new();
+            super::write_string(string, &mut encoded);
+            assert_eq!(bytes, encoded, "{}", string);
+
+            assert_eq!(
+                Ok(string.to_owned()),
+                super::read_string(&mut ReadCursor::new(&bytes))
+            );
+        }
+    }
+
+    #[test]
+    fn test_compound_keeps_order_but_ignores_it_when_compared() {
+        let mut first = Compound::new();
+        first.insert("a", 1);
+        first.insert("b", 2);
+        first.insert("a", 3);
+
+        let second: Compound = vec![("b", 2), ("a", 3)].into_iter().collect();
+
+        assert_eq!(
+            vec!["a", "b"],
+            first.iter().map(|(name, _)| name).collect::<Vec<_>>()
+        );
+        assert_eq!(first, second);
+        assert_ne!(first, Compound::new());
+    }
+
+    #[test]
+    fn test_decode_repeated_names() {
+        // the last tag with a name wins, like in vanilla
+        let bytes = [
+            0x0A, 0x00, 0x00, 0x01, 0x00, 0x01, b'a', 0x01, 0x01, 0x00, 0x01, b'b', 0x02, 0x01,
+            0x00, 0x01, b'a', 0x03, 0x00,
+        ];
+
+        let actual: Nbt = ReadCursor::new(&bytes).decode().unwrap();
+
+        assert_eq!(
+            vec![("a", &Tag::Byte(3)), ("b", &Tag::Byte(2))],
+            actual.root.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_nbt() {
+        // lists of lists of lists...
+        let mut too_deep = vec![0x0A, 0x00, 0x00, 0x09, 0x00, 0x00];
+        for _ in 0..600 {
+            too_deep.extend(vec![0x09, 0x00, 0x00, 0x00, 0x01]);
+        }
+
+        let mappings: Vec<(Vec<u8>, ProtocolError)> = vec![
+            (vec![], ProtocolError::unexpected_eof("NBT")),
+            (
+                vec![0x08, 0x00, 0x00],
+                ProtocolError::invalid_value("NBT root tag type", 8),
+            ),
+            (
+                vec![0x0A, 0x00, 0x00, 0x0D, 0x00, 0x00],
+                ProtocolError::invalid_value("NBT tag type", 13),
+            ),
+            (
+                vec![0x0A, 0x00, 0x00, 0x07, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF],
+                ProtocolError::invalid_value("TAG_Byte_Array", -1),
+            ),
+            (
+                vec![0x0A, 0x00, 0x00, 0x07, 0x00, 0x00, 0x7F, 0xFF, 0xFF, 0xFF],
+                ProtocolError::unexpected_eof("TAG_Byte_Array"),
+            ),
+            (
+                vec![
+                    0x0A, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+                ],
+                ProtocolError::invalid_value("TAG_List element type", 0),
+            ),
+            (
+                vec![0x0A, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x01, 0x80, 0x00],
+                ProtocolError::invalid_value("modified UTF-8 string", "[128]"),
+            ),
+            (
+                too_deep,
+                ProtocolError::TooLong {
+                    type_name: "NBT nesting",
+                    length: 513,
+                    max_length: 512,
+                },
+            ),
+            // the compound is not closed
+            (
+                vec![0x0A, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01],
+                ProtocolError::unexpected_eof("TAG_Compound"),
+            ),
+        ];
+
+        for (bytes, expected) in mappings {
+            let actual: Result<Nbt, ProtocolError> = ReadCursor::new(&bytes).decode();
+
+            assert_eq!(Err(expected), actual);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_string_round_trip(string: String) {
+            let nbt = Nbt::new(&string, vec![(string.clone(), string.clone())].into_iter().collect());
+            let actual: Nbt = ReadCursor::new(&nbt.encode()).decode().unwrap();
+
+            prop_assert_eq!(nbt, actual);
+        }
+    }
+}
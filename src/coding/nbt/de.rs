@@ -0,0 +1,388 @@
+//! Converts tags into Rust values, the counterpart of `ser`.
+//!
+//! Bytes are accepted for booleans and numbers are converted to the type asked for if they
+//! fit, so values written by `ser` or by vanilla can be read.
+use super::{Compound, Tag};
+use crate::coding::error::ProtocolError;
+use serde::de::value::{MapDeserializer, SeqDeserializer, StringDeserializer};
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use std::fmt::Display;
+
+impl de::Error for ProtocolError {
+    fn custom<T: Display>(message: T) -> ProtocolError {
+        ProtocolError::invalid_value("NBT", message)
+    }
+}
+
+pub fn from_tag<T: DeserializeOwned>(tag: Tag) -> Result<T, ProtocolError> {
+    T::deserialize(tag)
+}
+
+pub fn from_compound<T: DeserializeOwned>(compound: Compound) -> Result<T, ProtocolError> {
+    from_tag(Tag::Compound(compound))
+}
+
+impl<'de> IntoDeserializer<'de, ProtocolError> for Tag {
+    type Deserializer = Tag;
+
+    fn into_deserializer(self) -> Tag {
+        self
+    }
+}
+
+fn visit_seq<'de, V, I, T>(visitor: V, values: I) -> Result<V::Value, ProtocolError>
+where
+    V: Visitor<'de>,
+    I: Iterator<Item = T>,
+    T: IntoDeserializer<'de, ProtocolError>,
+{
+    let mut deserializer = SeqDeserializer::new(values);
+    let value = visitor.visit_seq(&mut deserializer)?;
+    deserializer.end()?;
+
+    Ok(value)
+}
+
+impl<'de> de::Deserializer<'de> for Tag {
+    type Error = ProtocolError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProtocolError> {
+        match self {
+            Tag::Byte(value) => visitor.visit_i8(value),
+            Tag::Short(value) => visitor.visit_i16(value),
+            Tag::Int(value) => visitor.visit_i32(value),
+            Tag::Long(value) => visitor.visit_i64(value),
+            Tag::Float(value) => visitor.visit_f32(value),
+            Tag::Double(value) => visitor.visit_f64(value),
+            Tag::ByteArray(values) => visit_seq(visitor, values.into_iter()),
+            Tag::String(value) => visitor.visit_string(value),
+            Tag::List(tags) => visit_seq(visitor, tags.into_iter()),
+            Tag::Compound(compound) => {
+                let mut deserializer = MapDeserializer::new(compound.into_iter());
+                let value = visitor.visit_map(&mut deserializer)?;
+                deserializer.end()?;
+
+                Ok(value)
+            }
+            Tag::IntArray(values) => visit_seq(visitor, values.into_iter()),
+            Tag::LongArray(values) => visit_seq(visitor, values.into_iter()),
+        }
+    }
+
+    /// Vanilla treats every byte other than 0 as true.
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProtocolError> {
+        match self {
+            Tag::Byte(value) => visitor.visit_bool(value != 0),
+            tag => tag.deserialize_any(visitor),
+        }
+    }
+
+    /// Byte arrays are signed, but usually used for unsigned bytes.
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProtocolError> {
+        match self {
+            Tag::ByteArray(values) => {
+                visitor.visit_byte_buf(values.into_iter().map(|byte| byte as u8).collect())
+            }
+            tag => tag.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProtocolError> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    /// Missing entries are `None`, so every tag that exists is `Some`.
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProtocolError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ProtocolError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Unit variants are strings, the others a compound with the variant as the only entry.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ProtocolError> {
+        match self {
+            Tag::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Tag::Compound(compound) if compound.len() == 1 => {
+                let (variant, tag) = compound.into_iter().next().unwrap();
+
+                visitor.visit_enum(EnumDeserializer { variant, tag })
+            }
+            tag => Err(de::Error::custom(format!(
+                "expected an enum variant, got a {}",
+                tag.type_name()
+            ))),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string unit unit_struct seq
+        tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    tag: Tag,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = ProtocolError;
+    type Variant = Tag;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Tag), ProtocolError> {
+        let variant: StringDeserializer<ProtocolError> = self.variant.into_deserializer();
+        let variant = seed.deserialize(variant)?;
+
+        Ok((variant, self.tag))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Tag {
+    type Error = ProtocolError;
+
+    fn unit_variant(self) -> Result<(), ProtocolError> {
+        Err(de::Error::custom(
+            "expected a unit variant as TAG_String, got a TAG_Compound",
+        ))
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, ProtocolError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _length: usize,
+        visitor: V,
+    ) -> Result<V::Value, ProtocolError> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ProtocolError> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ser::{to_compound, to_tag};
+    use super::super::{Compound, Tag};
+    use super::{from_compound, from_tag};
+    use crate::coding::error::ProtocolError;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Generator {
+        Flat,
+        Noise { seed: i64 },
+        Custom(String),
+        Scaled(i32, i32),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Level {
+        level_name: String,
+        hardcore: bool,
+        spawn_x: i32,
+        random_seed: i64,
+        border_size: f64,
+        game_rules: BTreeMap<String, String>,
+        data_version: Option<u32>,
+        last_played: Option<i64>,
+        generator: Generator,
+        /// the unit variant is a string, so it can not be in a list with the other variants
+        generators: Vec<Generator>,
+        #[serde(with = "serde_bytes_compat")]
+        heights: Vec<u8>,
+    }
+
+    /// Stores the bytes as a byte array instead of a list.
+    mod serde_bytes_compat {
+        use serde::de::{Deserializer, Visitor};
+        use serde::Serializer;
+        use std::fmt;
+
+        pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(bytes)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vec<u8>, D::Error> {
+            struct BytesVisitor;
+
+            impl<'de> Visitor<'de> for BytesVisitor {
+                type Value = Vec<u8>;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    write!(f, "a byte array")
+                }
+
+                fn visit_byte_buf<E>(self, bytes: Vec<u8>) -> Result<Vec<u8>, E> {
+                    Ok(bytes)
+                }
+            }
+
+            deserializer.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+
+    fn level() -> Level {
+        let mut game_rules = BTreeMap::new();
+        game_rules.insert("doDaylightCycle".to_owned(), "true".to_owned());
+
+        Level {
+            level_name: "world".to_owned(),
+            hardcore: true,
+            spawn_x: -42,
+            random_seed: 1_234_567_890_123,
+            border_size: 6.0e7,
+            game_rules,
+            data_version: Some(1631),
+            last_played: None,
+            generator: Generator::Flat,
+            generators: vec![
+                Generator::Noise { seed: 3 },
+                Generator::Custom("void".to_owned()),
+                Generator::Scaled(1, 2),
+            ],
+            heights: vec![0, 255],
+        }
+    }
+
+    #[test]
+    fn test_serialize_struct() {
+        let actual = to_compound(&level()).unwrap();
+
+        let expected: Compound = vec![
+            ("LevelName", Tag::String("world".to_owned())),
+            ("Hardcore", Tag::Byte(1)),
+            ("SpawnX", Tag::Int(-42)),
+            ("RandomSeed", Tag::Long(1_234_567_890_123)),
+            ("BorderSize", Tag::Double(6.0e7)),
+            (
+                "GameRules",
+                Tag::Compound(vec![("doDaylightCycle", "true")].into_iter().collect()),
+            ),
+            ("DataVersion", Tag::Long(1631)),
+            ("Generator", Tag::String("Flat".to_owned())),
+            (
+                "Generators",
+                Tag::List(vec![
+                    Tag::Compound(
+                        vec![(
+                            "Noise",
+                            Tag::Compound(vec![("seed", 3i64)].into_iter().collect()),
+                        )]
+                        .into_iter()
+                        .collect(),
+                    ),
+                    Tag::Compound(vec![("Custom", "void")].into_iter().collect()),
+                    Tag::Compound(
+                        vec![("Scaled", Tag::List(vec![Tag::Int(1), Tag::Int(2)]))]
+                            .into_iter()
+                            .collect(),
+                    ),
+                ]),
+            ),
+            ("Heights", Tag::ByteArray(vec![0, -1])),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_round_trip_struct() {
+        let level = level();
+        let actual: Level = from_compound(to_compound(&level).unwrap()).unwrap();
+
+        assert_eq!(level, actual);
+    }
+
+    #[test]
+    fn test_deserialize_vanilla_types() {
+        // vanilla may use other types than the Rust struct, e.g. a byte for a boolean
+        let mappings: Vec<(Tag, Result<i64, ProtocolError>)> = vec![
+            (Tag::Byte(-1), Ok(-1)),
+            (Tag::Int(7), Ok(7)),
+            (
+                Tag::String("7".to_owned()),
+                Err(ProtocolError::invalid_value(
+                    "NBT",
+                    "invalid type: string \"7\", expected i64",
+                )),
+            ),
+        ];
+
+        for (tag, expected) in mappings {
+            assert_eq!(expected, from_tag(tag));
+        }
+
+        assert_eq!(Ok(true), from_tag(Tag::Byte(2)));
+        assert_eq!(Ok(false), from_tag(Tag::Byte(0)));
+        assert_eq!(
+            Ok(vec![1u8, 2]),
+            from_tag(Tag::List(vec![Tag::Byte(1), Tag::Byte(2)]))
+        );
+    }
+
+    #[test]
+    fn test_serialize_invalid_values() {
+        let mappings: Vec<(Result<Tag, ProtocolError>, ProtocolError)> = vec![
+            (
+                to_tag(&u64::MAX),
+                ProtocolError::invalid_value("TAG_Long", u64::MAX),
+            ),
+            (
+                to_tag(&()),
+                ProtocolError::invalid_value("NBT", "a value without content can not be a tag"),
+            ),
+            (
+                to_tag(&vec![Some(1), None]),
+                ProtocolError::invalid_value("NBT", "a value without content can not be a tag"),
+            ),
+            (
+                to_tag(&(1, "two")),
+                ProtocolError::invalid_value(
+                    "NBT",
+                    "a TAG_List of TAG_Int can not contain a TAG_String",
+                ),
+            ),
+            (
+                to_tag(&vec![(1, 2)].into_iter().collect::<BTreeMap<_, _>>()),
+                ProtocolError::invalid_value(
+                    "NBT",
+                    "the names of a TAG_Compound have to be strings, not a TAG_Int",
+                ),
+            ),
+        ];
+
+        for (actual, expected) in mappings {
+            assert_eq!(Err(expected), actual);
+        }
+    }
+}
@@ -0,0 +1,388 @@
+//! Converts Rust values into tags.
+//!
+//! Structs and maps become compounds, sequences become lists. As NBT has no unsigned types,
+//! they are widened to the next bigger signed type. `None` and `()` have no representation,
+//! so they are left out of compounds.
+use super::{Compound, Tag};
+use crate::coding::error::ProtocolError;
+use serde::ser::{self, Serialize};
+use std::convert::TryFrom;
+use std::fmt::Display;
+
+impl ser::Error for ProtocolError {
+    fn custom<T: Display>(message: T) -> ProtocolError {
+        ProtocolError::invalid_value("NBT", message)
+    }
+}
+
+/// Converts a value into a tag, e.g. a struct into a compound.
+pub fn to_tag<T: Serialize + ?Sized>(value: &T) -> Result<Tag, ProtocolError> {
+    value
+        .serialize(Serializer)?
+        .ok_or_else(|| ser::Error::custom("a value without content can not be a tag"))
+}
+
+/// Converts a value that serializes to a map, e.g. a struct, into a compound.
+pub fn to_compound<T: Serialize + ?Sized>(value: &T) -> Result<Compound, ProtocolError> {
+    match to_tag(value)? {
+        Tag::Compound(compound) => Ok(compound),
+        tag => Err(ser::Error::custom(format!(
+            "expected a TAG_Compound, got a {}",
+            tag.type_name()
+        ))),
+    }
+}
+
+struct Serializer;
+
+impl ser::Serializer for Serializer {
+    /// `None` if the value has no representation.
+    type Ok = Option<Tag>;
+    type Error = ProtocolError;
+
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeVariant<SerializeList>;
+    type SerializeMap = SerializeCompound;
+    type SerializeStruct = SerializeCompound;
+    type SerializeStructVariant = SerializeVariant<SerializeCompound>;
+
+    fn serialize_bool(self, value: bool) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::Byte(value as i8)))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::Byte(value)))
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::Short(value)))
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::Int(value)))
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::Long(value)))
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::Short(i16::from(value))))
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::Int(i32::from(value))))
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::Long(i64::from(value))))
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Option<Tag>, ProtocolError> {
+        i64::try_from(value)
+            .map(|value| Some(Tag::Long(value)))
+            .map_err(|_| ProtocolError::invalid_value("TAG_Long", value))
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::Float(value)))
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::Double(value)))
+    }
+
+    fn serialize_char(self, value: char) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::String(value.to_string())))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::String(value.to_owned())))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::ByteArray(
+            value.iter().map(|&byte| byte as i8).collect(),
+        )))
+    }
+
+    fn serialize_none(self) -> Result<Option<Tag>, ProtocolError> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(
+        self,
+        value: &T,
+    ) -> Result<Option<Tag>, ProtocolError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Option<Tag>, ProtocolError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::Compound(Compound::new())))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::String(variant.to_owned())))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Option<Tag>, ProtocolError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Option<Tag>, ProtocolError> {
+        let mut compound = Compound::new();
+        compound.insert(variant, to_tag(value)?);
+
+        Ok(Some(Tag::Compound(compound)))
+    }
+
+    fn serialize_seq(self, length: Option<usize>) -> Result<SerializeList, ProtocolError> {
+        Ok(SerializeList {
+            tags: Vec::with_capacity(length.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, length: usize) -> Result<SerializeList, ProtocolError> {
+        self.serialize_seq(Some(length))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        length: usize,
+    ) -> Result<SerializeList, ProtocolError> {
+        self.serialize_seq(Some(length))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        length: usize,
+    ) -> Result<SerializeVariant<SerializeList>, ProtocolError> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_seq(Some(length))?,
+        })
+    }
+
+    fn serialize_map(self, _length: Option<usize>) -> Result<SerializeCompound, ProtocolError> {
+        Ok(SerializeCompound {
+            compound: Compound::new(),
+            next_name: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        length: usize,
+    ) -> Result<SerializeCompound, ProtocolError> {
+        self.serialize_map(Some(length))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        length: usize,
+    ) -> Result<SerializeVariant<SerializeCompound>, ProtocolError> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_map(Some(length))?,
+        })
+    }
+}
+
+struct SerializeList {
+    tags: Vec<Tag>,
+}
+
+impl ser::SerializeSeq for SerializeList {
+    type Ok = Option<Tag>;
+    type Error = ProtocolError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ProtocolError> {
+        let tag = to_tag(value)?;
+
+        if let Some(first) = self.tags.first() {
+            if first.id() != tag.id() {
+                return Err(ser::Error::custom(format!(
+                    "a TAG_List of {} can not contain a {}",
+                    first.type_name(),
+                    tag.type_name()
+                )));
+            }
+        }
+
+        self.tags.push(tag);
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::List(self.tags)))
+    }
+}
+
+impl ser::SerializeTuple for SerializeList {
+    type Ok = Option<Tag>;
+    type Error = ProtocolError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ProtocolError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Option<Tag>, ProtocolError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+    type Ok = Option<Tag>;
+    type Error = ProtocolError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ProtocolError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Option<Tag>, ProtocolError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct SerializeCompound {
+    compound: Compound,
+    next_name: Option<String>,
+}
+
+impl SerializeCompound {
+    fn insert<T: Serialize + ?Sized>(
+        &mut self,
+        name: String,
+        value: &T,
+    ) -> Result<(), ProtocolError> {
+        if let Some(tag) = value.serialize(Serializer)? {
+            self.compound.insert(name, tag);
+        }
+
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for SerializeCompound {
+    type Ok = Option<Tag>;
+    type Error = ProtocolError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ProtocolError> {
+        match to_tag(key)? {
+            Tag::String(name) => {
+                self.next_name = Some(name);
+
+                Ok(())
+            }
+            tag => Err(ser::Error::custom(format!(
+                "the names of a TAG_Compound have to be strings, not a {}",
+                tag.type_name()
+            ))),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ProtocolError> {
+        let name = self
+            .next_name
+            .take()
+            .expect("serialize_value is called after serialize_key");
+
+        self.insert(name, value)
+    }
+
+    fn end(self) -> Result<Option<Tag>, ProtocolError> {
+        Ok(Some(Tag::Compound(self.compound)))
+    }
+}
+
+impl ser::SerializeStruct for SerializeCompound {
+    type Ok = Option<Tag>;
+    type Error = ProtocolError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), ProtocolError> {
+        self.insert(name.to_owned(), value)
+    }
+
+    fn end(self) -> Result<Option<Tag>, ProtocolError> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+/// Enum variants with content are a compound with the name of the variant as the only entry.
+struct SerializeVariant<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+fn wrap_variant(variant: &'static str, tag: Option<Tag>) -> Result<Option<Tag>, ProtocolError> {
+    let mut compound = Compound::new();
+    if let Some(tag) = tag {
+        compound.insert(variant, tag);
+    }
+
+    Ok(Some(Tag::Compound(compound)))
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeList> {
+    type Ok = Option<Tag>;
+    type Error = ProtocolError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ProtocolError> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<Option<Tag>, ProtocolError> {
+        wrap_variant(self.variant, ser::SerializeSeq::end(self.inner)?)
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeCompound> {
+    type Ok = Option<Tag>;
+    type Error = ProtocolError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), ProtocolError> {
+        self.inner.insert(name.to_owned(), value)
+    }
+
+    fn end(self) -> Result<Option<Tag>, ProtocolError> {
+        wrap_variant(self.variant, ser::SerializeMap::end(self.inner)?)
+    }
+}
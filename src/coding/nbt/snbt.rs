@@ -0,0 +1,539 @@
+//! Stringified NBT, the text form of tags used in commands, e.g. `{Count:1b,id:"stone"}`.
+//!
+//! Tags are printed with `Display` and parsed with `FromStr`, which is mostly useful for
+//! debugging and writing tests.
+use super::{Compound, Nbt, Tag};
+use crate::coding::error::ProtocolError;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::{CharIndices, FromStr};
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Tag::Byte(value) => write!(f, "{}b", value),
+            Tag::Short(value) => write!(f, "{}s", value),
+            Tag::Int(value) => write!(f, "{}", value),
+            Tag::Long(value) => write!(f, "{}L", value),
+            Tag::Float(value) => write!(f, "{}f", value),
+            Tag::Double(value) => write!(f, "{}d", value),
+            Tag::ByteArray(values) => write_array(f, "B;", values.iter().cloned().map(Tag::Byte)),
+            Tag::String(value) => write_quoted(f, value),
+            Tag::List(tags) => write_array(f, "", tags.iter()),
+            Tag::Compound(compound) => compound.fmt(f),
+            Tag::IntArray(values) => write_array(f, "I;", values.iter().cloned().map(Tag::Int)),
+            Tag::LongArray(values) => write_array(f, "L;", values.iter().cloned().map(Tag::Long)),
+        }
+    }
+}
+
+impl fmt::Display for Compound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{")?;
+
+        for (index, (name, tag)) in self.iter().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+
+            if !name.is_empty() && name.chars().all(is_unquoted_char) {
+                write!(f, "{}:{}", name, tag)?;
+            } else {
+                write_quoted(f, name)?;
+                write!(f, ":{}", tag)?;
+            }
+        }
+
+        write!(f, "}}")
+    }
+}
+
+/// The name is not part of SNBT, so only the root compound is printed.
+impl fmt::Display for Nbt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.root.fmt(f)
+    }
+}
+
+fn write_array<T: fmt::Display, I: Iterator<Item = T>>(
+    f: &mut fmt::Formatter,
+    prefix: &str,
+    values: I,
+) -> fmt::Result {
+    write!(f, "[{}", prefix)?;
+
+    for (index, value) in values.enumerate() {
+        if index > 0 {
+            write!(f, ",")?;
+        }
+
+        write!(f, "{}", value)?;
+    }
+
+    write!(f, "]")
+}
+
+fn write_quoted(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+
+    for character in value.chars() {
+        if character == '"' || character == '\\' {
+            write!(f, "\\")?;
+        }
+
+        write!(f, "{}", character)?;
+    }
+
+    write!(f, "\"")
+}
+
+fn is_unquoted_char(character: char) -> bool {
+    character.is_ascii_alphanumeric() || "_-.+".contains(character)
+}
+
+impl FromStr for Tag {
+    type Err = ProtocolError;
+
+    fn from_str(snbt: &str) -> Result<Tag, ProtocolError> {
+        let mut parser = Parser {
+            snbt,
+            chars: snbt.char_indices().peekable(),
+            depth: 0,
+        };
+
+        let tag = parser.parse_tag()?;
+        parser.skip_whitespace();
+
+        match parser.chars.peek() {
+            None => Ok(tag),
+            Some(_) => Err(parser.error("Unexpected trailing data")),
+        }
+    }
+}
+
+impl FromStr for Compound {
+    type Err = ProtocolError;
+
+    fn from_str(snbt: &str) -> Result<Compound, ProtocolError> {
+        match snbt.parse()? {
+            Tag::Compound(compound) => Ok(compound),
+            tag => Err(ProtocolError::invalid_value(
+                "SNBT",
+                format!("Expected a TAG_Compound, got a {}", tag.type_name()),
+            )),
+        }
+    }
+}
+
+struct Parser<'a> {
+    snbt: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&mut self, message: &str) -> ProtocolError {
+        let position = self
+            .chars
+            .peek()
+            .map_or(self.snbt.len(), |&(position, _)| position);
+
+        ProtocolError::invalid_value("SNBT", format!("{} at position {}", message, position))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .chars
+            .peek()
+            .is_some_and(|(_, character)| character.is_whitespace())
+        {
+            self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ProtocolError> {
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            Some(&(_, character)) if character == expected => {
+                self.chars.next();
+
+                Ok(())
+            }
+            _ => Err(self.error(&format!("Expected '{}'", expected))),
+        }
+    }
+
+    /// Consumes the character if it is next, after optional whitespace.
+    fn accept(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+
+        if self.chars.peek().map(|&(_, character)| character) == Some(expected) {
+            self.chars.next();
+
+            return true;
+        }
+
+        false
+    }
+
+    fn parse_tag(&mut self) -> Result<Tag, ProtocolError> {
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            Some((_, '{')) => self.nested(Parser::parse_compound).map(Tag::Compound),
+            Some((_, '[')) => self.nested(Parser::parse_list),
+            Some((_, '"')) | Some((_, '\'')) => self.parse_quoted().map(Tag::String),
+            _ => {
+                let literal = self.parse_unquoted()?;
+
+                Ok(parse_literal(literal))
+            }
+        }
+    }
+
+    fn nested<T>(
+        &mut self,
+        parse: fn(&mut Parser<'a>) -> Result<T, ProtocolError>,
+    ) -> Result<T, ProtocolError> {
+        if self.depth >= super::MAX_DEPTH {
+            return Err(self.error("Too deeply nested"));
+        }
+
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+
+        result
+    }
+
+    fn parse_compound(&mut self) -> Result<Compound, ProtocolError> {
+        self.expect('{')?;
+        let mut compound = Compound::new();
+
+        if self.accept('}') {
+            return Ok(compound);
+        }
+
+        loop {
+            self.skip_whitespace();
+            let name = match self.chars.peek() {
+                Some((_, '"')) | Some((_, '\'')) => self.parse_quoted()?,
+                _ => self.parse_unquoted()?.to_owned(),
+            };
+
+            self.expect(':')?;
+            let tag = self.parse_tag()?;
+            compound.insert(name, tag);
+
+            if !self.accept(',') {
+                self.expect('}')?;
+
+                return Ok(compound);
+            }
+        }
+    }
+
+    fn parse_list(&mut self) -> Result<Tag, ProtocolError> {
+        self.expect('[')?;
+        self.skip_whitespace();
+
+        // arrays are prefixed with their type, e.g. `[I;1,2]`
+        let mut lookahead = self.chars.clone();
+        let array_type = match (lookahead.next(), lookahead.next()) {
+            (Some((_, array_type @ 'B')), Some((_, ';')))
+            | (Some((_, array_type @ 'I')), Some((_, ';')))
+            | (Some((_, array_type @ 'L')), Some((_, ';'))) => Some(array_type),
+            _ => None,
+        };
+
+        if let Some(array_type) = array_type {
+            self.chars = lookahead;
+            let tags = self.parse_elements()?;
+
+            return match array_type {
+                'B' => tags
+                    .iter()
+                    .map(|tag| match tag {
+                        Tag::Byte(value) => Ok(*value),
+                        _ => Err(tag),
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Tag::ByteArray),
+                'I' => tags
+                    .iter()
+                    .map(|tag| match tag {
+                        Tag::Int(value) => Ok(*value),
+                        _ => Err(tag),
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Tag::IntArray),
+                _ => tags
+                    .iter()
+                    .map(|tag| match tag {
+                        Tag::Long(value) => Ok(*value),
+                        _ => Err(tag),
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Tag::LongArray),
+            }
+            .map_err(|tag| {
+                ProtocolError::invalid_value(
+                    "SNBT",
+                    format!("A [{};] array can not contain {}", array_type, tag),
+                )
+            });
+        }
+
+        let tags = self.parse_elements()?;
+
+        if let Some(first) = tags.first() {
+            if let Some(tag) = tags.iter().find(|tag| tag.id() != first.id()) {
+                return Err(ProtocolError::invalid_value(
+                    "SNBT",
+                    format!(
+                        "A TAG_List of {} can not contain {}",
+                        first.type_name(),
+                        tag
+                    ),
+                ));
+            }
+        }
+
+        Ok(Tag::List(tags))
+    }
+
+    /// Parses the elements of a list up to and including the closing bracket.
+    fn parse_elements(&mut self) -> Result<Vec<Tag>, ProtocolError> {
+        let mut tags = Vec::new();
+
+        if self.accept(']') {
+            return Ok(tags);
+        }
+
+        loop {
+            tags.push(self.parse_tag()?);
+
+            if !self.accept(',') {
+                self.expect(']')?;
+
+                return Ok(tags);
+            }
+        }
+    }
+
+    fn parse_quoted(&mut self) -> Result<String, ProtocolError> {
+        let quote = match self.chars.next() {
+            Some((_, quote)) => quote,
+            None => return Err(self.error("Expected a string")),
+        };
+
+        let mut string = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some((_, '\\')) => match self.chars.next() {
+                    Some((_, character)) if character == quote || character == '\\' => {
+                        string.push(character)
+                    }
+                    _ => return Err(self.error("Invalid escape sequence")),
+                },
+                Some((_, character)) if character == quote => return Ok(string),
+                Some((_, character)) => string.push(character),
+                None => return Err(self.error("Unterminated string")),
+            }
+        }
+    }
+
+    fn parse_unquoted(&mut self) -> Result<&'a str, ProtocolError> {
+        let start = match self.chars.peek() {
+            Some(&(start, character)) if is_unquoted_char(character) => start,
+            _ => return Err(self.error("Expected a value")),
+        };
+
+        let mut end = start;
+        while let Some(&(position, character)) = self.chars.peek() {
+            if !is_unquoted_char(character) {
+                break;
+            }
+
+            end = position + character.len_utf8();
+            self.chars.next();
+        }
+
+        Ok(&self.snbt[start..end])
+    }
+}
+
+/// Unquoted values are numbers if they look like one, and strings otherwise.
+fn parse_literal(literal: &str) -> Tag {
+    match literal {
+        "true" => return Tag::Byte(1),
+        "false" => return Tag::Byte(0),
+        _ => {}
+    }
+
+    let (body, suffix) = match literal.char_indices().last() {
+        Some((index, suffix)) if suffix.is_ascii_alphabetic() => {
+            (&literal[..index], Some(suffix.to_ascii_lowercase()))
+        }
+        _ => (literal, None),
+    };
+
+    let tag = match suffix {
+        Some('b') if is_integer(body) => body.parse().ok().map(Tag::Byte),
+        Some('s') if is_integer(body) => body.parse().ok().map(Tag::Short),
+        Some('l') if is_integer(body) => body.parse().ok().map(Tag::Long),
+        Some('f') if is_decimal(body) => body.parse().ok().map(Tag::Float),
+        Some('d') if is_decimal(body) => body.parse().ok().map(Tag::Double),
+        None if is_integer(body) => body.parse().ok().map(Tag::Int),
+        None if is_decimal(body) => body.parse().ok().map(Tag::Double),
+        _ => None,
+    };
+
+    tag.unwrap_or_else(|| Tag::String(literal.to_owned()))
+}
+
+fn is_integer(literal: &str) -> bool {
+    let digits = literal.trim_start_matches(['-', '+']);
+
+    literal.len() - digits.len() <= 1
+        && !digits.is_empty()
+        && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Only plain decimals are allowed, unlike Rust, which also accepts e.g. `inf` or `NaN`.
+fn is_decimal(literal: &str) -> bool {
+    let unsigned = literal.trim_start_matches(['-', '+']);
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(index) => (&unsigned[..index], Some(&unsigned[index + 1..])),
+        None => (unsigned, None),
+    };
+
+    literal.len() - unsigned.len() <= 1
+        && mantissa.chars().any(|c| c.is_ascii_digit())
+        && mantissa.chars().filter(|&c| c == '.').count() <= 1
+        && mantissa.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && exponent.is_none_or(is_integer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Compound, Tag};
+    use crate::coding::error::ProtocolError;
+
+    fn compound(entries: Vec<(&str, Tag)>) -> Tag {
+        Tag::Compound(entries.into_iter().collect())
+    }
+
+    #[test]
+    fn test_print_snbt() {
+        let mappings = vec![
+            (Tag::Byte(-1), "-1b"),
+            (Tag::Short(300), "300s"),
+            (Tag::Int(42), "42"),
+            (Tag::Long(1 << 40), "1099511627776L"),
+            (Tag::Float(0.5), "0.5f"),
+            (Tag::Double(3.0), "3d"),
+            (Tag::String("say \"hi\"\\".to_owned()), r#""say \"hi\"\\""#),
+            (Tag::ByteArray(vec![1, -2]), "[B;1b,-2b]"),
+            (Tag::IntArray(vec![]), "[I;]"),
+            (Tag::LongArray(vec![1, 2]), "[L;1L,2L]"),
+            (
+                Tag::List(vec![Tag::Float(1.5), Tag::Float(-2.0)]),
+                "[1.5f,-2f]",
+            ),
+            (
+                compound(vec![
+                    ("id", Tag::String("minecraft:stone".to_owned())),
+                    ("Count", Tag::Byte(64)),
+                    ("display name", compound(vec![])),
+                    ("", Tag::Int(0)),
+                ]),
+                r#"{id:"minecraft:stone",Count:64b,"display name":{},"":0}"#,
+            ),
+        ];
+
+        for (tag, expected) in mappings {
+            assert_eq!(expected, tag.to_string());
+
+            // everything that is printed can be parsed again
+            assert_eq!(Ok(tag), expected.parse());
+        }
+    }
+
+    #[test]
+    fn test_parse_snbt() {
+        let mappings = vec![
+            ("true", Tag::Byte(1)),
+            ("false", Tag::Byte(0)),
+            ("12B", Tag::Byte(12)),
+            ("-7S", Tag::Short(-7)),
+            ("+5", Tag::Int(5)),
+            ("5l", Tag::Long(5)),
+            ("1.5", Tag::Double(1.5)),
+            (".5F", Tag::Float(0.5)),
+            ("1e3", Tag::Double(1000.0)),
+            ("2D", Tag::Double(2.0)),
+            // numbers out of range, or things that look like numbers but are none, are strings
+            ("300b", Tag::String("300b".to_owned())),
+            ("3000000000", Tag::String("3000000000".to_owned())),
+            ("inf", Tag::String("inf".to_owned())),
+            ("1.2.3", Tag::String("1.2.3".to_owned())),
+            ("minecraft", Tag::String("minecraft".to_owned())),
+            (r#"'say "hi"'"#, Tag::String("say \"hi\"".to_owned())),
+            (r"'it\'s'", Tag::String("it's".to_owned())),
+            (" [ ] ", Tag::List(vec![])),
+            ("[B; 1b, 2b]", Tag::ByteArray(vec![1, 2])),
+            ("[I;]", Tag::IntArray(vec![])),
+            // a list of strings, not an array
+            ("[B]", Tag::List(vec![Tag::String("B".to_owned())])),
+            (
+                "{ pos : [ 1d , 2d ] , 'name':\"a\" }",
+                compound(vec![
+                    ("pos", Tag::List(vec![Tag::Double(1.0), Tag::Double(2.0)])),
+                    ("name", Tag::String("a".to_owned())),
+                ]),
+            ),
+        ];
+
+        for (snbt, expected) in mappings {
+            assert_eq!(Ok(expected), snbt.parse(), "{}", snbt);
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_snbt() {
+        let too_deep = "[".repeat(600);
+
+        let mappings = vec![
+            ("", "Expected a value at position 0"),
+            ("{a:1", "Expected '}' at position 4"),
+            ("{a 1}", "Expected ':' at position 3"),
+            ("{a:1,}", "Expected a value at position 5"),
+            ("1 2", "Unexpected trailing data at position 2"),
+            ("\"open", "Unterminated string at position 5"),
+            (r#""\n""#, "Invalid escape sequence at position 3"),
+            ("[1,2b]", "A TAG_List of TAG_Int can not contain 2b"),
+            ("[I;1,2b]", "A [I;] array can not contain 2b"),
+            (&too_deep, "Too deeply nested at position 512"),
+        ];
+
+        for (snbt, message) in mappings {
+            assert_eq!(
+                Err(ProtocolError::invalid_value("SNBT", message)),
+                snbt.parse::<Tag>(),
+                "{}",
+                snbt
+            );
+        }
+
+        assert_eq!(
+            Err(ProtocolError::invalid_value(
+                "SNBT",
+                "Expected a TAG_Compound, got a TAG_Int"
+            )),
+            "1".parse::<Compound>()
+        );
+    }
+}
@@ -1,13 +1,14 @@
+use super::buffer::BufMut;
+use super::error::ProtocolError;
 use super::string::ReadString;
 use super::{Decodeable, Encodeable};
 use crate::plugin_message::{PluginMessage, PluginMessageOrigin};
-use std::collections::VecDeque;
-use std::io;
+use std::io::Read;
 
-impl Decodeable<PluginMessage, io::Error> for VecDeque<u8> {
-    fn decode(&mut self) -> Result<PluginMessage, io::Error> {
+impl<R: Read> Decodeable<PluginMessage, ProtocolError> for R {
+    fn decode(&mut self) -> Result<PluginMessage, ProtocolError> {
         let channel = self.read_string(32767)?;
-        let data: VecDeque<u8> = self.decode()?;
+        let data: Vec<u8> = self.decode()?;
         let origin = PluginMessageOrigin::Client;
 
         Ok(PluginMessage::new(channel, data, origin))
@@ -15,11 +16,12 @@ impl Decodeable<PluginMessage, io::Error> for VecDeque<u8> {
 }
 
 impl Encodeable for PluginMessage {
-    fn encode(&self) -> VecDeque<u8> {
-        crate::build_package_data!(self.channel(), self.data())
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        self.channel().encode_into(buffer);
+        self.data().encode_into(buffer);
     }
 
-    fn byte_length(&self) -> u8 {
+    fn byte_length(&self) -> usize {
         self.channel().byte_length() + self.data().byte_length()
     }
 }
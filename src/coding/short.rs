@@ -1,40 +1,133 @@
-use super::Decodeable;
-use std::collections::VecDeque;
-use std::io;
+use super::buffer::{BufMut, ReadBytes};
+use super::error::ProtocolError;
+use super::{Decodeable, Encodeable};
+use std::io::Read;
 
 pub type Short = i16;
 pub type UnsignedShort = u16;
 
-impl Decodeable<UnsignedShort, io::Error> for VecDeque<u8> {
-    fn decode(&mut self) -> Result<UnsignedShort, io::Error> {
-        let error_message =
-            "VecDeque needs to have at least 2 bytes for reading an unsigned-short.";
+impl<R: Read> Decodeable<Short, ProtocolError> for R {
+    fn decode(&mut self) -> Result<Short, ProtocolError> {
+        Ok(i16::from_be_bytes(self.read_fixed("short")?))
+    }
+}
+
+impl Encodeable for Short {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        buffer.put_slice(&self.to_be_bytes());
+    }
+
+    fn byte_length(&self) -> usize {
+        2
+    }
+}
+
+impl<R: Read> Decodeable<UnsignedShort, ProtocolError> for R {
+    fn decode(&mut self) -> Result<UnsignedShort, ProtocolError> {
+        Ok(u16::from_be_bytes(self.read_fixed("unsigned short")?))
+    }
+}
 
-        let result: UnsignedShort = (u16::from(self.pop_front().expect(error_message)) << 8)
-            .checked_add(u16::from(self.pop_front().expect(error_message)))
-            .expect("Unsigned short is too big!");
+impl Encodeable for UnsignedShort {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        buffer.put_slice(&self.to_be_bytes());
+    }
 
-        Ok(result)
+    fn byte_length(&self) -> usize {
+        2
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Decodeable, UnsignedShort};
-    use std::collections::VecDeque;
+    use super::{Decodeable, Encodeable, Short, UnsignedShort};
+    use crate::coding::buffer::ReadCursor;
+    use crate::coding::error::ProtocolError;
 
-    #[test]
-    fn test_read_unsigned_short_on_vec() {
-        let mappings: Vec<(UnsignedShort, Vec<u8>)> = vec![
+    fn short_mappings() -> Vec<(Short, Vec<u8>)> {
+        vec![
+            (0, vec![0, 0]),
+            (1, vec![0, 1]),
+            (-1, vec![0xFF, 0xFF]),
+            (256, vec![0x01, 0x00]),
+            (-32768, vec![0x80, 0x00]), // lowest possible value
+            (32767, vec![0x7F, 0xFF]),  // highest possible value
+        ]
+    }
+
+    fn unsigned_short_mappings() -> Vec<(UnsignedShort, Vec<u8>)> {
+        vec![
             (25565, vec![99, 221]),
             (25555, vec![99, 211]),
             (24555, vec![95, 235]),
-        ];
+            (0, vec![0, 0]),
+            (65535, vec![0xFF, 0xFF]),
+        ]
+    }
 
-        for mapping in mappings {
-            let actual: UnsignedShort = VecDeque::from(mapping.1).decode().unwrap();
+    #[test]
+    fn test_read_short_on_vec() {
+        for mapping in short_mappings() {
+            let actual: Short = ReadCursor::new(&mapping.1).decode().unwrap();
 
             assert_eq!(mapping.0, actual);
         }
     }
+
+    #[test]
+    fn test_write_short_to_vec() {
+        for mapping in short_mappings() {
+            assert_eq!(mapping.1, mapping.0.encode());
+        }
+    }
+
+    #[test]
+    fn test_read_unsigned_short_on_vec() {
+        for mapping in unsigned_short_mappings() {
+            let actual: UnsignedShort = ReadCursor::new(&mapping.1).decode().unwrap();
+
+            assert_eq!(mapping.0, actual);
+        }
+    }
+
+    #[test]
+    fn test_write_unsigned_short_to_vec() {
+        for mapping in unsigned_short_mappings() {
+            assert_eq!(mapping.1, mapping.0.encode());
+        }
+    }
+
+    #[test]
+    fn test_read_unsigned_short_too_short() {
+        let actual: Result<UnsignedShort, ProtocolError> = ReadCursor::new(&[99]).decode();
+
+        assert_eq!(Err(ProtocolError::unexpected_eof("unsigned short")), actual);
+    }
+
+    #[test]
+    fn test_read_short_too_short() {
+        let actual: Result<Short, ProtocolError> = ReadCursor::new(&[]).decode();
+
+        assert_eq!(Err(ProtocolError::unexpected_eof("short")), actual);
+    }
+
+    proptest! {
+        #[test]
+        fn test_short_round_trip(value: Short) {
+            let encoded = value.encode();
+            let actual: Short = ReadCursor::new(&encoded).decode().unwrap();
+
+            prop_assert_eq!(value.to_be_bytes().to_vec(), encoded);
+            prop_assert_eq!(value, actual);
+        }
+
+        #[test]
+        fn test_unsigned_short_round_trip(value: UnsignedShort) {
+            let encoded = value.encode();
+            let actual: UnsignedShort = ReadCursor::new(&encoded).decode().unwrap();
+
+            prop_assert_eq!(value.to_be_bytes().to_vec(), encoded);
+            prop_assert_eq!(value, actual);
+        }
+    }
 }
@@ -1,31 +1,22 @@
+use super::buffer::{BufMut, ReadBytes};
+use super::error::ProtocolError;
 use super::{Decodeable, Encodeable};
-use std::collections::VecDeque;
-use std::io;
+use std::io::Read;
 
 pub type MinecraftSignedByte = i8;
 
-impl Decodeable<MinecraftSignedByte, io::Error> for VecDeque<u8> {
-    fn decode(&mut self) -> Result<MinecraftSignedByte, io::Error> {
-        let value = self.pop_front();
-
-        if value.is_some() {
-            let result = value.unwrap() as MinecraftSignedByte;
-            Ok(result)
-        } else {
-            Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "A minecraft byte consists of one byte, but there is none!",
-            ))
-        }
+impl<R: Read> Decodeable<MinecraftSignedByte, ProtocolError> for R {
+    fn decode(&mut self) -> Result<MinecraftSignedByte, ProtocolError> {
+        Ok(self.read_u8("byte")? as MinecraftSignedByte)
     }
 }
 
 impl Encodeable for MinecraftSignedByte {
-    fn encode(&self) -> VecDeque<u8> {
-        VecDeque::from(vec![*self as u8])
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        buffer.put_u8(*self as u8);
     }
 
-    fn byte_length(&self) -> u8 {
+    fn byte_length(&self) -> usize {
         1
     }
 }
@@ -33,8 +24,8 @@ impl Encodeable for MinecraftSignedByte {
 #[cfg(test)]
 mod tests {
     use super::{Decodeable, Encodeable, MinecraftSignedByte};
-    use std::collections::VecDeque;
-    use std::io;
+    use crate::coding::buffer::ReadCursor;
+    use crate::coding::error::ProtocolError;
 
     #[test]
     fn test_decoding() {
@@ -46,14 +37,14 @@ mod tests {
         ];
 
         for mapping in mappings {
-            let actual: MinecraftSignedByte = VecDeque::from(mapping.1).decode().unwrap();
+            let actual: MinecraftSignedByte = ReadCursor::new(&mapping.1).decode().unwrap();
             assert_eq!(mapping.0, actual);
         }
     }
 
     #[test]
     fn test_decoding_err() {
-        let actual: Result<MinecraftSignedByte, io::Error> = VecDeque::from(vec![]).decode();
+        let actual: Result<MinecraftSignedByte, ProtocolError> = ReadCursor::new(&[]).decode();
 
         assert!(actual.is_err());
     }
@@ -68,7 +59,18 @@ mod tests {
         ];
 
         for mapping in mappings {
-            assert_eq!(VecDeque::from(mapping.1), mapping.0.encode());
+            assert_eq!(mapping.1, mapping.0.encode());
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(value: MinecraftSignedByte) {
+            let encoded = value.encode();
+            let actual: MinecraftSignedByte = ReadCursor::new(&encoded).decode().unwrap();
+
+            prop_assert_eq!(value.to_be_bytes().to_vec(), encoded);
+            prop_assert_eq!(value, actual);
         }
     }
 }
@@ -1,40 +1,48 @@
+use super::buffer::{BufMut, ReadBytes};
+use super::error::ProtocolError;
 use super::varint::Varint;
 use super::{Decodeable, Encodeable};
-use crate::connection::ensure_data_size;
-use std::char;
-use std::collections::VecDeque;
-use std::io;
+use std::io::Read;
 
 pub type MinecraftString = String;
 
 pub trait ReadString<E> {
-    /// Reads a string from the buffer, throwing an error if the length is over `max_size`
+    /// Reads a UTF-8 string from the buffer, throwing an error if it is longer than `max_size`
+    /// UTF-16 code units, like vanilla counts them.
     fn read_string(&mut self, max_size: u16) -> Result<MinecraftString, E>;
 }
 
-impl ReadString<io::Error> for VecDeque<u8> {
-    fn read_string(&mut self, max_size: u16) -> Result<MinecraftString, io::Error> {
+impl<R: Read> ReadString<ProtocolError> for R {
+    fn read_string(&mut self, max_size: u16) -> Result<MinecraftString, ProtocolError> {
         let length: Varint = self.decode()?;
-        let mut length = length.0;
-        ensure_data_size(length)?;
-
-        if length > i32::from(max_size) {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "String is too large.",
-            ));
+
+        if length.0 < 0 {
+            return Err(ProtocolError::invalid_value("string length", length));
         }
 
-        let mut result = String::with_capacity(length as usize);
+        let length = length.0 as usize;
 
-        while length > 0 {
-            result.push(
-                char::from_u32(u32::from(self.pop_front().expect(
-                    "Vec of bytes is too short to read the length that the string should be.",
-                )))
-                .expect("Invalid character in String"),
-            );
-            length -= 1;
+        // every UTF-16 code unit takes up to four bytes in UTF-8
+        if length > usize::from(max_size) * 4 {
+            return Err(ProtocolError::TooLong {
+                type_name: "String",
+                length,
+                max_length: usize::from(max_size) * 4,
+            });
+        }
+
+        let bytes = self.read_bytes(length, "String")?;
+        let result = String::from_utf8(bytes)
+            .map_err(|error| ProtocolError::invalid_value("UTF-8 string", error))?;
+
+        let utf16_length = result.encode_utf16().count();
+
+        if utf16_length > usize::from(max_size) {
+            return Err(ProtocolError::TooLong {
+                type_name: "String",
+                length: utf16_length,
+                max_length: usize::from(max_size),
+            });
         }
 
         Ok(result)
@@ -42,67 +50,110 @@ impl ReadString<io::Error> for VecDeque<u8> {
 }
 
 impl Encodeable for String {
-    fn encode(&self) -> VecDeque<u8> {
-        let length_varint = Varint(self.len() as i32).encode();
-
-        let mut result = VecDeque::with_capacity(self.len() + length_varint.len());
-
-        result.extend(length_varint);
-
-        self.chars().for_each(|x| result.push_back(x as u8));
-
-        result
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        self.as_str().encode_into(buffer)
     }
 
-    fn byte_length(&self) -> u8 {
-        self.len() as u8 + 7 // we need to prefix the length with a varint, which is encoded in seven bytes
+    fn byte_length(&self) -> usize {
+        self.as_str().byte_length()
     }
 }
 
 impl Encodeable for str {
-    fn encode(&self) -> VecDeque<u8> {
-        let length_varint = Varint(self.len() as i32).encode();
-
-        let mut result = VecDeque::with_capacity(self.len() + length_varint.len());
-
-        result.extend(length_varint);
-
-        self.chars().for_each(|x| result.push_back(x as u8));
-
-        result
+    /// Encodes the string as UTF-8, prefixed with its length in bytes.
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        Varint(self.len() as i32).encode_into(buffer);
+        buffer.put_slice(self.as_bytes());
     }
 
-    fn byte_length(&self) -> u8 {
-        self.len() as u8 + 7 // we need to prefix the length with a varint, which is encoded in seven bytes
+    fn byte_length(&self) -> usize {
+        Varint(self.len() as i32).encoded_length() + self.len()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{Encodeable, ReadString};
-    use std::collections::VecDeque;
+    use crate::coding::buffer::ReadCursor;
 
     #[test]
     fn read_string() {
         let vector = vec![9, 108, 111, 99, 97, 108, 104, 111, 115, 116];
-        assert_eq!("localhost", VecDeque::from(vector).read_string(9).unwrap());
+        assert_eq!(
+            "localhost",
+            ReadCursor::new(&vector).read_string(9).unwrap()
+        );
     }
 
     #[test]
     fn write_str() {
         let expected = vec![9, 108, 111, 99, 97, 108, 104, 111, 115, 116];
-        assert_eq!(VecDeque::from(expected), "localhost".encode());
+        assert_eq!(expected, "localhost".encode());
     }
 
     #[test]
     fn write_string() {
         let expected = vec![9, 108, 111, 99, 97, 108, 104, 111, 115, 116];
-        assert_eq!(VecDeque::from(expected), "localhost".to_owned().encode());
+        assert_eq!(expected, "localhost".to_owned().encode());
     }
 
     #[test]
     fn read_string_should_err_if_too_long() {
         let vector = vec![111, 108, 111, 99, 97, 108, 104, 111, 115, 116];
-        assert!(VecDeque::from(vector).read_string(9).is_err());
+        assert!(ReadCursor::new(&vector).read_string(9).is_err());
+    }
+
+    #[test]
+    fn read_empty_string() {
+        assert_eq!("", ReadCursor::new(&[0]).read_string(9).unwrap());
+    }
+
+    #[test]
+    fn utf8_strings() {
+        let mappings: Vec<(&str, Vec<u8>)> = vec![
+            ("ä", vec![2, 0xC3, 0xA4]),
+            ("§aHi", vec![5, 0xC2, 0xA7, b'a', b'H', b'i']),
+            ("日本", vec![6, 0xE6, 0x97, 0xA5, 0xE6, 0x9C, 0xAC]),
+            ("😀", vec![4, 0xF0, 0x9F, 0x98, 0x80]),
+        ];
+
+        for (string, bytes) in mappings {
+            assert_eq!(bytes.clone(), string.encode());
+            assert_eq!(bytes, string.to_owned().encode());
+            assert_eq!(string, ReadCursor::new(&bytes).read_string(16).unwrap());
+        }
+    }
+
+    #[test]
+    fn read_string_counts_utf16_code_units() {
+        // two characters which take six bytes
+        let japanese = vec![6, 0xE6, 0x97, 0xA5, 0xE6, 0x9C, 0xAC];
+        assert_eq!("日本", ReadCursor::new(&japanese).read_string(2).unwrap());
+        assert!(ReadCursor::new(&japanese).read_string(1).is_err());
+
+        // a surrogate pair counts twice
+        let emoji = vec![4, 0xF0, 0x9F, 0x98, 0x80];
+        assert_eq!("😀", ReadCursor::new(&emoji).read_string(2).unwrap());
+        assert!(ReadCursor::new(&emoji).read_string(1).is_err());
+    }
+
+    #[test]
+    fn read_string_should_err_on_invalid_input() {
+        let mappings = vec![
+            // invalid UTF-8
+            vec![2, 0xC3, 0x28],
+            // truncated
+            vec![5, b'a', b'b'],
+            // negative length
+            vec![0xFF, 0xFF, 0xFF, 0xFF, 0x0F],
+            // more bytes than four per code unit
+            vec![
+                40, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            ],
+        ];
+
+        for mapping in mappings {
+            assert!(ReadCursor::new(&mapping).read_string(9).is_err());
+        }
     }
 }
@@ -1,30 +1,22 @@
+use super::buffer::{BufMut, ReadBytes};
+use super::error::ProtocolError;
 use super::{Decodeable, Encodeable};
-use std::collections::VecDeque;
-use std::io;
+use std::io::Read;
 
 pub type MinecraftUnsignedByte = u8;
 
-impl Decodeable<MinecraftUnsignedByte, io::Error> for VecDeque<u8> {
-    fn decode(&mut self) -> Result<MinecraftUnsignedByte, io::Error> {
-        let value = self.pop_front();
-
-        if value.is_some() {
-            Ok(value.unwrap())
-        } else {
-            Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "A minecraft byte consists of one byte, but there is none!",
-            ))
-        }
+impl<R: Read> Decodeable<MinecraftUnsignedByte, ProtocolError> for R {
+    fn decode(&mut self) -> Result<MinecraftUnsignedByte, ProtocolError> {
+        self.read_u8("unsigned byte")
     }
 }
 
 impl Encodeable for MinecraftUnsignedByte {
-    fn encode(&self) -> VecDeque<u8> {
-        VecDeque::from(vec![*self])
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        buffer.put_u8(*self);
     }
 
-    fn byte_length(&self) -> u8 {
+    fn byte_length(&self) -> usize {
         1
     }
 }
@@ -32,8 +24,8 @@ impl Encodeable for MinecraftUnsignedByte {
 #[cfg(test)]
 mod tests {
     use super::{Decodeable, Encodeable, MinecraftUnsignedByte};
-    use std::collections::VecDeque;
-    use std::io;
+    use crate::coding::buffer::ReadCursor;
+    use crate::coding::error::ProtocolError;
 
     #[test]
     fn test_decoding() {
@@ -41,7 +33,7 @@ mod tests {
             vec![(0x01, vec![0x01]), (0x00, vec![0x00])];
 
         for mapping in mappings {
-            let actual: MinecraftUnsignedByte = VecDeque::from(mapping.1).decode().unwrap();
+            let actual: MinecraftUnsignedByte = ReadCursor::new(&mapping.1).decode().unwrap();
 
             assert_eq!(mapping.0, actual);
         }
@@ -49,7 +41,7 @@ mod tests {
 
     #[test]
     fn test_decoding_err() {
-        let actual: Result<MinecraftUnsignedByte, io::Error> = VecDeque::from(vec![]).decode();
+        let actual: Result<MinecraftUnsignedByte, ProtocolError> = ReadCursor::new(&[]).decode();
 
         assert!(actual.is_err());
     }
@@ -60,7 +52,18 @@ mod tests {
             vec![(0x01, vec![0x01]), (0x00, vec![0x00])];
 
         for mapping in mappings {
-            assert_eq!(VecDeque::from(mapping.1), mapping.0.encode());
+            assert_eq!(mapping.1, mapping.0.encode());
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(value: MinecraftUnsignedByte) {
+            let encoded = value.encode();
+            let actual: MinecraftUnsignedByte = ReadCursor::new(&encoded).decode().unwrap();
+
+            prop_assert_eq!(value.to_be_bytes().to_vec(), encoded);
+            prop_assert_eq!(value, actual);
         }
     }
 }
@@ -0,0 +1,234 @@
+use super::buffer::{BufMut, ReadBytes};
+use super::error::ProtocolError;
+use super::{Decodeable, Encodeable};
+use md5::{Digest, Md5};
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::io::{self, Read};
+use std::str::FromStr;
+
+/// A 128-bit UUID, which is encoded as two big-endian longs on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Uuid(pub u128);
+
+/// A UUID which is encoded as its hyphenated string representation on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HyphenatedUuid(pub Uuid);
+
+impl Uuid {
+    /// Derives the UUID of a player in offline mode from their username, like vanilla does.
+    ///
+    /// This is a version 3 (name based, MD5) UUID of `"OfflinePlayer:" + username`.
+    pub fn offline_player(username: &str) -> Uuid {
+        let mut hasher = Md5::new();
+        hasher.update(b"OfflinePlayer:");
+        hasher.update(username.as_bytes());
+
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(&hasher.finalize());
+
+        // set the version to 3
+        bytes[6] = (bytes[6] & 0x0F) | 0x30;
+        // set the variant to IETF
+        bytes[8] = (bytes[8] & 0x3F) | 0x80;
+
+        Uuid(u128::from_be_bytes(bytes))
+    }
+
+    /// Returns the UUID without hyphens, as it is used by the session server.
+    pub fn to_simple_string(self) -> String {
+        format!("{:032x}", self.0)
+    }
+}
+
+impl fmt::Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let simple = self.to_simple_string();
+
+        write!(
+            f,
+            "{}-{}-{}-{}-{}",
+            &simple[0..8],
+            &simple[8..12],
+            &simple[12..16],
+            &simple[16..20],
+            &simple[20..32]
+        )
+    }
+}
+
+impl FromStr for Uuid {
+    type Err = io::Error;
+
+    /// Parses a UUID with or without hyphens.
+    fn from_str(value: &str) -> Result<Uuid, io::Error> {
+        let invalid_uuid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid uuid {}!", value),
+            )
+        };
+
+        let simple = match value.len() {
+            32 => value.to_owned(),
+            36 if [8, 13, 18, 23]
+                .iter()
+                .all(|&index| value.as_bytes()[index] == b'-') =>
+            {
+                value.replace('-', "")
+            }
+            _ => return Err(invalid_uuid()),
+        };
+
+        if !simple.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid_uuid());
+        }
+
+        u128::from_str_radix(&simple, 16)
+            .map(Uuid)
+            .map_err(|_| invalid_uuid())
+    }
+}
+
+impl Serialize for Uuid {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl Encodeable for Uuid {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        buffer.put_slice(&self.0.to_be_bytes());
+    }
+
+    fn byte_length(&self) -> usize {
+        16
+    }
+}
+
+impl<R: Read> Decodeable<Uuid, ProtocolError> for R {
+    fn decode(&mut self) -> Result<Uuid, ProtocolError> {
+        Ok(Uuid(u128::from_be_bytes(self.read_fixed("uuid")?)))
+    }
+}
+
+impl Encodeable for HyphenatedUuid {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        self.0.to_string().encode_into(buffer)
+    }
+
+    fn byte_length(&self) -> usize {
+        // 36 characters prefixed with their length
+        37
+    }
+}
+
+impl<R: Read> Decodeable<HyphenatedUuid, ProtocolError> for R {
+    fn decode(&mut self) -> Result<HyphenatedUuid, ProtocolError> {
+        use super::string::ReadString;
+
+        let value = self.read_string(36)?;
+
+        match value.parse() {
+            Ok(uuid) => Ok(HyphenatedUuid(uuid)),
+            Err(_) => Err(ProtocolError::invalid_value("uuid", value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HyphenatedUuid, Uuid};
+    use crate::coding::buffer::ReadCursor;
+    use crate::coding::{Decodeable, Encodeable};
+
+    #[test]
+    fn test_offline_player_uuid() {
+        // as generated by vanilla servers in offline mode
+        let mappings = vec![
+            ("jens1o", "e52eb9a4-2e5e-3114-853a-6b9492f4e841"),
+            ("Notch", "b50ad385-829d-3141-a216-7e7d7539ba7f"),
+        ];
+
+        for (username, expected) in mappings {
+            let uuid = Uuid::offline_player(username);
+
+            assert_eq!(expected, uuid.to_string());
+            // version 3
+            assert_eq!(3, (uuid.0 >> 76) & 0xF);
+        }
+
+        assert_ne!(
+            Uuid::offline_player("jens1o"),
+            Uuid::offline_player("Jens1o")
+        );
+    }
+
+    #[test]
+    fn test_parse_uuid() {
+        let expected = Uuid(0x8e38_3e9f_608e_4556_97c9_6131_2c74_1ea0);
+
+        let mappings = vec![
+            "8e383e9f-608e-4556-97c9-61312c741ea0",
+            "8e383e9f608e455697c961312c741ea0",
+            "8E383E9F608E455697C961312C741EA0",
+        ];
+
+        for mapping in mappings {
+            assert_eq!(expected, mapping.parse::<Uuid>().unwrap());
+        }
+
+        assert_eq!(
+            "8e383e9f608e455697c961312c741ea0",
+            expected.to_simple_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_uuid() {
+        let mappings = vec![
+            "",
+            "8e383e9f-608e-4556-97c9-61312c741ea",
+            "8e383e9f608e-4556-97c9-61312c741ea0-",
+            "zz383e9f608e455697c961312c741ea0",
+            "+e383e9f608e455697c961312c741ea0",
+        ];
+
+        for mapping in mappings {
+            assert!(mapping.parse::<Uuid>().is_err());
+        }
+    }
+
+    #[test]
+    fn test_binary_uuid() {
+        let uuid = Uuid(0x8e38_3e9f_608e_4556_97c9_6131_2c74_1ea0);
+        let bytes = vec![
+            0x8e, 0x38, 0x3e, 0x9f, 0x60, 0x8e, 0x45, 0x56, 0x97, 0xc9, 0x61, 0x31, 0x2c, 0x74,
+            0x1e, 0xa0,
+        ];
+
+        assert_eq!(bytes.clone(), uuid.encode());
+
+        let actual: Uuid = ReadCursor::new(&bytes).decode().unwrap();
+        assert_eq!(uuid, actual);
+
+        let too_short: Result<Uuid, _> = ReadCursor::new(&[0; 15]).decode();
+        assert!(too_short.is_err());
+    }
+
+    #[test]
+    fn test_hyphenated_uuid() {
+        let uuid = HyphenatedUuid(Uuid(0x8e38_3e9f_608e_4556_97c9_6131_2c74_1ea0));
+
+        let mut bytes = vec![36];
+        bytes.extend(b"8e383e9f-608e-4556-97c9-61312c741ea0");
+
+        assert_eq!(bytes.clone(), uuid.encode());
+
+        let actual: HyphenatedUuid = ReadCursor::new(&bytes).decode().unwrap();
+        assert_eq!(uuid, actual);
+    }
+}
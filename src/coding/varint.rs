@@ -1,11 +1,11 @@
+use super::buffer::{BufMut, ReadBytes};
+use super::error::ProtocolError;
 use super::{Decodeable, Encodeable};
-use std::collections::VecDeque;
 use std::fmt;
-use std::io::{self, Read};
-use std::net::TcpStream;
-use std::{i32, u8};
+use std::i32;
+use std::io::Read;
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy)]
 pub struct Varint(pub i32);
 
 impl fmt::Debug for Varint {
@@ -32,112 +32,48 @@ impl PartialEq<i32> for Varint {
     }
 }
 
-impl Decodeable<Varint, io::Error> for TcpStream {
-    fn decode(&mut self) -> Result<Varint, io::Error> {
-        // see https://wiki.vg/Protocol#VarInt_and_VarLong
-        let mut num_of_reads: u8 = 0;
-        let mut result: i32 = 0;
-
-        loop {
-            let mut buffer = [0; 1];
-            self.read_exact(&mut buffer)?;
-            let byte = buffer[0];
-
-            let value = byte & 0b0111_1111;
-            result |= i32::from(value) << (7 * num_of_reads);
-
-            num_of_reads += 1;
-            if num_of_reads > 5 {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "VarInt is too big",
-                ));
-            }
-
-            if (byte & 0b1000_0000) == 0 {
-                break;
-            }
-        }
-
-        Ok(Varint(result))
-    }
-}
+/// The maximum number of bytes a varint may occupy.
+pub const MAX_VARINT_LENGTH: usize = 5;
 
-impl Decodeable<Varint, io::Error> for VecDeque<u8> {
-    fn decode(&mut self) -> Result<Varint, io::Error> {
+impl<R: Read> Decodeable<Varint, ProtocolError> for R {
+    fn decode(&mut self) -> Result<Varint, ProtocolError> {
         // see https://wiki.vg/Protocol#VarInt_and_VarLong
-        let mut num_of_reads: u8 = 0;
         let mut result: i32 = 0;
 
-        #[inline(always)]
-        fn get_byte_or_fail(vector: &mut VecDeque<u8>) -> Result<u8, io::Error> {
-            let value = vector.pop_front();
-
-            if value.is_some() {
-                Ok(value.unwrap())
-            } else {
-                Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "Not enough bytes to decode a Varint!",
-                ))
-            }
-        };
+        for num_of_reads in 0..MAX_VARINT_LENGTH {
+            let byte = self.read_u8("Varint")?;
 
-        loop {
-            let byte = get_byte_or_fail(self)?;
             let value = byte & 0b0111_1111;
             result |= i32::from(value) << (7 * num_of_reads);
 
-            num_of_reads += 1;
-            if num_of_reads > 5 {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "VarInt is too big",
-                ));
-            }
-
             if (byte & 0b1000_0000) == 0 {
-                break;
+                return Ok(Varint(result));
             }
         }
 
-        Ok(Varint(result))
+        Err(ProtocolError::TooLong {
+            type_name: "Varint",
+            length: MAX_VARINT_LENGTH + 1,
+            max_length: MAX_VARINT_LENGTH,
+        })
     }
 }
 
-impl Encodeable for Varint {
-    fn encode(&self) -> VecDeque<u8> {
-        let mut result = VecDeque::with_capacity(7);
-
-        let mut value = (*self).0 as u32;
-
-        loop {
-            let mut temp = value & 0b0111_1111;
-            value >>= 7;
-            if value != 0 {
-                temp |= 0b1000_0000;
-            }
-
-            result.push_back(temp as u8);
+impl Varint {
+    /// Returns how many bytes the varint takes up on the wire.
+    pub fn encoded_length(self) -> usize {
+        let value = self.0 as u32;
 
-            if value == 0 {
-                break;
-            }
-        }
+        // every byte carries 7 bits of the value
+        let significant_bits = 32 - value.leading_zeros() as usize;
 
-        result
-    }
-
-    fn byte_length(&self) -> u8 {
-        7
+        significant_bits.max(1).div_ceil(7)
     }
 }
 
-impl Encodeable for usize {
-    fn encode(&self) -> VecDeque<u8> {
-        let mut result = VecDeque::with_capacity(7);
-
-        let mut value = *self;
+impl Encodeable for Varint {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        let mut value = self.0 as u32;
 
         loop {
             let mut temp = value & 0b0111_1111;
@@ -146,27 +82,26 @@ impl Encodeable for usize {
                 temp |= 0b1000_0000;
             }
 
-            result.push_front(temp as u8);
+            buffer.put_u8(temp as u8);
 
             if value == 0 {
                 break;
             }
         }
-
-        result
     }
 
-    fn byte_length(&self) -> u8 {
-        7
+    fn byte_length(&self) -> usize {
+        self.encoded_length()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{Decodeable, Encodeable, Varint};
-    use std::collections::VecDeque;
+    use crate::coding::buffer::ReadCursor;
+    use crate::coding::error::ProtocolError;
     use std::i32;
-    use std::io;
+    use std::io::{self, Read};
 
     #[test]
     fn test_read_varint_from_vec() {
@@ -182,23 +117,43 @@ mod tests {
         ];
 
         for mapping in mappings {
-            let expected: Varint = VecDeque::from(mapping.1).decode().unwrap();
+            let expected: Varint = ReadCursor::new(&mapping.1).decode().unwrap();
             assert_eq!(mapping.0, expected);
         }
     }
 
     #[test]
-    fn test_read_err() {
-        let mappings: Vec<Vec<u8>> = vec![vec![]];
+    fn test_read_varint_from_reader() {
+        // the bytes of a varint may be spread over several reads, like on a socket
+        let mut reader = io::Cursor::new(vec![0x80]).chain(&[0x01, 0x7f][..]);
 
-        for mapping in mappings {
-            let expected: Result<Varint, io::Error> = VecDeque::from(mapping).decode();
-            assert!(expected.is_err());
+        let first: Varint = reader.decode().unwrap();
+        let second: Varint = reader.decode().unwrap();
+        let third: Result<Varint, ProtocolError> = reader.decode();
 
-            let err = expected.unwrap_err();
+        assert_eq!(128, first);
+        assert_eq!(127, second);
+        assert_eq!(Err(ProtocolError::unexpected_eof("Varint")), third);
+    }
 
-            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
-            assert_eq!(err.to_string(), "Not enough bytes to decode a Varint!");
+    #[test]
+    fn test_read_err() {
+        let mappings: Vec<(Vec<u8>, ProtocolError)> = vec![
+            (vec![], ProtocolError::unexpected_eof("Varint")),
+            (vec![0x80, 0x80], ProtocolError::unexpected_eof("Varint")),
+            (
+                vec![0x80, 0x80, 0x80, 0x80, 0x80, 0x01],
+                ProtocolError::TooLong {
+                    type_name: "Varint",
+                    length: 6,
+                    max_length: 5,
+                },
+            ),
+        ];
+
+        for (bytes, error) in mappings {
+            let actual: Result<Varint, ProtocolError> = ReadCursor::new(&bytes).decode();
+            assert_eq!(Err(error), actual);
         }
     }
 
@@ -210,7 +165,7 @@ mod tests {
         ];
 
         for mapping in mappings {
-            assert_eq!(VecDeque::from(mapping.1), Varint(mapping.0).encode());
+            assert_eq!(mapping.1, Varint(mapping.0).encode());
         }
     }
 
@@ -226,7 +181,30 @@ mod tests {
         ];
 
         for mapping in mappings {
-            assert_eq!(VecDeque::from(mapping.1), Varint(mapping.0).encode());
+            assert_eq!(mapping.1, Varint(mapping.0).encode());
+        }
+    }
+
+    #[test]
+    fn test_encoded_length() {
+        for value in vec![
+            0,
+            1,
+            127,
+            128,
+            255,
+            2097151,
+            2097152,
+            2147483647,
+            -1,
+            -2147483648,
+        ] {
+            assert_eq!(
+                Varint(value).encode().len(),
+                Varint(value).encoded_length(),
+                "{}",
+                value
+            );
         }
     }
 
@@ -0,0 +1,208 @@
+use super::buffer::{BufMut, ReadBytes};
+use super::error::ProtocolError;
+use super::{Decodeable, Encodeable};
+use std::fmt;
+use std::io::Read;
+
+#[derive(PartialEq, Clone, Copy)]
+pub struct Varlong(pub i64);
+
+impl fmt::Debug for Varlong {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // write the output in big hexadecimal notation
+        write!(f, "Varlong({:#X})", self.0)
+    }
+}
+
+impl fmt::Display for Varlong {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PartialEq<Varlong> for i64 {
+    fn eq(&self, other: &Varlong) -> bool {
+        *self == other.0
+    }
+}
+impl PartialEq<i64> for Varlong {
+    fn eq(&self, other: &i64) -> bool {
+        self.0 == *other
+    }
+}
+
+/// The maximum number of bytes a varlong may occupy.
+pub const MAX_VARLONG_LENGTH: usize = 10;
+
+impl<R: Read> Decodeable<Varlong, ProtocolError> for R {
+    fn decode(&mut self) -> Result<Varlong, ProtocolError> {
+        // see https://wiki.vg/Protocol#VarInt_and_VarLong
+        let mut result: i64 = 0;
+
+        for num_of_reads in 0..MAX_VARLONG_LENGTH {
+            let byte = self.read_u8("Varlong")?;
+
+            let value = byte & 0b0111_1111;
+            result |= i64::from(value) << (7 * num_of_reads);
+
+            if (byte & 0b1000_0000) == 0 {
+                return Ok(Varlong(result));
+            }
+        }
+
+        Err(ProtocolError::TooLong {
+            type_name: "Varlong",
+            length: MAX_VARLONG_LENGTH + 1,
+            max_length: MAX_VARLONG_LENGTH,
+        })
+    }
+}
+
+impl Varlong {
+    /// Returns how many bytes the varlong takes up on the wire.
+    pub fn encoded_length(self) -> usize {
+        let value = self.0 as u64;
+
+        // every byte carries 7 bits of the value
+        let significant_bits = 64 - value.leading_zeros() as usize;
+
+        significant_bits.max(1).div_ceil(7)
+    }
+}
+
+impl Encodeable for Varlong {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        let mut value = self.0 as u64;
+
+        loop {
+            let mut temp = value & 0b0111_1111;
+            value >>= 7;
+            if value != 0 {
+                temp |= 0b1000_0000;
+            }
+
+            buffer.put_u8(temp as u8);
+
+            if value == 0 {
+                break;
+            }
+        }
+    }
+
+    fn byte_length(&self) -> usize {
+        self.encoded_length()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Decodeable, Encodeable, Varlong};
+    use crate::coding::buffer::ReadCursor;
+    use crate::coding::error::ProtocolError;
+    use std::i64;
+    use std::io::{self, Read};
+
+    fn mappings() -> Vec<(i64, Vec<u8>)> {
+        vec![
+            (0, vec![0x00]),
+            (1, vec![0x01]),
+            (127, vec![0x7f]),
+            (128, vec![0x80, 0x01]),
+            (255, vec![0xff, 0x01]),
+            (2147483647, vec![0xff, 0xff, 0xff, 0xff, 0x07]),
+            (
+                9223372036854775807,
+                vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f],
+            ),
+            (
+                -1,
+                vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01],
+            ),
+            (
+                -2147483648,
+                vec![0x80, 0x80, 0x80, 0x80, 0xf8, 0xff, 0xff, 0xff, 0xff, 0x01],
+            ),
+            (
+                -9223372036854775808,
+                vec![0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_read_varlong_from_vec() {
+        for mapping in mappings() {
+            let expected: Varlong = ReadCursor::new(&mapping.1).decode().unwrap();
+            assert_eq!(mapping.0, expected);
+        }
+    }
+
+    #[test]
+    fn test_read_varlong_from_reader() {
+        // the bytes of a varlong may be spread over several reads, like on a socket
+        let mut reader = io::Cursor::new(vec![0x80]).chain(&[0x01, 0x7f][..]);
+
+        let first: Varlong = reader.decode().unwrap();
+        let second: Varlong = reader.decode().unwrap();
+        let third: Result<Varlong, ProtocolError> = reader.decode();
+
+        assert_eq!(128, first);
+        assert_eq!(127, second);
+        assert_eq!(Err(ProtocolError::unexpected_eof("Varlong")), third);
+    }
+
+    #[test]
+    fn test_read_err() {
+        let mappings: Vec<(Vec<u8>, ProtocolError)> = vec![
+            (vec![], ProtocolError::unexpected_eof("Varlong")),
+            (vec![0x80, 0x80], ProtocolError::unexpected_eof("Varlong")),
+            (
+                vec![
+                    0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01,
+                ],
+                ProtocolError::TooLong {
+                    type_name: "Varlong",
+                    length: 11,
+                    max_length: 10,
+                },
+            ),
+        ];
+
+        for (bytes, error) in mappings {
+            let actual: Result<Varlong, ProtocolError> = ReadCursor::new(&bytes).decode();
+            assert_eq!(Err(error), actual);
+        }
+    }
+
+    #[test]
+    fn test_write_vec_from_varlong() {
+        for mapping in mappings() {
+            assert_eq!(mapping.1, Varlong(mapping.0).encode());
+        }
+    }
+
+    #[test]
+    fn test_encoded_length() {
+        for (value, bytes) in mappings() {
+            assert_eq!(bytes.len(), Varlong(value).encoded_length(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_varlong_and_i64_are_same() {
+        for i in (i64::min_value()..=i64::max_value()).take(1000) {
+            assert_eq!(i, Varlong(i));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(value: i64) {
+            let encoded = Varlong(value).encode();
+            let actual: Varlong = ReadCursor::new(&encoded).decode().unwrap();
+
+            prop_assert_eq!(Varlong(value).encoded_length(), encoded.len());
+            prop_assert_eq!(value, actual);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::coding::buffer::ReadCursor;
+use crate::coding::varint::Varint;
+use crate::coding::{Decodeable, Encodeable};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::io::{self, Read, Write};
+
+/// The biggest size a packet may have after decompressing it, as enforced by vanilla servers.
+pub const MAX_UNCOMPRESSED_SIZE: usize = 2_097_152;
+
+/// Compresses the packet id and data of a packet, appending them to `output`.
+///
+/// The compressed data is prefixed with the uncompressed length. Packets which are too small to
+/// be compressed are instead prefixed with a zero by the caller.
+pub fn compress(uncompressed: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+    Varint(uncompressed.len() as i32).encode_into(output);
+
+    // the encoder appends the compressed data right after the length
+    let mut encoder = ZlibEncoder::new(output, Compression::default());
+    encoder.write_all(uncompressed)?;
+    encoder.finish()?;
+
+    Ok(())
+}
+
+/// Reverses `compress`, returning the packet id and data of the packet.
+pub fn decompress(data: &[u8], threshold: usize) -> io::Result<Vec<u8>> {
+    let mut data = ReadCursor::new(data);
+    let data_length: Varint = data.decode()?;
+
+    if data_length.0 == 0 {
+        return Ok(data.read_remaining().to_vec());
+    }
+
+    if data_length.0 < 0 || data_length.0 as usize > MAX_UNCOMPRESSED_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid size of compressed packet: {}", data_length.0),
+        ));
+    }
+
+    let data_length = data_length.0 as usize;
+
+    if data_length < threshold {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Packet of size {} was compressed although the threshold is {}.",
+                data_length, threshold
+            ),
+        ));
+    }
+
+    let mut decoder = ZlibDecoder::new(data.read_remaining()).take(data_length as u64);
+
+    let mut result = Vec::with_capacity(data_length);
+    decoder.read_to_end(&mut result)?;
+
+    if result.len() != data_length {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Compressed packet announced {} bytes, but contained {}.",
+                data_length,
+                result.len()
+            ),
+        ));
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress, decompress};
+    use std::io;
+
+    fn compressed(data: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+        compress(data, &mut output).unwrap();
+
+        output
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data: Vec<u8> = (0..1000).map(|x| (x % 7) as u8).collect();
+
+        let compressed = compressed(&data);
+
+        // 1000 as a varint
+        assert_eq!(vec![0xE8, 0x07], compressed[..2].to_vec());
+        assert!(compressed.len() < data.len());
+
+        assert_eq!(data, decompress(&compressed, 256).unwrap());
+    }
+
+    #[test]
+    fn test_compress_appends_to_output() {
+        let mut output = vec![0xAA];
+        compress(&[0x01; 300], &mut output).unwrap();
+
+        assert_eq!(vec![0xAA, 0xAC, 0x02], output[..3].to_vec());
+        assert_eq!(vec![0x01; 300], decompress(&output[1..], 256).unwrap());
+    }
+
+    #[test]
+    fn test_uncompressed_round_trip() {
+        assert_eq!(
+            vec![0x00, 0x01],
+            decompress(&[0x00, 0x00, 0x01], 256).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decompress_rejects_packets_below_threshold() {
+        let compressed = compressed(&[0x01; 64]);
+
+        let err = decompress(&compressed, 256).unwrap_err();
+
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn test_decompress_rejects_wrong_size() {
+        let mut compressed = compressed(&[0x01; 300]);
+        // claim the packet is bigger than it actually is
+        compressed[0] = 0xFF;
+
+        assert!(decompress(&compressed, 256).is_err());
+    }
+}
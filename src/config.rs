@@ -0,0 +1,596 @@
+mod properties;
+
+use self::properties::Properties;
+use crate::coding::{gamemode::Gamemode, level_type::LevelType};
+use crate::difficulty::Difficulty;
+use crate::world::generator::FlatPreset;
+use log::LevelFilter;
+use std::fs;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Where the config is stored, next to the server like vanilla does.
+pub const PROPERTIES_PATH: &str = "server.properties";
+
+/// Settings of the server that are the same for all connections.
+pub struct ServerConfig {
+    /// The address to listen on, `None` listens on all interfaces.
+    pub server_ip: Option<IpAddr>,
+    pub server_port: u16,
+    /// Packets of at least this size (in bytes) are compressed, `None` disables compression.
+    pub network_compression_threshold: Option<usize>,
+    /// The message of the day, shown in the server list.
+    pub motd: String,
+    pub max_players: u16,
+    /// Whether players need to be authenticated by the session server and the connection is encrypted.
+    pub online_mode: bool,
+    /// `Hardcore` is stored as survival with `hardcore=true`, like vanilla does.
+    pub gamemode: Gamemode,
+    pub difficulty: Difficulty,
+    /// The directory of a vanilla world to load chunks from, relative to the working
+    /// directory. Chunks that are not stored there are generated.
+    pub level_name: String,
+    pub level_type: LevelType,
+    /// The superflat preset if the level type is flat, an empty one uses vanilla's default.
+    pub generator_settings: String,
+    /// Worlds with the same seed are generated the same.
+    ///
+    /// A random seed is chosen and written to the file if none is given, as generated
+    /// chunks are not saved.
+    pub level_seed: i64,
+    /// How many chunks around a player are sent to them in each direction.
+    pub view_distance: u8,
+    /// Not known to vanilla, which ignores it.
+    pub log_level: LevelFilter,
+}
+
+impl Default for ServerConfig {
+    fn default() -> ServerConfig {
+        ServerConfig {
+            server_ip: None,
+            server_port: 25565,
+            network_compression_threshold: Some(256),
+            motd: "Java edition doesn't necessarily mean everything is run by Java.".to_owned(),
+            max_players: 20,
+            online_mode: true,
+            gamemode: Gamemode::Creative,
+            difficulty: Difficulty::Peaceful,
+            level_name: "world".to_owned(),
+            level_type: LevelType::Flat,
+            generator_settings: String::new(),
+            level_seed: rand::random(),
+            view_distance: 10,
+            log_level: if cfg!(debug_assertions) {
+                LevelFilter::Trace
+            } else {
+                LevelFilter::Info
+            },
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Reads the config from the given properties file, creating it with the default values
+    /// on first run. Keys missing in the file are added to it, unknown keys are kept.
+    pub fn load_or_create<P: AsRef<Path>>(path: P) -> io::Result<ServerConfig> {
+        let path = path.as_ref();
+
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(error),
+        };
+
+        let mut properties = Properties::parse(&content)?;
+        let config = ServerConfig::from_properties(&properties)?;
+
+        config.write_properties(&mut properties);
+
+        let new_content = properties.to_string();
+
+        if new_content != content {
+            fs::write(path, new_content)?;
+        }
+
+        Ok(config)
+    }
+
+    /// Reads the config from the given properties, using the default for every missing key.
+    fn from_properties(properties: &Properties) -> io::Result<ServerConfig> {
+        let default = ServerConfig::default();
+
+        let server_ip = match properties.get("server-ip") {
+            None | Some("") => None,
+            Some(value) => Some(parse_value(
+                "server-ip",
+                value,
+                "an IP address",
+                IpAddr::from_str,
+            )?),
+        };
+
+        let network_compression_threshold = match properties.get("network-compression-threshold") {
+            None => default.network_compression_threshold,
+            Some(value) => {
+                let threshold: i32 = parse_value(
+                    "network-compression-threshold",
+                    value,
+                    "a number, or -1 to disable compression",
+                    i32::from_str,
+                )?;
+
+                if threshold < 0 {
+                    None
+                } else {
+                    Some(threshold as usize)
+                }
+            }
+        };
+
+        let level_type = parse_property(
+            properties,
+            "level-type",
+            "DEFAULT, FLAT, LARGEBIOMES, AMPLIFIED or DEFAULT_1_1",
+            default.level_type,
+            parse_level_type,
+        )?;
+
+        let generator_settings = properties
+            .get("generator-settings")
+            .map(str::to_owned)
+            .unwrap_or(default.generator_settings);
+
+        if level_type == LevelType::Flat {
+            parse_value(
+                "generator-settings",
+                &generator_settings,
+                "a superflat preset like minecraft:bedrock,2*minecraft:dirt,minecraft:grass_block;minecraft:plains",
+                FlatPreset::from_str,
+            )?;
+        }
+
+        let level_seed = match properties.get("level-seed").map(str::trim) {
+            None | Some("") => default.level_seed,
+            Some(value) => parse_seed(value),
+        };
+
+        let gamemode = parse_property(
+            properties,
+            "gamemode",
+            "0 to 3 or survival, creative, adventure or spectator",
+            default.gamemode,
+            parse_gamemode,
+        )?;
+
+        let hardcore = parse_property(
+            properties,
+            "hardcore",
+            "true or false",
+            gamemode == Gamemode::Hardcore,
+            bool::from_str,
+        )?;
+
+        Ok(ServerConfig {
+            server_ip,
+            server_port: parse_property(
+                properties,
+                "server-port",
+                "a port between 1 and 65535",
+                default.server_port,
+                |value| match value.parse() {
+                    Ok(0) => Err(()),
+                    Ok(port) => Ok(port),
+                    Err(_) => Err(()),
+                },
+            )?,
+            network_compression_threshold,
+            motd: properties
+                .get("motd")
+                .map(str::to_owned)
+                .unwrap_or(default.motd),
+            max_players: parse_property(
+                properties,
+                "max-players",
+                "a number between 0 and 65535",
+                default.max_players,
+                u16::from_str,
+            )?,
+            online_mode: parse_property(
+                properties,
+                "online-mode",
+                "true or false",
+                default.online_mode,
+                bool::from_str,
+            )?,
+            gamemode: if hardcore {
+                Gamemode::Hardcore
+            } else {
+                gamemode
+            },
+            difficulty: parse_property(
+                properties,
+                "difficulty",
+                "0 to 3 or peaceful, easy, normal or hard",
+                default.difficulty,
+                parse_difficulty,
+            )?,
+            level_name: properties
+                .get("level-name")
+                .map(str::to_owned)
+                .unwrap_or(default.level_name),
+            level_type,
+            generator_settings,
+            level_seed,
+            view_distance: parse_property(
+                properties,
+                "view-distance",
+                "a number between 3 and 15",
+                default.view_distance,
+                |value| match value.parse() {
+                    Ok(view_distance @ 3..=15) => Ok(view_distance),
+                    _ => Err(()),
+                },
+            )?,
+            log_level: parse_property(
+                properties,
+                "log-level",
+                "off, error, warn, info, debug or trace",
+                default.log_level,
+                LevelFilter::from_str,
+            )?,
+        })
+    }
+
+    /// Stores the config in the given properties, in the format vanilla uses.
+    fn write_properties(&self, properties: &mut Properties) {
+        let server_ip = self
+            .server_ip
+            .map(|server_ip| server_ip.to_string())
+            .unwrap_or_default();
+
+        let network_compression_threshold = self
+            .network_compression_threshold
+            .map(|threshold| threshold as i64)
+            .unwrap_or(-1);
+
+        let gamemode = match self.gamemode {
+            Gamemode::Survival | Gamemode::Hardcore => "survival",
+            Gamemode::Creative => "creative",
+            Gamemode::Adventure => "adventure",
+            Gamemode::Spectator => "spectator",
+        };
+
+        let difficulty = match self.difficulty {
+            Difficulty::Peaceful => "peaceful",
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        };
+
+        let level_type = match self.level_type {
+            LevelType::Default => "DEFAULT",
+            LevelType::Flat => "FLAT",
+            LevelType::LargeBiomes => "LARGEBIOMES",
+            LevelType::Amplified => "AMPLIFIED",
+            LevelType::Default1_1 => "DEFAULT_1_1",
+        };
+
+        properties.set("server-ip", &server_ip);
+        properties.set("server-port", &self.server_port.to_string());
+        properties.set(
+            "network-compression-threshold",
+            &network_compression_threshold.to_string(),
+        );
+        properties.set("motd", &self.motd);
+        properties.set("max-players", &self.max_players.to_string());
+        properties.set("online-mode", &self.online_mode.to_string());
+        properties.set("gamemode", gamemode);
+        properties.set(
+            "hardcore",
+            &(self.gamemode == Gamemode::Hardcore).to_string(),
+        );
+        properties.set("difficulty", difficulty);
+        properties.set("level-name", &self.level_name);
+        properties.set("level-type", level_type);
+        properties.set("generator-settings", &self.generator_settings);
+        properties.set("level-seed", &self.level_seed.to_string());
+        properties.set("view-distance", &self.view_distance.to_string());
+        properties.set("log-level", &self.log_level.to_string().to_lowercase());
+    }
+
+    pub fn bind_address(&self) -> SocketAddr {
+        SocketAddr::new(
+            self.server_ip.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            self.server_port,
+        )
+    }
+}
+
+fn parse_property<T, E, F>(
+    properties: &Properties,
+    key: &str,
+    expected: &str,
+    default: T,
+    parse: F,
+) -> io::Result<T>
+where
+    F: FnOnce(&str) -> Result<T, E>,
+{
+    match properties.get(key) {
+        Some(value) => parse_value(key, value, expected, parse),
+        None => Ok(default),
+    }
+}
+
+fn parse_value<T, E, F>(key: &str, value: &str, expected: &str, parse: F) -> io::Result<T>
+where
+    F: FnOnce(&str) -> Result<T, E>,
+{
+    parse(value.trim()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Invalid value \"{}\" for {} in {}, expected {}.",
+                value, key, PROPERTIES_PATH, expected
+            ),
+        )
+    })
+}
+
+fn parse_gamemode(value: &str) -> Result<Gamemode, ()> {
+    match value.to_lowercase().as_str() {
+        "0" | "survival" => Ok(Gamemode::Survival),
+        "1" | "creative" => Ok(Gamemode::Creative),
+        "2" | "adventure" => Ok(Gamemode::Adventure),
+        "3" | "spectator" => Ok(Gamemode::Spectator),
+        _ => Err(()),
+    }
+}
+
+fn parse_difficulty(value: &str) -> Result<Difficulty, ()> {
+    match value.to_lowercase().as_str() {
+        "0" | "peaceful" => Ok(Difficulty::Peaceful),
+        "1" | "easy" => Ok(Difficulty::Easy),
+        "2" | "normal" => Ok(Difficulty::Normal),
+        "3" | "hard" => Ok(Difficulty::Hard),
+        _ => Err(()),
+    }
+}
+
+fn parse_level_type(value: &str) -> Result<LevelType, ()> {
+    match value.to_uppercase().as_str() {
+        "DEFAULT" => Ok(LevelType::Default),
+        "FLAT" => Ok(LevelType::Flat),
+        "LARGEBIOMES" => Ok(LevelType::LargeBiomes),
+        "AMPLIFIED" => Ok(LevelType::Amplified),
+        "DEFAULT_1_1" => Ok(LevelType::Default1_1),
+        _ => Err(()),
+    }
+}
+
+/// Reads a seed like vanilla, which uses the hash code of the text if it is no number.
+fn parse_seed(value: &str) -> i64 {
+    value.parse().unwrap_or_else(|_| {
+        let hash = value.encode_utf16().fold(0i32, |hash, unit| {
+            hash.wrapping_mul(31).wrapping_add(i32::from(unit))
+        });
+
+        i64::from(hash)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::properties::Properties;
+    use super::{parse_seed, ServerConfig};
+    use crate::coding::{gamemode::Gamemode, level_type::LevelType};
+    use crate::difficulty::Difficulty;
+    use log::LevelFilter;
+    use std::fs;
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+    #[test]
+    fn test_read_vanilla_properties() {
+        let properties = Properties::parse(
+            "#Minecraft server properties\n\
+             #Sat Jan 05 13:37:00 CET 2019\n\
+             spawn-protection=16\n\
+             server-ip=127.0.0.1\n\
+             gamemode=0\n\
+             difficulty=hard\n\
+             level-name=survival\n\
+             level-type=DEFAULT\n\
+             generator-settings=\n\
+             level-seed=-1234567890123\n\
+             view-distance=8\n\
+             network-compression-threshold=-1\n\
+             max-players=100\n\
+             server-port=25566\n\
+             online-mode=false\n\
+             motd=\\u00A7aA Minecraft Server\n",
+        )
+        .unwrap();
+
+        let config = ServerConfig::from_properties(&properties).unwrap();
+
+        assert_eq!(Some(IpAddr::V4(Ipv4Addr::LOCALHOST)), config.server_ip);
+        assert_eq!(Gamemode::Survival, config.gamemode);
+        assert_eq!(Difficulty::Hard, config.difficulty);
+        assert_eq!("survival", config.level_name);
+        assert_eq!(LevelType::Default, config.level_type);
+        assert_eq!(-1234567890123, config.level_seed);
+        assert_eq!(8, config.view_distance);
+        assert_eq!(None, config.network_compression_threshold);
+        assert_eq!(100, config.max_players);
+        assert!(!config.online_mode);
+        assert_eq!("§aA Minecraft Server", config.motd);
+        assert_eq!(
+            "127.0.0.1:25566".parse::<SocketAddr>().unwrap(),
+            config.bind_address()
+        );
+        // not set, so the default is used
+        assert_eq!(ServerConfig::default().log_level, config.log_level);
+    }
+
+    #[test]
+    fn test_invalid_values_are_rejected() {
+        let mappings = vec![
+            "server-ip=localhost",
+            "server-port=0",
+            "server-port=65536",
+            "max-players=-1",
+            "online-mode=yes",
+            "hardcore=1",
+            "gamemode=4",
+            "difficulty=insane",
+            "level-type=customized",
+            "generator-settings=minecraft:unknown",
+            "view-distance=2",
+            "view-distance=16",
+            "network-compression-threshold=none",
+            "log-level=verbose",
+        ];
+
+        for mapping in mappings {
+            let properties = Properties::parse(mapping).unwrap();
+            let error = ServerConfig::from_properties(&properties)
+                .err()
+                .unwrap_or_else(|| panic!("{} was accepted", mapping));
+
+            let key = mapping.split('=').next().unwrap();
+            assert!(error.to_string().contains(key), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_properties_round_trip() {
+        let config = ServerConfig {
+            server_ip: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            network_compression_threshold: None,
+            motd: "§cHello=World".to_owned(),
+            gamemode: Gamemode::Spectator,
+            difficulty: Difficulty::Normal,
+            level_name: "worlds/creative".to_owned(),
+            level_type: LevelType::Flat,
+            generator_settings: "minecraft:bedrock,3*minecraft:sand;minecraft:desert".to_owned(),
+            level_seed: 42,
+            view_distance: 4,
+            log_level: LevelFilter::Warn,
+            ..Default::default()
+        };
+
+        let mut properties = Properties::default();
+        config.write_properties(&mut properties);
+
+        assert_eq!(Some("-1"), properties.get("network-compression-threshold"));
+        assert_eq!(Some("spectator"), properties.get("gamemode"));
+        assert_eq!(Some("false"), properties.get("hardcore"));
+        assert_eq!(Some("normal"), properties.get("difficulty"));
+        assert_eq!(Some("FLAT"), properties.get("level-type"));
+        assert_eq!(Some("warn"), properties.get("log-level"));
+
+        let properties = Properties::parse(&properties.to_string()).unwrap();
+        let read = ServerConfig::from_properties(&properties).unwrap();
+
+        assert_eq!(config.server_ip, read.server_ip);
+        assert_eq!(
+            config.network_compression_threshold,
+            read.network_compression_threshold
+        );
+        assert_eq!(config.motd, read.motd);
+        assert_eq!(config.gamemode, read.gamemode);
+        assert_eq!(config.difficulty, read.difficulty);
+        assert_eq!(config.level_name, read.level_name);
+        assert_eq!(config.level_type, read.level_type);
+        assert_eq!(config.generator_settings, read.generator_settings);
+        assert_eq!(config.level_seed, read.level_seed);
+        assert_eq!(config.view_distance, read.view_distance);
+        assert_eq!(config.log_level, read.log_level);
+    }
+
+    #[test]
+    fn test_gamemodes_and_difficulties_round_trip() {
+        let gamemodes = vec![
+            (Gamemode::Survival, "survival", "false"),
+            (Gamemode::Creative, "creative", "false"),
+            (Gamemode::Adventure, "adventure", "false"),
+            (Gamemode::Spectator, "spectator", "false"),
+            (Gamemode::Hardcore, "survival", "true"),
+        ];
+        let difficulties = vec![
+            (Difficulty::Peaceful, "peaceful"),
+            (Difficulty::Easy, "easy"),
+            (Difficulty::Normal, "normal"),
+            (Difficulty::Hard, "hard"),
+        ];
+
+        for (gamemode, gamemode_name, hardcore) in gamemodes {
+            for &(difficulty, difficulty_name) in &difficulties {
+                let config = ServerConfig {
+                    gamemode,
+                    difficulty,
+                    ..Default::default()
+                };
+
+                let mut properties = Properties::default();
+                config.write_properties(&mut properties);
+
+                assert_eq!(Some(gamemode_name), properties.get("gamemode"));
+                assert_eq!(Some(hardcore), properties.get("hardcore"));
+                assert_eq!(Some(difficulty_name), properties.get("difficulty"));
+
+                let properties = Properties::parse(&properties.to_string()).unwrap();
+                let read = ServerConfig::from_properties(&properties).unwrap();
+
+                assert_eq!(gamemode, read.gamemode);
+                assert_eq!(difficulty, read.difficulty);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_seed() {
+        let mappings = vec![
+            ("42", 42),
+            ("-9223372036854775808", i64::MIN),
+            ("hello", 99_162_322),
+            ("Oxicraft", -1_824_662_880),
+            ("9223372036854775808", -1_773_151_197),
+        ];
+
+        for (value, seed) in mappings {
+            assert_eq!(seed, parse_seed(value), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_load_or_create() {
+        let path = std::env::temp_dir().join(format!(
+            "oxicraft-test-{}-server.properties",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        // the defaults are written on first run
+        let config = ServerConfig::load_or_create(&path).unwrap();
+        assert_eq!(ServerConfig::default().motd, config.motd);
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .contains("max-players=20"));
+
+        // unknown keys are kept and missing ones are added
+        fs::write(&path, "spawn-protection=16\nmax-players=42\n").unwrap();
+
+        let config = ServerConfig::load_or_create(&path).unwrap();
+        assert_eq!(42, config.max_players);
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("spawn-protection=16"));
+        assert!(content.contains("max-players=42"));
+        assert!(content.contains("online-mode=true"));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,248 @@
+use std::fmt;
+use std::io;
+
+/// The entries of a Java properties file, like the `server.properties` of vanilla servers,
+/// in the order they appear in the file.
+#[derive(Debug, Default, PartialEq)]
+pub struct Properties {
+    entries: Vec<(String, String)>,
+}
+
+impl Properties {
+    pub fn parse(content: &str) -> io::Result<Properties> {
+        let mut properties = Properties::default();
+        let mut lines = content.lines();
+
+        while let Some(line) = lines.next() {
+            let mut line = line.trim_start().to_owned();
+
+            if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+                continue;
+            }
+
+            // a line ending with an unescaped backslash is continued on the next line
+            while ends_with_line_continuation(&line) {
+                line.pop();
+
+                match lines.next() {
+                    Some(next_line) => line.push_str(next_line.trim_start()),
+                    None => break,
+                }
+            }
+
+            let (key, value) = split_entry(&line);
+
+            properties.set(&unescape(key)?, &unescape(value)?);
+        }
+
+        Ok(properties)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(entry_key, _)| entry_key == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the value of the given key, keeping its position if it exists already.
+    pub fn set(&mut self, key: &str, value: &str) {
+        match self
+            .entries
+            .iter_mut()
+            .find(|(entry_key, _)| entry_key == key)
+        {
+            Some(entry) => entry.1 = value.to_owned(),
+            None => self.entries.push((key.to_owned(), value.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Properties {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "#Minecraft server properties")?;
+
+        for (key, value) in &self.entries {
+            writeln!(f, "{}={}", escape(key, true), escape(value, false))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn ends_with_line_continuation(line: &str) -> bool {
+    line.chars().rev().take_while(|&c| c == '\\').count() % 2 == 1
+}
+
+/// Splits a line at the first unescaped `=`, `:` or whitespace into the raw key and value.
+fn split_entry(line: &str) -> (&str, &str) {
+    let mut escaped = false;
+
+    for (index, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '=' || c == ':' || c.is_whitespace() {
+            let value = line[index..].trim_start();
+
+            // whitespace may surround a single separator
+            let value = if c.is_whitespace() && (value.starts_with('=') || value.starts_with(':')) {
+                &value[1..]
+            } else if c.is_whitespace() {
+                value
+            } else {
+                &value[1..]
+            };
+
+            return (&line[..index], value.trim_start());
+        }
+    }
+
+    (line, "")
+}
+
+fn unescape(raw: &str) -> io::Result<String> {
+    // collected as UTF-16, because characters outside of the BMP are escaped as surrogate pairs
+    let mut result: Vec<u16> = Vec::with_capacity(raw.len());
+    let mut chars = raw.chars();
+
+    while let Some(c) = chars.next() {
+        let c = if c == '\\' {
+            match chars.next() {
+                Some('t') => '\t',
+                Some('n') => '\n',
+                Some('r') => '\r',
+                Some('f') => '\u{000C}',
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).collect();
+
+                    match u16::from_str_radix(&hex, 16) {
+                        Ok(code_unit) if hex.len() == 4 => {
+                            result.push(code_unit);
+                            continue;
+                        }
+                        _ => {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!("Malformed \\u{} escape in properties.", hex),
+                            ));
+                        }
+                    }
+                }
+                Some(other) => other,
+                None => break,
+            }
+        } else {
+            c
+        };
+
+        let mut buffer = [0; 2];
+        result.extend_from_slice(c.encode_utf16(&mut buffer));
+    }
+
+    Ok(String::from_utf16_lossy(&result))
+}
+
+fn escape(value: &str, is_key: bool) -> String {
+    let mut result = String::with_capacity(value.len());
+
+    for (index, c) in value.chars().enumerate() {
+        match c {
+            ' ' if is_key || index == 0 => result.push_str("\\ "),
+            '\\' => result.push_str("\\\\"),
+            '\t' => result.push_str("\\t"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\u{000C}' => result.push_str("\\f"),
+            '=' | ':' | '#' | '!' => {
+                result.push('\\');
+                result.push(c);
+            }
+            ' '..='~' => result.push(c),
+            _ => {
+                let mut buffer = [0; 2];
+
+                for code_unit in c.encode_utf16(&mut buffer) {
+                    result.push_str(&format!("\\u{:04X}", code_unit));
+                }
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Properties;
+
+    #[test]
+    fn test_parse_properties() {
+        let content = "#Minecraft server properties\n\
+                       ! another comment\n\
+                       \n\
+                       server-port=25566\n\
+                       motd = \\u00A7aHello\\=World\n\
+                       level-type:FLAT\n\
+                       white list\n\
+                       server-ip=\n\
+                       generator-settings=first,\\\n    second\r\n\
+                       key\\ with\\ spaces  value\n\
+                       emoji=\\uD83D\\uDE00\n";
+
+        let properties = Properties::parse(content).unwrap();
+
+        let mappings = vec![
+            ("server-port", "25566"),
+            ("motd", "§aHello=World"),
+            ("level-type", "FLAT"),
+            ("white", "list"),
+            ("server-ip", ""),
+            ("generator-settings", "first,second"),
+            ("key with spaces", "value"),
+            ("emoji", "😀"),
+        ];
+
+        for (key, expected) in mappings {
+            assert_eq!(Some(expected), properties.get(key), "{}", key);
+        }
+
+        assert_eq!(None, properties.get("Minecraft"));
+    }
+
+    #[test]
+    fn test_parse_malformed_unicode_escape() {
+        assert!(Properties::parse("motd=\\u00G7").is_err());
+        assert!(Properties::parse("motd=\\u00").is_err());
+    }
+
+    #[test]
+    fn test_write_properties() {
+        let mut properties = Properties::default();
+        properties.set("motd", " §aA=B: C\\");
+        properties.set("server-port", "25565");
+        properties.set("odd key", "😀");
+        properties.set("motd", "§aHi");
+
+        assert_eq!(
+            "#Minecraft server properties\n\
+             motd=\\u00A7aHi\n\
+             server-port=25565\n\
+             odd\\ key=\\uD83D\\uDE00\n",
+            properties.to_string()
+        );
+    }
+
+    #[test]
+    fn test_properties_round_trip() {
+        let mut properties = Properties::default();
+        properties.set("motd", " §aA=B: C\\ #!");
+        properties.set("key with spaces", "multi\nline\ttext");
+
+        assert_eq!(
+            properties,
+            Properties::parse(&properties.to_string()).unwrap()
+        );
+    }
+}
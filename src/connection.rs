@@ -1,22 +1,47 @@
 pub mod handshake;
+pub mod legacy_ping;
+pub mod stream;
 
+use crate::chat::ChatComponent;
 use crate::client_settings::ClientSettings;
-use crate::coding::short::UnsignedShort;
-use crate::coding::string::ReadString;
+use crate::coding::buffer::ReadCursor;
+use crate::coding::byte_array::ByteArray;
+use crate::coding::uuid::Uuid;
 use crate::coding::varint::Varint;
-use crate::coding::{Decodeable, Encodeable};
-use crate::packet::{Packet, PacketData};
-use std::collections::VecDeque;
+use crate::coding::Decodeable;
+use crate::compression;
+use crate::encryption;
+use crate::packet::handshaking::{Handshake, ServerboundHandshakingPacket};
+use crate::packet::login::{
+    self as login_packet, ClientboundLoginPacket, EncryptionRequest, EncryptionResponse,
+    LoginStart, ServerboundLoginPacket, SetCompression,
+};
+use crate::packet::play::{self as play_packet, ClientboundPlayPacket};
+use crate::packet::status::{ClientboundStatusPacket, Ping, Pong, Response, ServerboundStatusPacket};
+use crate::packet::{
+    self, ClientboundPacket, Packet, ServerboundPacket, PROTOCOL_VERSION, VERSION_NAME,
+};
+use crate::server::Server;
+use crate::session::GameProfile;
+use rand::Rng;
 use std::fmt;
 use std::io::{self, prelude::*};
 use std::net::{AddrParseError, IpAddr, SocketAddr, TcpStream};
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::SystemTime;
-use std::u16;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use stream::ConnectionStream;
 
 static CONNECTION_COUNTER: AtomicUsize = AtomicUsize::new(1);
 
-#[derive(Copy, Clone)]
+/// The biggest length of a packet that is accepted, like in vanilla, so clients can not
+/// make the server allocate arbitrary amounts of memory.
+const MAX_PACKET_LENGTH: i32 = 1 << 21;
+
+/// How long to wait for the rest of a legacy server list ping after its first byte.
+const LEGACY_PING_PAYLOAD_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ConnectionId(pub usize);
 
 impl ConnectionId {
@@ -31,9 +56,11 @@ impl fmt::Display for ConnectionId {
     }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Copy, Clone)]
 pub enum ConnectionState {
     Handshaking,
+    Status,
+    Login,
     Play,
 }
 
@@ -47,220 +74,438 @@ pub struct Connection {
     pub connection_id: ConnectionId,
     pub start_time: SystemTime,
     pub ip_address: SocketAddr,
-    pub tcp_stream: TcpStream,
+    pub stream: ConnectionStream,
     pub state: ConnectionState,
     /// the protocol version specified by the client
     pub protocol_version: Option<u16>,
     /// the server address used to connect to this specified by the client
     pub server_address: Option<SocketAddr>,
     pub username: Option<String>,
+    /// the token the client has to send back encrypted, while waiting for the Encryption Response
+    pub verify_token: Option<[u8; 4]>,
     pub client_settings: Option<ClientSettings>,
+    /// packets of at least this size are compressed, once the client was told so
+    pub compression_threshold: Option<usize>,
+    /// the state shared with all other connections
+    pub server: Arc<Server>,
 }
 
 impl Connection {
-    pub fn from_tcp_stream(stream: TcpStream) -> io::Result<Connection> {
+    pub fn from_tcp_stream(stream: TcpStream, server: Arc<Server>) -> io::Result<Connection> {
         Ok(Connection {
             connection_id: ConnectionId::new(),
             start_time: SystemTime::now(),
             ip_address: stream.peer_addr()?,
-            tcp_stream: stream,
+            stream: ConnectionStream::new(stream),
             state: Default::default(),
             protocol_version: Default::default(),
             server_address: Default::default(),
             username: Default::default(),
+            verify_token: None,
             client_settings: Default::default(),
+            compression_threshold: None,
+            server,
+        })
+    }
+
+    /// Reads the next packet sent by the client and decodes it according to the current state.
+    pub fn read_packet(&mut self) -> io::Result<ServerboundPacket> {
+        let mut packet = self.read_data_packet()?;
+
+        Ok(match self.state {
+            ConnectionState::Handshaking => ServerboundPacket::Handshaking(packet.decode()?),
+            ConnectionState::Status => ServerboundPacket::Status(packet.decode()?),
+            ConnectionState::Login => ServerboundPacket::Login(packet.decode()?),
+            ConnectionState::Play => ServerboundPacket::Play(packet.decode()?),
         })
     }
 
-    pub fn do_handshake(&mut self) -> io::Result<handshake::HandshakeNextState> {
-        assert_eq!(self.state, ConnectionState::Handshaking);
+    /// Checks whether the client sent a server list ping of a client older than 1.7
+    /// instead of a handshake.
+    pub fn is_legacy_ping(&self) -> io::Result<bool> {
+        let mut buffer = [0; 1];
+
+        if self.stream.peek(&mut buffer)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "The connection was closed before the handshake.",
+            ));
+        }
+
+        Ok(self.state == ConnectionState::Handshaking
+            && buffer[0] == legacy_ping::LEGACY_PING_PACKET_ID)
+    }
+
+    /// Answers the server list ping of a client older than 1.7 and closes the connection.
+    pub fn handle_legacy_ping(&mut self) -> io::Result<()> {
+        info!(
+            "Sending legacy status to connection {}.",
+            self.connection_id
+        );
+
+        let mut packet_id = [0; 1];
+        self.stream.read_exact(&mut packet_id)?;
+
+        // clients since 1.4 send a 0x01 afterwards, older ones nothing
+        let mut payload = [0; 1];
+        let since_1_4 = self.stream.wait_for_data(LEGACY_PING_PAYLOAD_TIMEOUT)?
+            && self.stream.read(&mut payload)? == 1
+            && payload[0] == 0x01;
+
+        let slp = handshake::server_list_ping(&self.server);
+
+        self.stream
+            .write_all(&legacy_ping::legacy_ping_response(&slp, since_1_4))?;
+        self.stream.flush()?;
+
+        self.stream.shutdown()
+    }
 
+    pub fn handle_handshaking_packet(
+        &mut self,
+        packet: ServerboundHandshakingPacket,
+    ) -> io::Result<()> {
         info!(
             "Processing handshake for connection {}.",
             self.connection_id
         );
 
         let benchmark_start = SystemTime::now();
-        let data_packet = self.read_data_packet()?;
 
-        // ensure it is the Handshake packet that was sent by the client
-        if data_packet.packet_id != 0x00 {
+        let ServerboundHandshakingPacket::Handshake(Handshake {
+            protocol_version,
+            server_address,
+            server_port,
+            next_state,
+        }) = packet;
+
+        let next_state = match next_state {
+            Varint(1) => handshake::HandshakeNextState::Status,
+            Varint(2) => handshake::HandshakeNextState::Login,
+            x => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("The next state may only be 1 or 2, {} given.", x.0),
+                ));
+            }
+        };
+
+        info!("Next state of {}: {:?}", self.connection_id, next_state);
+
+        // switch the state first, so errors below can be reported to clients that want to log in
+        self.state = match next_state {
+            handshake::HandshakeNextState::Status => ConnectionState::Status,
+            handshake::HandshakeNextState::Login => ConnectionState::Login,
+        };
+
+        if protocol_version.0 > i32::from(u16::MAX) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Too great protocol version supplied.",
+            ));
+        } else if protocol_version.0 < i32::from(u16::MIN) {
             return Err(io::Error::new(
-                io::ErrorKind::AlreadyExists,
-                "Client indicates it is not looking for a handshake, rather than an existing connection."
+                io::ErrorKind::InvalidInput,
+                "Too tiny protocol version supplied.",
             ));
         }
 
-        if let PacketData::Data(mut packet_data) = data_packet.data {
-            // read protocol version
-            let protocol_version: Varint = packet_data.decode()?;
+        trace!("Protocol version: {:?}", protocol_version);
 
-            if protocol_version.0 > i32::from(u16::max_value()) {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "Too great protocol version supplied.",
-                ));
-            } else if protocol_version.0 < i32::from(u16::min_value()) {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "Too tiny protocol version supplied.",
-                ));
-            }
+        let protocol_version = protocol_version.0 as u16;
+        self.protocol_version = Some(protocol_version);
+
+        // clients of other versions may still ask for the status, but not log in
+        if self.state == ConnectionState::Login && protocol_version != PROTOCOL_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                if protocol_version < PROTOCOL_VERSION {
+                    format!("Outdated client! Please use {}", VERSION_NAME)
+                } else {
+                    format!("Outdated server! I'm still on {}", VERSION_NAME)
+                },
+            ));
+        }
 
-            trace!("Protocol version: {:?}", protocol_version);
+        let mut server_address = server_address;
 
-            self.protocol_version = Some(protocol_version.0 as u16);
+        if server_address == "localhost" {
+            // bugfix to avoid unnecessary error
+            server_address = "127.0.0.1".to_owned();
+        }
 
-            let mut server_address = packet_data.read_string(255)?;
+        let ip_addr: Result<IpAddr, AddrParseError> = server_address.parse();
 
-            if server_address == "localhost" {
-                // bugfix to avoid unnecessary error
-                server_address = "127.0.0.1".to_owned();
-            }
+        if ip_addr.is_err() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid ip address given.",
+            ));
+        }
 
-            let ip_addr: Result<IpAddr, AddrParseError> = server_address.parse();
+        let ip_addr = ip_addr.unwrap();
+        let socket_addr = SocketAddr::new(ip_addr, server_port);
 
-            if ip_addr.is_err() {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "Invalid ip address given.",
-                ));
+        self.server_address = Some(socket_addr);
+
+        trace!("Client used {} to connect.", socket_addr);
+
+        let benchmark_duration = SystemTime::now().duration_since(benchmark_start).unwrap();
+
+        trace!(
+            "Handling of handshake package took {:?}.",
+            benchmark_duration
+        );
+
+        Ok(())
+    }
+
+    pub fn handle_status_packet(&mut self, packet: ServerboundStatusPacket) -> io::Result<()> {
+        match packet {
+            ServerboundStatusPacket::Request => {
+                let benchmark_start = SystemTime::now();
+
+                info!("Sending status to connection {}.", self.connection_id);
+
+                let response = serde_json::to_string(&handshake::server_list_ping(&self.server))?;
+
+                self.send_packet(ClientboundStatusPacket::Response(Response {
+                    json_response: response,
+                }))?;
+
+                let benchmark_duration =
+                    SystemTime::now().duration_since(benchmark_start).unwrap();
+                info!(
+                    "Sent status to {} (took {:?}).",
+                    self.connection_id, benchmark_duration
+                );
+            }
+            ServerboundStatusPacket::Ping(Ping { payload }) => {
+                // the client pings us with a long we need to pong back.
+                self.send_packet(ClientboundStatusPacket::Pong(Pong { payload }))?;
+                // flush package immediately(we pong asap)
+                self.stream.flush()?;
             }
+        }
 
-            let ip_addr = ip_addr.unwrap();
-            let port: UnsignedShort = packet_data.decode()?;
-            let socket_addr = SocketAddr::new(ip_addr, port);
+        Ok(())
+    }
 
-            self.server_address = Some(socket_addr);
+    /// Handles the packets sent by the client while logging in.
+    /// Returns a tuple (username, uuid) as soon as the client may join the game.
+    pub fn handle_login_packet(
+        &mut self,
+        packet: ServerboundLoginPacket,
+    ) -> io::Result<Option<(String, Uuid)>> {
+        match packet {
+            ServerboundLoginPacket::LoginStart(LoginStart { username }) => {
+                info!("New login from {} ({})!", username, self.connection_id);
+
+                if self.username.is_some() {
+                    return Err(packet::unexpected_packet(ConnectionState::Login, 0x00));
+                }
 
-            trace!("Client used {} to connect.", &socket_addr);
+                self.username = Some(username.clone());
 
-            let next_state = match packet_data.decode()? {
-                Varint(1) => handshake::HandshakeNextState::Status,
-                Varint(2) => handshake::HandshakeNextState::Login,
-                x => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidInput,
-                        format!("The next state may only be 1 or 2, {} given.", x.0),
-                    ));
+                if self.server.config.online_mode {
+                    self.request_encryption()?;
+
+                    return Ok(None);
                 }
-            };
 
-            info!("Next state of {}: {:?}", self.connection_id, next_state);
+                let uuid = Uuid::offline_player(&username);
 
-            if !packet_data.is_empty() {
-                warn!("The handshake packet sent by the client contains more data than expected. Rest of data: {:?}", packet_data);
+                self.finish_login(username, uuid).map(Some)
             }
+            ServerboundLoginPacket::EncryptionResponse(response) => {
+                let (username, verify_token) =
+                    match (self.username.clone(), self.verify_token.take()) {
+                        (Some(username), Some(verify_token)) => (username, verify_token),
+                        _ => return Err(packet::unexpected_packet(ConnectionState::Login, 0x01)),
+                    };
 
-            let benchmark_duration = SystemTime::now().duration_since(benchmark_start).unwrap();
+                let profile = self.authenticate(&username, &verify_token, response)?;
 
-            trace!(
-                "Handling of handshake package took {:?}.",
-                benchmark_duration
-            );
+                info!(
+                    "Authenticated {} with uuid {} ({}).",
+                    profile.name, profile.id, self.connection_id
+                );
 
-            Ok(next_state)
-        } else {
-            Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Handshake packet does not contain any data?!",
-            ))
+                let uuid = profile.uuid()?;
+
+                self.finish_login(profile.name, uuid).map(Some)
+            }
         }
     }
 
-    pub fn send_status(&mut self) -> io::Result<()> {
-        assert_eq!(self.state, ConnectionState::Handshaking);
-        let benchmark_start = SystemTime::now();
+    /// S->C Encryption Request
+    fn request_encryption(&mut self) -> io::Result<()> {
+        let verify_token: [u8; 4] = rand::thread_rng().gen();
+        let public_key = self.server.key_pair()?.public_key_der().to_vec();
 
-        info!("Sending status to connection {}.", self.connection_id);
+        self.verify_token = Some(verify_token);
 
-        // the package id for this(empty) package is 0x00.
-        assert_eq!(self.read_data_packet()?.packet_id, 0x00);
+        self.send_packet(ClientboundLoginPacket::EncryptionRequest(EncryptionRequest {
+            server_id: String::new(),
+            public_key: ByteArray(public_key),
+            verify_token: ByteArray(verify_token.to_vec()),
+        }))
+    }
 
-        let response = serde_json::to_string(&handshake::mock_slp())?.to_owned();
+    /// Checks the Encryption Response of the client, enables encryption and asks the session
+    /// verifier whether the client may log in with the given username.
+    fn authenticate(
+        &mut self,
+        username: &str,
+        verify_token: &[u8],
+        response: EncryptionResponse,
+    ) -> io::Result<GameProfile> {
+        let server = Arc::clone(&self.server);
+        let key_pair = server.key_pair()?;
+
+        if key_pair.decrypt(&response.verify_token.0)? != verify_token {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "The verify token sent by the client does not match.",
+            ));
+        }
 
-        let mut response_packet: Packet =
-            Packet::from_id_and_data(Varint(0x00), PacketData::Data(response.encode()));
+        let shared_secret = key_pair.decrypt(&response.shared_secret.0)?;
 
-        response_packet.send(&mut self.tcp_stream)?;
+        // everything after the Encryption Response is encrypted, in both directions
+        self.stream.enable_encryption(&shared_secret)?;
 
-        let benchmark_duration = SystemTime::now().duration_since(benchmark_start).unwrap();
-        info!(
-            "Sent status to {} (took {:?}).",
-            self.connection_id, benchmark_duration
-        );
+        let server_hash = encryption::server_hash("", &shared_secret, key_pair.public_key_der());
 
-        // now, the client sends a data packet (basically to ping us), with a long we need to pong back.
-        if let PacketData::Data(packet_data) = self.read_data_packet()?.data {
-            let mut pong_packet =
-                Packet::from_id_and_data(Varint(0x01), PacketData::Data(packet_data));
+        trace!("Server hash for {}: {}", username, server_hash);
 
-            pong_packet.send(&mut self.tcp_stream)?;
-            // flush package immediately(we pong asap)
-            self.tcp_stream.flush()?;
-        } else {
-            unreachable!();
+        match server.session_verifier.has_joined(username, &server_hash)? {
+            Some(profile) => Ok(profile),
+            None => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Failed to verify username {}!", username),
+            )),
         }
+    }
 
-        Ok(())
+    /// Enables compression and hands the (username, uuid) pair over to the game.
+    fn finish_login(&mut self, username: String, uuid: Uuid) -> io::Result<(String, Uuid)> {
+        self.enable_compression()?;
+
+        Ok((username, uuid))
     }
 
-    /// Performs the necessary step to let the user spawn on a flat-map with grass all over the place.
-    /// Returns a tuple (username, uuid).
-    pub fn prepare_login(&mut self) -> io::Result<(String, String)> {
-        // C->S Login Start
-        let login_packet = self.read_data_packet()?;
+    /// Tells the client why it is disconnected, if the current state allows so,
+    /// and closes the connection.
+    pub fn disconnect(&mut self, reason: &str) -> io::Result<()> {
+        info!("Disconnecting {}: {}", self.connection_id, reason);
 
-        assert_eq!(login_packet.packet_id, 0x00);
-        if let PacketData::Data(mut packet_data) = login_packet.data {
-            let username = packet_data.read_string(16)?;
+        let reason = ChatComponent::text(reason).to_json()?;
 
-            info!("New login from {} ({})!", &username, self.connection_id);
+        match self.state {
+            ConnectionState::Login => {
+                self.send_packet(ClientboundLoginPacket::Disconnect(
+                    login_packet::Disconnect { reason },
+                ))?;
+            }
+            ConnectionState::Play => {
+                self.send_packet(ClientboundPlayPacket::Disconnect(play_packet::Disconnect {
+                    reason,
+                }))?;
+            }
+            // there is no packet to tell the client why
+            ConnectionState::Handshaking | ConnectionState::Status => {}
+        }
 
-            // UUID-4 (with hyphens) of jens1o
-            let player_uuid = "8e383e9f-608e-4556-97c9-61312c741ea0".to_owned();
+        self.stream.shutdown()
+    }
 
-            Ok((username, player_uuid))
-        } else {
-            Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Login Start packet not sent by client!",
-            ))
+    /// Disconnects the client because of the given error, unless the connection is
+    /// already lost anyway. Returns the error for further handling.
+    pub fn abort(&mut self, error: io::Error) -> io::Error {
+        let connection_lost = matches!(
+            error.kind(),
+            io::ErrorKind::UnexpectedEof
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::BrokenPipe
+        );
+
+        if !connection_lost {
+            if let Err(disconnect_error) = self.disconnect(&error.to_string()) {
+                debug!(
+                    "Could not disconnect {} properly: {}",
+                    self.connection_id, disconnect_error
+                );
+            }
         }
+
+        error
+    }
+
+    /// Sends the given packet to the client.
+    pub fn send_packet<P: ClientboundPacket>(&mut self, packet: P) -> io::Result<()> {
+        packet::write_packet(
+            &mut self.stream,
+            packet.packet_id(),
+            &packet,
+            self.compression_threshold,
+        )
     }
 
     pub fn read_data_packet(&mut self) -> io::Result<Packet> {
-        let length: Varint = self.tcp_stream.decode()?;
+        let length: Varint = self.stream.decode()?;
         ensure_data_size(length.0)?;
 
         // we now can ensure it is a positive number, thus cast it
         let length: usize = length.0 as usize;
 
-        let packet_id: Varint = self.tcp_stream.decode()?;
+        trace!("Reading {} bytes to read content of package.", length);
 
-        // read the package contents. We need to read the amount that was given, without the package id.
-        // TODO: Simplify this expression
-        let data_length = length - packet_id.encode().len();
+        let mut buffer = vec![0; length];
+        self.stream.read_exact(&mut buffer)?;
 
-        trace!(
-            "Reading {} bytes to read content of package with id {:#X}.",
-            data_length,
-            packet_id.0
-        );
+        if let Some(threshold) = self.compression_threshold {
+            buffer = compression::decompress(&buffer, threshold)?;
+        }
+
+        let mut cursor = ReadCursor::new(&buffer);
+        let packet_id: Varint = cursor.decode()?;
+        let packet_id_length = buffer.len() - cursor.remaining();
 
-        let mut buffer = vec![0; data_length];
-        self.tcp_stream.read_exact(&mut buffer)?;
+        // the packet id is followed by the data, which is moved to the front
+        buffer.drain(..packet_id_length);
 
         let packet = Packet {
             length,
             packet_id,
-            data: PacketData::Data(VecDeque::from(buffer)),
+            data: buffer,
         };
 
         trace!("Received data packet: {:?}", packet);
 
         Ok(packet)
     }
+
+    /// S->C Set Compression
+    ///
+    /// Enables compression for all following packets, if configured.
+    fn enable_compression(&mut self) -> io::Result<()> {
+        if let Some(threshold) = self.server.config.network_compression_threshold {
+            self.send_packet(ClientboundLoginPacket::SetCompression(SetCompression {
+                threshold: Varint(threshold as i32),
+            }))?;
+
+            self.compression_threshold = Some(threshold);
+
+            debug!(
+                "Enabled compression for packets of {} bytes or more on connection {}.",
+                threshold, self.connection_id
+            );
+        }
+
+        Ok(())
+    }
 }
 
 #[inline]
@@ -271,7 +516,237 @@ pub fn ensure_data_size(size: i32) -> io::Result<()> {
             io::ErrorKind::InvalidData,
             "Received packet with data size of zero (or less).",
         ))
+    } else if size > MAX_PACKET_LENGTH {
+        warn!(r#"Received "data" with size of {}."#, size);
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Received packet with data size of {}, which is more than {}.",
+                size, MAX_PACKET_LENGTH
+            ),
+        ))
     } else {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::stream::ConnectionStream;
+    use super::{ensure_data_size, Connection, ConnectionState};
+    use crate::coding::buffer::ReadCursor;
+    use crate::coding::byte_array::ByteArray;
+    use crate::coding::uuid::Uuid;
+    use crate::coding::varint::Varint;
+    use crate::coding::{Decodeable, Encodeable};
+    use crate::config::ServerConfig;
+    use crate::packet::handshaking::{Handshake, ServerboundHandshakingPacket};
+    use crate::packet::{self, ServerboundPacket};
+    use crate::server::Server;
+    use crate::session::{GameProfile, MockSessionVerifier};
+    use rsa::pkcs8::DecodePublicKey;
+    use rsa::{Pkcs1v15Encrypt, RsaPublicKey};
+    use std::io::{self, prelude::*};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::Arc;
+
+    /// Returns the stream of a client connected to the given server, and its connection.
+    fn connect(server: Server) -> (ConnectionStream, Connection) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let connection =
+            Connection::from_tcp_stream(listener.accept().unwrap().0, Arc::new(server)).unwrap();
+
+        (ConnectionStream::new(client), connection)
+    }
+
+    fn offline_server() -> Server {
+        Server::new(ServerConfig {
+            network_compression_threshold: None,
+            online_mode: false,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    /// Logs in as `username` against a server which only knows jens1o, the way a vanilla client does.
+    fn online_login(username: &str) -> io::Result<Option<(String, Uuid)>> {
+        let mut server = Server::new(ServerConfig {
+            network_compression_threshold: Some(256),
+            online_mode: true,
+            ..Default::default()
+        })?;
+        server.session_verifier = Box::new(MockSessionVerifier {
+            profiles: vec![GameProfile {
+                id: "8e383e9f608e455697c961312c741ea0".to_owned(),
+                name: "jens1o".to_owned(),
+                properties: vec![],
+            }],
+        });
+
+        let (mut client, mut connection) = connect(server);
+        connection.state = ConnectionState::Login;
+
+        let handle_next_packet = |connection: &mut Connection| match connection.read_packet()? {
+            ServerboundPacket::Login(packet) => connection.handle_login_packet(packet),
+            packet => panic!("Unexpected packet {:?}", packet),
+        };
+
+        // C->S Login Start
+        packet::write_packet(&mut client, Varint(0x00), username, None)?;
+        assert_eq!(None, handle_next_packet(&mut connection)?);
+
+        // S->C Encryption Request
+        let request = read_raw_packet(&mut client);
+        let mut request = ReadCursor::new(&request);
+        let packet_id: Varint = request.decode().unwrap();
+        assert_eq!(Varint(0x01), packet_id);
+        // the server id is an empty string
+        let server_id_length: Varint = request.decode().unwrap();
+        assert_eq!(Varint(0), server_id_length);
+        let public_key: ByteArray = request.decode().unwrap();
+        let verify_token: ByteArray = request.decode().unwrap();
+
+        let public_key = RsaPublicKey::from_public_key_der(&public_key.0).unwrap();
+        let encrypt = |data: &[u8]| {
+            ByteArray(
+                public_key
+                    .encrypt(&mut rand::thread_rng(), Pkcs1v15Encrypt, data)
+                    .unwrap(),
+            )
+        };
+
+        // C->S Encryption Response
+        let shared_secret = [3; 16];
+        let mut response = encrypt(&shared_secret).encode();
+        response.extend(encrypt(&verify_token.0).encode());
+
+        packet::write_packet(&mut client, Varint(0x01), &response, None)?;
+        client.enable_encryption(&shared_secret)?;
+
+        let result = handle_next_packet(&mut connection)?;
+
+        // S->C Set Compression, which must already be encrypted
+        assert_eq!(vec![0x03, 0x80, 0x02], read_raw_packet(&mut client));
+
+        Ok(result)
+    }
+
+    fn read_raw_packet(client: &mut ConnectionStream) -> Vec<u8> {
+        let length: Varint = client.decode().unwrap();
+        let mut buffer = vec![0; length.0 as usize];
+        client.read_exact(&mut buffer).unwrap();
+
+        buffer
+    }
+
+    #[test]
+    fn test_online_mode_login() {
+        assert_eq!(
+            Some((
+                "jens1o".to_owned(),
+                Uuid(0x8e38_3e9f_608e_4556_97c9_6131_2c74_1ea0)
+            )),
+            online_login("jens1o").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_online_mode_login_of_unknown_user() {
+        let error = online_login("Notch").unwrap_err();
+
+        assert_eq!(io::ErrorKind::PermissionDenied, error.kind());
+    }
+
+    #[test]
+    fn test_disconnect_while_logging_in() {
+        let (mut client, mut connection) = connect(offline_server());
+        connection.state = ConnectionState::Login;
+
+        connection.disconnect("Bye").unwrap();
+
+        let mut expected = vec![0x00, 14];
+        expected.extend(br#"{"text":"Bye"}"#);
+        assert_eq!(expected, read_raw_packet(&mut client));
+
+        // the connection is closed afterwards
+        assert_eq!(0, client.read(&mut [0; 1]).unwrap());
+    }
+
+    #[test]
+    fn test_outdated_client_is_disconnected() {
+        let (mut client, mut connection) = connect(offline_server());
+
+        let error = connection
+            .handle_handshaking_packet(ServerboundHandshakingPacket::Handshake(Handshake {
+                protocol_version: Varint(340),
+                server_address: "localhost".to_owned(),
+                server_port: 25565,
+                next_state: Varint(2),
+            }))
+            .unwrap_err();
+
+        assert_eq!(ConnectionState::Login, connection.state);
+
+        let error = connection.abort(error);
+        assert_eq!(io::ErrorKind::InvalidInput, error.kind());
+
+        let mut expected = vec![0x00, 45];
+        expected.extend(br#"{"text":"Outdated client! Please use 1.13.2"}"#);
+        assert_eq!(expected, read_raw_packet(&mut client));
+    }
+
+    #[test]
+    fn test_legacy_ping() {
+        let (mut client, mut connection) = connect(offline_server());
+
+        client.write_all(&[0xFE, 0x01]).unwrap();
+
+        assert!(connection.is_legacy_ping().unwrap());
+        connection.handle_legacy_ping().unwrap();
+
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).unwrap();
+
+        // "§1" after the packet id and the length
+        assert_eq!(vec![0xFF], response[..1].to_vec());
+        assert_eq!(vec![0x00, 0xA7, 0x00, 0x31], response[3..7].to_vec());
+    }
+
+    #[test]
+    fn test_handshake_is_no_legacy_ping() {
+        let (mut client, connection) = connect(offline_server());
+
+        client.write_all(&[0x10, 0x00]).unwrap();
+
+        assert!(!connection.is_legacy_ping().unwrap());
+    }
+
+    #[test]
+    fn test_packet_lengths_are_limited() {
+        let mappings = vec![
+            (0, false),
+            (-1, false),
+            (1, true),
+            (2_097_152, true),
+            (2_097_153, false),
+            (i32::MAX, false),
+        ];
+
+        for (length, accepted) in mappings {
+            assert_eq!(accepted, ensure_data_size(length).is_ok(), "{}", length);
+        }
+    }
+
+    #[test]
+    fn test_oversized_packet_is_rejected() {
+        let (mut client, mut connection) = connect(offline_server());
+
+        // a length of i32::MAX, without any data following
+        client.write_all(&Varint(i32::MAX).encode()).unwrap();
+
+        let error = connection.read_data_packet().unwrap_err();
+
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+    }
+}
@@ -1,6 +1,12 @@
+use crate::chat::ChatComponent;
 use crate::coding::short::UnsignedShort;
 use crate::coding::string::MinecraftString;
-use crate::player::Player;
+use crate::packet::{PROTOCOL_VERSION, VERSION_NAME};
+use crate::server::{OnlinePlayer, Server};
+use rand::seq::SliceRandom;
+
+/// The maximum number of players that are listed in the server list, like vanilla does.
+const MAX_SAMPLE_SIZE: usize = 12;
 
 #[derive(Debug)]
 pub enum HandshakeNextState {
@@ -10,58 +16,68 @@ pub enum HandshakeNextState {
 
 #[derive(Serialize)]
 pub struct ServerListPingVersion {
-    name: MinecraftString,
-    protocol: UnsignedShort,
+    pub name: MinecraftString,
+    pub protocol: UnsignedShort,
 }
 
 #[derive(Serialize)]
 pub struct ServerListPingPlayers {
-    max: UnsignedShort,
-    online: UnsignedShort,
-    sample: Vec<Player>,
-}
-
-#[derive(Serialize)]
-pub struct ServerListPingDescription {
-    text: MinecraftString,
+    pub max: UnsignedShort,
+    pub online: UnsignedShort,
+    pub sample: Vec<OnlinePlayer>,
 }
 
 #[derive(Serialize)]
 pub struct ServerListPingResponse {
-    version: ServerListPingVersion,
-    players: ServerListPingPlayers,
-    description: ServerListPingDescription,
+    pub version: ServerListPingVersion,
+    pub players: ServerListPingPlayers,
+    pub description: ChatComponent,
+    /// a 64x64 PNG image as data URI
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub favicon: Option<String>,
 }
 
-pub fn mock_slp() -> ServerListPingResponse {
+/// Builds the response to a server list ping from the configuration and the players
+/// that are currently online.
+pub fn server_list_ping(server: &Server) -> ServerListPingResponse {
+    let online_players = server.online_players();
+
+    let sample = online_players
+        .choose_multiple(&mut rand::thread_rng(), MAX_SAMPLE_SIZE)
+        .cloned()
+        .collect();
+
     ServerListPingResponse {
         version: ServerListPingVersion {
-            name: "1.13.1".to_owned(),
-            protocol: 404,
+            name: VERSION_NAME.to_owned(),
+            protocol: PROTOCOL_VERSION,
         },
         players: ServerListPingPlayers {
-            max: 100,
-            online: 5,
-            sample: vec![],
-        },
-        description: ServerListPingDescription {
-            text: "Java edition doesn't necessarily mean everything is run by Java.".to_owned(),
+            max: server.config.max_players,
+            online: online_players.len().min(usize::from(UnsignedShort::MAX)) as u16,
+            sample,
         },
+        description: ChatComponent::text(server.config.motd.clone()),
+        favicon: server.favicon.clone(),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        ServerListPingDescription, ServerListPingPlayers, ServerListPingResponse,
-        ServerListPingVersion,
+        server_list_ping, ServerListPingPlayers, ServerListPingResponse, ServerListPingVersion,
     };
+    use crate::chat::ChatComponent;
+    use crate::coding::uuid::Uuid;
+    use crate::config::ServerConfig;
+    use crate::connection::ConnectionId;
+    use crate::server::{OnlinePlayer, Server};
 
     #[test]
     fn test_build_slp_response() {
         let slp = ServerListPingResponse {
             version: ServerListPingVersion {
-                name: "1.13.1".to_owned(),
+                name: "1.13.2".to_owned(),
                 protocol: 404,
             },
             players: ServerListPingPlayers {
@@ -69,15 +85,64 @@ mod tests {
                 online: 5,
                 sample: vec![],
             },
-            description: ServerListPingDescription {
-                text: "Random test message".to_owned(),
-            },
+            description: ChatComponent::text("Random test message"),
+            favicon: None,
         };
 
         let json = serde_json::to_string(&slp).unwrap();
 
-        let expected = r##"{"version":{"name":"1.13.1","protocol":404},"players":{"max":100,"online":5,"sample":[]},"description":{"text":"Random test message"}}"##;
+        let expected = r##"{"version":{"name":"1.13.2","protocol":404},"players":{"max":100,"online":5,"sample":[]},"description":{"text":"Random test message"}}"##;
 
         assert_eq!(expected.to_owned(), json);
     }
+
+    #[test]
+    fn test_server_list_ping_shows_live_state() {
+        let mut server = Server::new(ServerConfig {
+            motd: "A test server".to_owned(),
+            max_players: 42,
+            online_mode: false,
+            ..Default::default()
+        })
+        .unwrap();
+        server.favicon = Some("data:image/png;base64,AAAA".to_owned());
+
+        server.add_player(
+            ConnectionId(1),
+            OnlinePlayer {
+                username: "jens1o".to_owned(),
+                uuid: Uuid(0x8e38_3e9f_608e_4556_97c9_6131_2c74_1ea0),
+            },
+        );
+
+        let json = serde_json::to_string(&server_list_ping(&server)).unwrap();
+
+        let expected = r##"{"version":{"name":"1.13.2","protocol":404},"players":{"max":42,"online":1,"sample":[{"name":"jens1o","id":"8e383e9f-608e-4556-97c9-61312c741ea0"}]},"description":{"text":"A test server"},"favicon":"data:image/png;base64,AAAA"}"##;
+
+        assert_eq!(expected.to_owned(), json);
+    }
+
+    #[test]
+    fn test_server_list_ping_sample_is_limited() {
+        let server = Server::new(ServerConfig {
+            online_mode: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+        for i in 0..20 {
+            server.add_player(
+                ConnectionId(i),
+                OnlinePlayer {
+                    username: format!("player{}", i),
+                    uuid: Uuid::offline_player(&format!("player{}", i)),
+                },
+            );
+        }
+
+        let slp = server_list_ping(&server);
+
+        assert_eq!(20, slp.players.online);
+        assert_eq!(12, slp.players.sample.len());
+    }
 }
@@ -0,0 +1,99 @@
+use super::handshake::ServerListPingResponse;
+
+/// The first byte of a server list ping sent by clients older than 1.7.
+pub const LEGACY_PING_PACKET_ID: u8 = 0xFE;
+
+/// The id of the kick packet legacy clients expect as answer.
+const LEGACY_KICK_PACKET_ID: u8 = 0xFF;
+
+/// The protocol version vanilla reports to legacy clients, so they show it as incompatible.
+const LEGACY_PROTOCOL_VERSION: u16 = 127;
+
+/// Builds the kick packet legacy clients expect as answer to their server list ping.
+///
+/// Clients since 1.4 send an additional `0x01` after the packet id and understand
+/// the protocol and version, older ones only get the MOTD and the player counts.
+pub fn legacy_ping_response(slp: &ServerListPingResponse, since_1_4: bool) -> Vec<u8> {
+    let message = if since_1_4 {
+        format!(
+            "§1\0{}\0{}\0{}\0{}\0{}",
+            LEGACY_PROTOCOL_VERSION,
+            slp.version.name,
+            slp.description.to_legacy(),
+            slp.players.online,
+            slp.players.max
+        )
+    } else {
+        // the section sign separates the values here, so it may not appear in the MOTD
+        format!(
+            "{}§{}§{}",
+            slp.description.to_legacy().replace('§', ""),
+            slp.players.online,
+            slp.players.max
+        )
+    };
+
+    let message: Vec<u16> = message.encode_utf16().collect();
+
+    let mut response = Vec::with_capacity(3 + message.len() * 2);
+    response.push(LEGACY_KICK_PACKET_ID);
+    // the length is given in characters, not in bytes
+    response.extend(&(message.len() as u16).to_be_bytes());
+
+    for character in message {
+        response.extend(&character.to_be_bytes());
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::legacy_ping_response;
+    use crate::chat::ChatComponent;
+    use crate::connection::handshake::{
+        ServerListPingPlayers, ServerListPingResponse, ServerListPingVersion,
+    };
+
+    fn slp() -> ServerListPingResponse {
+        ServerListPingResponse {
+            version: ServerListPingVersion {
+                name: "1.13.2".to_owned(),
+                protocol: 404,
+            },
+            players: ServerListPingPlayers {
+                max: 20,
+                online: 3,
+                sample: vec![],
+            },
+            description: ChatComponent::text("§aOxi"),
+            favicon: None,
+        }
+    }
+
+    /// Encodes the given string the way legacy clients expect it.
+    fn utf_16_be(string: &str) -> Vec<u8> {
+        string
+            .encode_utf16()
+            .flat_map(|character| character.to_be_bytes().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn test_legacy_ping_response() {
+        let message = "§1\u{0}127\u{0}1.13.2\u{0}§aOxi\u{0}3\u{0}20";
+
+        let mut expected = vec![0xFF, 0, 24];
+        expected.extend(utf_16_be(message));
+
+        assert_eq!(expected, legacy_ping_response(&slp(), true));
+    }
+
+    #[test]
+    fn test_legacy_ping_response_before_1_4() {
+        let mut expected = vec![0xFF, 0, 9];
+        expected.extend(utf_16_be("aOxi§3§20"));
+
+        assert_eq!(expected, legacy_ping_response(&slp(), false));
+    }
+}
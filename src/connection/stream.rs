@@ -0,0 +1,153 @@
+use crate::encryption::StreamCipher;
+use std::io::{self, prelude::*};
+use std::net::{Shutdown, TcpStream};
+use std::time::Duration;
+
+/// The stream of a connection, which transparently en- and decrypts all data
+/// once encryption was enabled.
+pub struct ConnectionStream {
+    tcp_stream: TcpStream,
+    cipher: Option<StreamCipher>,
+}
+
+impl ConnectionStream {
+    pub fn new(tcp_stream: TcpStream) -> ConnectionStream {
+        ConnectionStream {
+            tcp_stream,
+            cipher: None,
+        }
+    }
+
+    /// Encrypts everything that is read or written after this call with the shared secret.
+    pub fn enable_encryption(&mut self, shared_secret: &[u8]) -> io::Result<()> {
+        self.cipher = Some(StreamCipher::new(shared_secret)?);
+
+        Ok(())
+    }
+
+    /// Reads data from the client without consuming it, like `read` does.
+    pub fn peek(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.tcp_stream.peek(buffer)
+    }
+
+    /// Closes the connection in both directions.
+    pub fn shutdown(&self) -> io::Result<()> {
+        self.tcp_stream.shutdown(Shutdown::Both)
+    }
+
+    /// Fails reads that take longer than the given duration, `None` blocks forever.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.tcp_stream.set_read_timeout(timeout)
+    }
+
+    /// Waits up to `timeout` for data from the client, without consuming it.
+    /// Returns whether there is data that can be read.
+    pub fn wait_for_data(&self, timeout: Duration) -> io::Result<bool> {
+        let read_timeout = self.tcp_stream.read_timeout()?;
+        self.tcp_stream.set_read_timeout(Some(timeout))?;
+
+        let result = self.tcp_stream.peek(&mut [0; 1]);
+
+        self.tcp_stream.set_read_timeout(read_timeout)?;
+
+        match result {
+            Ok(0) => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "The connection was closed.",
+            )),
+            Ok(_) => Ok(true),
+            Err(ref error)
+                if error.kind() == io::ErrorKind::WouldBlock
+                    || error.kind() == io::ErrorKind::TimedOut =>
+            {
+                Ok(false)
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
+impl Read for ConnectionStream {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let length = self.tcp_stream.read(buffer)?;
+
+        if let Some(cipher) = &mut self.cipher {
+            cipher.decrypt(&mut buffer[..length]);
+        }
+
+        Ok(length)
+    }
+}
+
+impl Write for ConnectionStream {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        match &mut self.cipher {
+            Some(cipher) => {
+                // the cipher advances with every byte, so we may not write only a part of it
+                let mut encrypted = buffer.to_vec();
+                cipher.encrypt(&mut encrypted);
+                self.tcp_stream.write_all(&encrypted)?;
+
+                Ok(buffer.len())
+            }
+            None => self.tcp_stream.write(buffer),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.tcp_stream.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConnectionStream;
+    use std::io;
+    use std::io::prelude::*;
+    use std::net::{TcpListener, TcpStream};
+    use std::time::Duration;
+
+    #[test]
+    fn test_encrypted_round_trip() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client =
+            ConnectionStream::new(TcpStream::connect(listener.local_addr().unwrap()).unwrap());
+        let mut server = ConnectionStream::new(listener.accept().unwrap().0);
+
+        client.write_all(b"plain").unwrap();
+
+        let mut buffer = [0; 5];
+        server.read_exact(&mut buffer).unwrap();
+        assert_eq!(b"plain", &buffer);
+
+        client.enable_encryption(&[7; 16]).unwrap();
+        server.enable_encryption(&[7; 16]).unwrap();
+
+        client.write_all(b"secret").unwrap();
+
+        let mut buffer = [0; 6];
+        server.read_exact(&mut buffer).unwrap();
+        assert_eq!(b"secret", &buffer);
+    }
+
+    #[test]
+    fn test_wait_for_data() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let mut server = ConnectionStream::new(listener.accept().unwrap().0);
+
+        assert!(!server.wait_for_data(Duration::from_millis(10)).unwrap());
+
+        client.write_all(&[42]).unwrap();
+        assert!(server.wait_for_data(Duration::from_secs(5)).unwrap());
+
+        // waiting does not consume any data
+        let mut buffer = [0; 1];
+        server.read_exact(&mut buffer).unwrap();
+        assert_eq!([42], buffer);
+
+        drop(client);
+        let error = server.wait_for_data(Duration::from_secs(5)).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, error.kind());
+    }
+}
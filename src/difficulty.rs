@@ -1,3 +1,5 @@
+#[derive(Debug, Clone, Copy, PartialEq, Encodeable)]
+#[repr(u8)]
 pub enum Difficulty {
     Peaceful = 0x00,
     Easy = 0x01,
@@ -9,4 +11,4 @@ impl Default for Difficulty {
     fn default() -> Difficulty {
         Difficulty::Peaceful
     }
-}
\ No newline at end of file
+}
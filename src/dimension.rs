@@ -1,5 +1,14 @@
+#[derive(Debug, Clone, Copy, PartialEq, Encodeable)]
+#[repr(i32)]
 pub enum Dimension {
     Nether = -1,
     Overworld = 0,
     End = 1,
-}
\ No newline at end of file
+}
+
+impl Dimension {
+    /// Only the overworld has a sky, so only its chunks are sent with sky light.
+    pub fn has_sky_light(self) -> bool {
+        self == Dimension::Overworld
+    }
+}
@@ -0,0 +1,192 @@
+use aes::Aes128;
+use cfb8::cipher::generic_array::GenericArray;
+use cfb8::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use rsa::pkcs8::EncodePublicKey;
+use rsa::{Pkcs1v15Encrypt, RsaPrivateKey};
+use sha1::{Digest, Sha1};
+use std::io;
+use std::slice;
+
+/// The size of the RSA key vanilla servers use for the login.
+const KEY_SIZE: usize = 1024;
+
+/// The length of the shared secret the client generates, in bytes.
+const SHARED_SECRET_LENGTH: usize = 16;
+
+/// The RSA key pair the client encrypts the shared secret with during the login.
+pub struct KeyPair {
+    private_key: RsaPrivateKey,
+    /// the public key in the ASN.1 DER format, as it is sent to the client
+    public_key_der: Vec<u8>,
+}
+
+impl KeyPair {
+    pub fn generate() -> io::Result<KeyPair> {
+        let private_key =
+            RsaPrivateKey::new(&mut rand::thread_rng(), KEY_SIZE).map_err(io::Error::other)?;
+
+        let public_key_der = private_key
+            .to_public_key()
+            .to_public_key_der()
+            .map_err(io::Error::other)?
+            .as_bytes()
+            .to_vec();
+
+        Ok(KeyPair {
+            private_key,
+            public_key_der,
+        })
+    }
+
+    pub fn public_key_der(&self) -> &[u8] {
+        &self.public_key_der
+    }
+
+    /// Decrypts data the client encrypted with our public key.
+    pub fn decrypt(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        self.private_key
+            .decrypt(Pkcs1v15Encrypt, data)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
+
+/// AES/CFB8 cipher used in both directions after the client sent its shared secret.
+pub struct StreamCipher {
+    encryptor: cfb8::Encryptor<Aes128>,
+    decryptor: cfb8::Decryptor<Aes128>,
+}
+
+impl StreamCipher {
+    /// The shared secret is used as both the key and the initial vector.
+    pub fn new(shared_secret: &[u8]) -> io::Result<StreamCipher> {
+        if shared_secret.len() != SHARED_SECRET_LENGTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "The shared secret must be {} bytes long, {} given.",
+                    SHARED_SECRET_LENGTH,
+                    shared_secret.len()
+                ),
+            ));
+        }
+
+        let invalid_length = |_| io::Error::new(io::ErrorKind::InvalidData, "Invalid key length.");
+
+        Ok(StreamCipher {
+            encryptor: cfb8::Encryptor::new_from_slices(shared_secret, shared_secret)
+                .map_err(invalid_length)?,
+            decryptor: cfb8::Decryptor::new_from_slices(shared_secret, shared_secret)
+                .map_err(invalid_length)?,
+        })
+    }
+
+    pub fn encrypt(&mut self, data: &mut [u8]) {
+        // CFB8 works on blocks of a single byte
+        for byte in data.iter_mut() {
+            self.encryptor
+                .encrypt_block_mut(GenericArray::from_mut_slice(slice::from_mut(byte)));
+        }
+    }
+
+    pub fn decrypt(&mut self, data: &mut [u8]) {
+        for byte in data.iter_mut() {
+            self.decryptor
+                .decrypt_block_mut(GenericArray::from_mut_slice(slice::from_mut(byte)));
+        }
+    }
+}
+
+/// Computes the hash the client and the session server agree on to identify this login.
+///
+/// This is a SHA-1 digest which is interpreted as a signed two's complement number and
+/// written in hexadecimal notation without leading zeros.
+pub fn server_hash(server_id: &str, shared_secret: &[u8], public_key_der: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(server_id.as_bytes());
+    hasher.update(shared_secret);
+    hasher.update(public_key_der);
+
+    let mut digest = hasher.finalize().to_vec();
+
+    let negative = digest[0] & 0b1000_0000 != 0;
+
+    if negative {
+        // take the two's complement to get the absolute value
+        let mut carry = true;
+
+        for byte in digest.iter_mut().rev() {
+            *byte = !*byte;
+
+            if carry {
+                let (value, overflowed) = byte.overflowing_add(1);
+                *byte = value;
+                carry = overflowed;
+            }
+        }
+    }
+
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    let hex = hex.trim_start_matches('0');
+
+    if negative {
+        format!("-{}", hex)
+    } else {
+        hex.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{server_hash, KeyPair, StreamCipher};
+    use rsa::pkcs8::DecodePublicKey;
+    use rsa::{Pkcs1v15Encrypt, RsaPublicKey};
+
+    #[test]
+    fn test_server_hash() {
+        // see https://wiki.vg/Protocol_Encryption#Sample_Code
+        let mappings = vec![
+            ("Notch", "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48"),
+            ("jeb_", "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1"),
+            ("simon", "88e16a1019277b15d58faf0541e11910eb756f6"),
+        ];
+
+        for (name, expected) in mappings {
+            assert_eq!(expected, server_hash(name, &[], &[]));
+        }
+    }
+
+    #[test]
+    fn test_stream_cipher_round_trip() {
+        let shared_secret = [42; 16];
+        let mut client = StreamCipher::new(&shared_secret).unwrap();
+        let mut server = StreamCipher::new(&shared_secret).unwrap();
+
+        let mut data = b"Hello, Minecraft!".to_vec();
+        client.encrypt(&mut data[..5]);
+        client.encrypt(&mut data[5..]);
+        assert_ne!(b"Hello, Minecraft!".to_vec(), data);
+
+        // the cipher state carries over, so decrypting in different chunks works as well
+        server.decrypt(&mut data[..11]);
+        server.decrypt(&mut data[11..]);
+        assert_eq!(b"Hello, Minecraft!".to_vec(), data);
+    }
+
+    #[test]
+    fn test_stream_cipher_rejects_invalid_secret() {
+        assert!(StreamCipher::new(&[0; 15]).is_err());
+    }
+
+    #[test]
+    fn test_key_pair_decrypts() {
+        let key_pair = KeyPair::generate().unwrap();
+        let public_key = RsaPublicKey::from_public_key_der(key_pair.public_key_der()).unwrap();
+
+        let encrypted = public_key
+            .encrypt(&mut rand::thread_rng(), Pkcs1v15Encrypt, &[1, 2, 3, 4])
+            .unwrap();
+
+        assert_eq!(vec![1, 2, 3, 4], key_pair.decrypt(&encrypted).unwrap());
+        assert!(key_pair.decrypt(&[1, 2, 3]).is_err());
+    }
+}
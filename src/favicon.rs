@@ -0,0 +1,84 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The file the favicon shown in the server list is loaded from, like vanilla does.
+pub const FAVICON_PATH: &str = "server-icon.png";
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// The width and height the client expects the favicon to have.
+const FAVICON_SIZE: u32 = 64;
+
+/// Loads the favicon from the given PNG file as data URI, if the file exists.
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Option<String>> {
+    match fs::read(path) {
+        Ok(png) => from_png(&png).map(Some),
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Checks that the given bytes are a PNG image of 64x64 pixels and returns them as data URI.
+pub fn from_png(png: &[u8]) -> io::Result<String> {
+    // the signature is followed by the IHDR chunk: length, type, width and height
+    if png.len() < 24 || png[..8] != PNG_SIGNATURE || &png[12..16] != b"IHDR" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "The favicon is not a PNG image.",
+        ));
+    }
+
+    let width = u32::from_be_bytes([png[16], png[17], png[18], png[19]]);
+    let height = u32::from_be_bytes([png[20], png[21], png[22], png[23]]);
+
+    if width != FAVICON_SIZE || height != FAVICON_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "The favicon must be {0}x{0} pixels, but is {1}x{2}.",
+                FAVICON_SIZE, width, height
+            ),
+        ));
+    }
+
+    Ok(format!("data:image/png;base64,{}", base64::encode(png)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_png, load};
+
+    fn png_header(width: u8, height: u8) -> Vec<u8> {
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13];
+        png.extend(b"IHDR");
+        png.extend(vec![0, 0, 0, width, 0, 0, 0, height]);
+
+        png
+    }
+
+    #[test]
+    fn test_favicon_from_png() {
+        assert_eq!(
+            "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAEAAAABA",
+            from_png(&png_header(64, 64)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_favicon() {
+        let mut not_a_png = png_header(64, 64);
+        not_a_png[1] = b'J';
+
+        let mappings = vec![vec![], not_a_png, png_header(32, 32), png_header(64, 128)];
+
+        for png in mappings {
+            assert!(from_png(&png).is_err());
+        }
+    }
+
+    #[test]
+    fn test_missing_favicon() {
+        assert_eq!(None, load("does-not-exist.png").unwrap());
+    }
+}
@@ -0,0 +1,137 @@
+use crate::coding::long::Long;
+use rand::Rng;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// How often the server sends a Keep Alive to a playing client.
+pub const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long the client may take to answer a Keep Alive before it is disconnected.
+pub const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Keeps track of the Keep Alive packets sent to a client and its answers.
+pub struct KeepAlive {
+    /// when the last Keep Alive was sent
+    last_sent: Instant,
+    /// the id and the sending time of the Keep Alive the client still needs to answer
+    pending: Option<(Long, Instant)>,
+}
+
+impl KeepAlive {
+    pub fn new(now: Instant) -> KeepAlive {
+        KeepAlive {
+            last_sent: now,
+            pending: None,
+        }
+    }
+
+    /// Returns the id of the next Keep Alive if one needs to be sent now.
+    ///
+    /// Fails if the client did not answer the pending Keep Alive in time.
+    pub fn poll(&mut self, now: Instant) -> io::Result<Option<Long>> {
+        match self.pending {
+            Some((_, sent_at)) => {
+                if now.duration_since(sent_at) >= KEEP_ALIVE_TIMEOUT {
+                    Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!(
+                            "The client did not answer the Keep Alive within {:?}.",
+                            KEEP_ALIVE_TIMEOUT
+                        ),
+                    ))
+                } else {
+                    Ok(None)
+                }
+            }
+            None if now.duration_since(self.last_sent) >= KEEP_ALIVE_INTERVAL => {
+                let keep_alive_id: Long = rand::thread_rng().gen();
+
+                self.last_sent = now;
+                self.pending = Some((keep_alive_id, now));
+
+                Ok(Some(keep_alive_id))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Validates the answer of the client and returns the measured latency.
+    pub fn answer(&mut self, keep_alive_id: Long, now: Instant) -> io::Result<Duration> {
+        match self.pending {
+            Some((pending_id, sent_at)) if pending_id == keep_alive_id => {
+                self.pending = None;
+
+                Ok(now.duration_since(sent_at))
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Received unexpected Keep Alive {}.", keep_alive_id),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeepAlive, KEEP_ALIVE_INTERVAL, KEEP_ALIVE_TIMEOUT};
+    use std::io;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_keep_alive_is_sent_periodically() {
+        let start = Instant::now();
+        let mut keep_alive = KeepAlive::new(start);
+
+        assert_eq!(None, keep_alive.poll(start).unwrap());
+
+        let sent_at = start + KEEP_ALIVE_INTERVAL;
+        let keep_alive_id = keep_alive.poll(sent_at).unwrap().unwrap();
+
+        // nothing new is sent while waiting for the answer
+        assert_eq!(
+            None,
+            keep_alive.poll(sent_at + KEEP_ALIVE_INTERVAL).unwrap()
+        );
+
+        let latency = keep_alive
+            .answer(keep_alive_id, sent_at + Duration::from_millis(42))
+            .unwrap();
+        assert_eq!(Duration::from_millis(42), latency);
+
+        assert!(keep_alive
+            .poll(sent_at + KEEP_ALIVE_INTERVAL)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_keep_alive_times_out() {
+        let start = Instant::now();
+        let mut keep_alive = KeepAlive::new(start);
+
+        let sent_at = start + KEEP_ALIVE_INTERVAL;
+        keep_alive.poll(sent_at).unwrap().unwrap();
+
+        let error = keep_alive.poll(sent_at + KEEP_ALIVE_TIMEOUT).unwrap_err();
+        assert_eq!(io::ErrorKind::TimedOut, error.kind());
+    }
+
+    #[test]
+    fn test_keep_alive_rejects_unexpected_answers() {
+        let start = Instant::now();
+        let mut keep_alive = KeepAlive::new(start);
+
+        // nothing was sent yet
+        assert!(keep_alive.answer(1, start).is_err());
+
+        let sent_at = start + KEEP_ALIVE_INTERVAL;
+        let keep_alive_id = keep_alive.poll(sent_at).unwrap().unwrap();
+
+        assert!(keep_alive
+            .answer(keep_alive_id.wrapping_add(1), sent_at)
+            .is_err());
+        assert!(keep_alive.answer(keep_alive_id, sent_at).is_ok());
+        // every Keep Alive may only be answered once
+        assert!(keep_alive.answer(keep_alive_id, sent_at).is_err());
+    }
+}
@@ -4,148 +4,192 @@ extern crate test;
 
 #[macro_use]
 extern crate log;
+extern crate aes;
+extern crate base64;
+extern crate cfb8;
+extern crate flate2;
+extern crate md5;
+extern crate rand;
+extern crate rand_chacha;
+extern crate rsa;
 extern crate serde_json;
+extern crate sha1;
 extern crate simplelog;
+extern crate ureq;
 #[macro_use]
 extern crate serde_derive;
+#[macro_use]
+extern crate oxicraft_derive;
+#[cfg(test)]
+#[macro_use]
+extern crate proptest;
 
+mod block;
+// only plain text is sent yet, the rest of the components is for the chat and titles to come
+#[allow(dead_code)]
+mod chat;
+mod chunk;
 mod client_settings;
 mod client_status;
 mod coding;
+mod compression;
+mod config;
 mod connection;
 mod difficulty;
 mod dimension;
+mod encryption;
 mod entity;
+mod favicon;
+mod keep_alive;
 mod location;
 mod packet;
 mod player;
 mod plugin_message;
+mod server;
+mod session;
 mod world;
 
-use crate::connection::{handshake::HandshakeNextState, Connection};
-use crate::location::Location;
+use crate::coding::uuid::Uuid;
+use crate::config::ServerConfig;
+use crate::connection::Connection;
+use crate::packet::ServerboundPacket;
 use crate::player::Player;
-use log::LevelFilter;
+use crate::server::{OnlinePlayer, Server};
 use simplelog::{Config, SimpleLogger};
 use std::io;
-use std::net::{TcpListener, TcpStream};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::thread;
 use std::time::SystemTime;
 
-fn handle_connection(stream: TcpStream) -> io::Result<()> {
-    let mut connection = Connection::from_tcp_stream(stream)?;
-    let start_time = connection.start_time;
-    let connection_id = connection.connection_id;
+fn handle_connection(mut connection: Connection) -> io::Result<()> {
+    if connection.is_legacy_ping()? {
+        return connection.handle_legacy_ping();
+    }
 
-    info!(
-        "New connection from {} ({})!",
-        connection.ip_address, connection_id
-    );
+    let (username, uuid) = match log_in(&mut connection) {
+        Ok(login) => login,
+        Err(error) => return Err(connection.abort(error)),
+    };
 
-    let next_state = connection.do_handshake()?;
+    play(connection, username, uuid)
+}
 
-    match next_state {
-        HandshakeNextState::Status => {
-            connection.send_status()?;
-        }
-        HandshakeNextState::Login => {
-            let (username, uuid) = connection.prepare_login()?;
-
-            let mut player = Player::from_basic_data(connection, username, uuid);
-
-            player.send_login_success()?;
-            player.send_join_game()?;
-            player.broadcast_server_name()?;
-            player.set_spawn_location(Location::default())?;
-            // TODO: Find better fitting values
-            player.set_player_abilities(0b1101 /* flying and creative */, 0.05, 0.1)?;
-            player.read_client_settings()?;
-
-            // TODO: Make this dynamic, as this is an optional package.
-            let mut plugin_message = player.receive_plugin_message()?;
-
-            debug!("Received plugin message: {:?}", plugin_message);
-
-            if plugin_message.channel() == "minecraft:brand" {
-                info!("Client is called \"{}\".", plugin_message.data_stringify()?);
-            } else {
-                warn!(
-                    "Unknown plugin message channel {}!",
-                    plugin_message.channel()
-                );
+/// Handles the packets of the client until it logged in, returning its username and uuid.
+fn log_in(connection: &mut Connection) -> io::Result<(String, Uuid)> {
+    loop {
+        match connection.read_packet()? {
+            ServerboundPacket::Handshaking(packet) => {
+                connection.handle_handshaking_packet(packet)?;
+            }
+            ServerboundPacket::Status(packet) => {
+                connection.handle_status_packet(packet)?;
+            }
+            ServerboundPacket::Login(packet) => {
+                if let Some(login) = connection.handle_login_packet(packet)? {
+                    return Ok(login);
+                }
+            }
+            ServerboundPacket::Play(packet) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Received {:?} before the login was done.", packet),
+                ));
             }
-
-            // Tell client they're ready to spawn.
-            let teleport_id = player.set_location(&Location::default(), 0.0, 0.0, 0b0)?;
-
-            // C->S Teleport Confirm
-            player.expect_teleport_confirm(teleport_id)?;
-
-            // Send position once again to confirm
-            player.set_location(&Location::default(), 0.0, 0.0, 0b0)?;
-
-            // FIXME: This will fail since the client is sending their positon back first.
-            let client_status = player.receive_client_status()?;
-            debug!("Client status: {:?}", client_status);
-
-            info!("Client login successfully done.");
         }
     }
+}
 
-    let connect_duration = SystemTime::now().duration_since(start_time).unwrap();
+fn play(connection: Connection, username: String, uuid: Uuid) -> io::Result<()> {
+    let server = Arc::clone(&connection.server);
+    let connection_id = connection.connection_id;
 
-    info!(
-        "Connection terminated; client {} was connected for {:?}.",
-        connection_id, connect_duration
+    // removes the player again when they leave, even if this thread panics
+    let _registration = server.register_player(
+        connection_id,
+        OnlinePlayer {
+            username: username.clone(),
+            uuid,
+        },
     );
 
-    Ok(())
+    let mut player = Player::from_basic_data(connection, username, uuid);
+
+    player.play()
 }
 
 fn main() -> io::Result<()> {
-    SimpleLogger::init(
-        if cfg!(debug_assertions) {
-            LevelFilter::Trace
-        } else {
-            LevelFilter::Info
-        },
-        Config::default(),
-    )
-    .unwrap();
+    let config = ServerConfig::load_or_create(config::PROPERTIES_PATH)?;
+
+    SimpleLogger::init(config.log_level, Config::default()).unwrap();
 
     info!("Started logging.");
 
-    let listener = TcpListener::bind("0.0.0.0:25565")?;
+    let listener = TcpListener::bind(config.bind_address())?;
 
     info!("Started listening on {}.", listener.local_addr()?);
 
-    for incoming_stream in listener.incoming() {
-        handle_connection(incoming_stream?)?;
-    }
+    let mut server = Server::new(config)?;
 
-    Ok(())
-}
+    server.favicon = favicon::load(favicon::FAVICON_PATH).unwrap_or_else(|error| {
+        warn!("Could not load {}: {}", favicon::FAVICON_PATH, error);
+        None
+    });
 
-#[macro_export]
-macro_rules! build_package_data {
-    ( $( $x: expr ),* ) => {
-        {
-            #[allow(unused_imports)]
-            use crate::coding::Encodeable; // wrongly claimed for not being used.
-            use std::collections::VecDeque;
-
-            let mut package_data: VecDeque<u8> = VecDeque::with_capacity(
-                (
-                    $(
-                        $x.byte_length() +
-                    )*
-                0) as usize
-            );
+    let server = Arc::new(server);
 
-            $(
-                package_data.extend($x.encode());
-            )*
+    for incoming_stream in listener.incoming() {
+        let connection = match incoming_stream
+            .and_then(|stream| Connection::from_tcp_stream(stream, Arc::clone(&server)))
+        {
+            Ok(connection) => connection,
+            Err(error) => {
+                warn!("Failed to accept connection: {}", error);
+                continue;
+            }
+        };
+
+        let connection_id = connection.connection_id;
+        let start_time = connection.start_time;
+
+        info!(
+            "New connection from {} ({})!",
+            connection.ip_address, connection_id
+        );
+
+        let spawn_result = thread::Builder::new()
+            .name(format!("connection {}", connection_id))
+            .spawn(move || {
+                match handle_connection(connection) {
+                    Err(ref error) if error.kind() == io::ErrorKind::UnexpectedEof => {
+                        debug!("Connection {} was closed by the client.", connection_id);
+                    }
+                    Err(error) => {
+                        warn!(
+                            "Connection {} was aborted due to an error: {}",
+                            connection_id, error
+                        );
+                    }
+                    Ok(()) => {}
+                }
+
+                let connect_duration = SystemTime::now()
+                    .duration_since(start_time)
+                    .unwrap_or_default();
+
+                info!(
+                    "Connection terminated; client {} was connected for {:?}.",
+                    connection_id, connect_duration
+                );
+            });
 
-            package_data
+        if let Err(error) = spawn_result {
+            error!(
+                "Could not spawn thread for connection {}: {}",
+                connection_id, error
+            );
         }
     }
+
+    Ok(())
 }
@@ -1,50 +1,112 @@
-use crate::coding::varint::Varint;
+pub mod handshaking;
+pub mod login;
+pub mod play;
+pub mod status;
+
+use self::handshaking::ServerboundHandshakingPacket;
+use self::login::ServerboundLoginPacket;
+use self::play::ServerboundPlayPacket;
+use self::status::ServerboundStatusPacket;
+use crate::coding::error::ProtocolError;
+use crate::coding::varint::{Varint, MAX_VARINT_LENGTH};
 use crate::coding::Encodeable;
-use std::collections::VecDeque;
+use crate::compression;
+use crate::connection::ConnectionState;
 use std::fmt;
 use std::io::{self, Write};
-use std::net::TcpStream;
+
+/// The version of the protocol this server speaks.
+pub const PROTOCOL_VERSION: u16 = 404;
+
+/// The name of the Minecraft version that uses `PROTOCOL_VERSION`.
+pub const VERSION_NAME: &str = "1.13.2";
+
+/// A packet sent by the client, decoded according to the state the connection was in.
+#[derive(Debug)]
+pub enum ServerboundPacket {
+    Handshaking(ServerboundHandshakingPacket),
+    Status(ServerboundStatusPacket),
+    Login(ServerboundLoginPacket),
+    Play(ServerboundPlayPacket),
+}
 
 #[derive(Debug)]
 pub struct Packet {
     pub length: usize,
     pub packet_id: Varint,
-    pub data: PacketData,
+    pub data: Vec<u8>,
 }
 
 impl Packet {
-    pub fn from_id_and_data(packet_id: Varint, data: PacketData) -> Packet {
+    pub fn from_id_and_data(packet_id: Varint, data: Vec<u8>) -> Packet {
         Packet {
             length: 0,
             packet_id,
             data,
         }
     }
+}
 
-    pub fn send(&mut self, connection: &mut TcpStream) -> io::Result<()> {
-        let packet_id_varint = self.packet_id.encode();
-
-        let length = packet_id_varint.len() + self.data.len();
-        self.length = length;
-
-        let length_varint: VecDeque<u8> = Varint(length as i32).encode();
+/// A packet the server sends to the client, which knows its own packet id.
+pub trait ClientboundPacket: Encodeable {
+    fn packet_id(&self) -> Varint;
+}
 
-        // acquiring more capacity, because we also need to have enough space for our varint
-        let mut write_buffer: Vec<u8> = Vec::with_capacity(length + length_varint.len());
+/// Space reserved in front of the packet id for the length of the packet and, when compression
+/// is enabled, the uncompressed data length. Each of them is a varint.
+const HEADER_LENGTH: usize = 2 * MAX_VARINT_LENGTH;
+
+/// Writes a packet to the given connection, compressing it if a threshold is given.
+///
+/// The packet is encoded right behind a reserved header, so that the packet as a whole is
+/// allocated once and written with a single call.
+pub fn write_packet<W: Write, P: Encodeable + ?Sized>(
+    connection: &mut W,
+    packet_id: Varint,
+    packet: &P,
+    compression_threshold: Option<usize>,
+) -> io::Result<()> {
+    let mut buffer =
+        Vec::with_capacity(HEADER_LENGTH + packet_id.encoded_length() + packet.byte_length());
+    buffer.resize(HEADER_LENGTH, 0);
+
+    packet_id.encode_into(&mut buffer);
+    packet.encode_into(&mut buffer);
+
+    // The packet consists of a Varint that represents the size of this package, the package id and the data.
+    // When compression is enabled, the (possibly compressed) package id and data are prefixed with their uncompressed size.
+    let content_start = match compression_threshold {
+        Some(threshold) if buffer.len() - HEADER_LENGTH >= threshold => {
+            let mut compressed = vec![0; MAX_VARINT_LENGTH];
+            compression::compress(&buffer[HEADER_LENGTH..], &mut compressed)?;
+            buffer = compressed;
+
+            MAX_VARINT_LENGTH
+        }
+        // too small to be compressed, which is marked by an uncompressed size of zero
+        Some(_) => HEADER_LENGTH - Varint(0).encoded_length(),
+        None => HEADER_LENGTH,
+    };
 
-        // The packet consists of a Varint that represents the size of this package, the package id and the data.
+    let length = Varint((buffer.len() - content_start) as i32);
+    let packet_start = content_start - length.encoded_length();
 
-        write_buffer.extend(length_varint);
-        write_buffer.extend(packet_id_varint);
+    length.encode_into(&mut &mut buffer[packet_start..content_start]);
 
-        write_buffer.extend(self.data.to_bytes());
+    connection.write_all(&buffer[packet_start..])?;
 
-        connection.write_all(&write_buffer)?;
+    debug!(
+        "Sent packet {:#X} of {} bytes",
+        packet_id.0,
+        buffer.len() - content_start
+    );
 
-        debug!("Sent data {:?}", self);
+    Ok(())
+}
 
-        Ok(())
-    }
+/// Builds the error returned when the client sent a packet that is not valid in the given state.
+pub fn unexpected_packet(state: ConnectionState, packet_id: i32) -> io::Error {
+    ProtocolError::UnexpectedPacket { state, packet_id }.into()
 }
 
 impl fmt::Display for Packet {
@@ -57,28 +119,82 @@ impl fmt::Display for Packet {
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub enum PacketData {
-    Command,
-    Data(VecDeque<u8>),
-    Message(String),
-}
+#[cfg(test)]
+mod tests {
+    use super::play::{ClientboundPlayPacket, ClientboundPlayerPositionAndLook};
+    use super::{write_packet, ClientboundPacket};
+    use crate::coding::varint::Varint;
+    use crate::plugin_message::{PluginMessage, PluginMessageOrigin};
 
-impl PacketData {
-    pub fn len(&self) -> usize {
-        if let PacketData::Data(packet_data) = self {
-            return packet_data.len();
-        }
+    #[test]
+    fn test_write_uncompressed() {
+        let mut output: Vec<u8> = Vec::new();
+        write_packet(&mut output, Varint(0x01), &vec![0xAA, 0xBB], None).unwrap();
 
-        unimplemented!();
+        assert_eq!(vec![0x03, 0x01, 0xAA, 0xBB], output);
     }
 
-    pub fn to_bytes(&self) -> Vec<u8> {
-        if let PacketData::Data(packet_data) = self {
-            // TODO: Optimize this.
-            return packet_data.iter().cloned().collect::<Vec<_>>();
-        }
+    #[test]
+    fn test_write_compressed_below_threshold() {
+        let mut output: Vec<u8> = Vec::new();
+        write_packet(&mut output, Varint(0x01), &vec![0xAA, 0xBB], Some(256)).unwrap();
+
+        assert_eq!(vec![0x04, 0x00, 0x01, 0xAA, 0xBB], output);
+    }
+
+    #[test]
+    fn test_write_compressed_above_threshold() {
+        let mut output: Vec<u8> = Vec::new();
+        write_packet(&mut output, Varint(0x01), &vec![0x42; 511], Some(256)).unwrap();
 
-        unimplemented!();
+        // the (single byte) length of the packet, followed by 512 (packet id and data) as a varint
+        assert_eq!(vec![0x80, 0x04], output[1..3].to_vec());
+        assert!(output.len() < 512);
+        assert_eq!(output.len() - 1, output[0] as usize);
+    }
+
+    #[test]
+    fn test_write_long_packet() {
+        let mut output: Vec<u8> = Vec::new();
+        write_packet(&mut output, Varint(0x01), &vec![0x42; 200], None).unwrap();
+
+        // 201 bytes need a varint of two bytes
+        assert_eq!(vec![0xC9, 0x01, 0x01], output[..3].to_vec());
+        assert_eq!(203, output.len());
+    }
+
+    use test::{black_box, Bencher};
+    #[bench]
+    fn bench_write_position_and_look(b: &mut Bencher) {
+        let packet =
+            ClientboundPlayPacket::PlayerPositionAndLook(ClientboundPlayerPositionAndLook {
+                x: 0.5,
+                y: 64.0,
+                z: -0.5,
+                yaw: 90.0,
+                pitch: 0.0,
+                flags: 0,
+                teleport_id: Varint(1),
+            });
+        let mut output = Vec::with_capacity(64);
+
+        b.iter(|| {
+            output.clear();
+            write_packet(&mut output, packet.packet_id(), black_box(&packet), None).unwrap();
+        });
+    }
+    #[bench]
+    fn bench_write_large_plugin_message(b: &mut Bencher) {
+        let packet = ClientboundPlayPacket::PluginMessage(PluginMessage::new(
+            "oxicraft:bench".to_owned(),
+            vec![0x42; 4096],
+            PluginMessageOrigin::Server,
+        ));
+        let mut output = Vec::with_capacity(4200);
+
+        b.iter(|| {
+            output.clear();
+            write_packet(&mut output, packet.packet_id(), black_box(&packet), None).unwrap();
+        });
     }
 }
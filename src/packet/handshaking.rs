@@ -0,0 +1,94 @@
+use super::Packet;
+use crate::coding::buffer::ReadCursor;
+use crate::coding::short::UnsignedShort;
+use crate::coding::string::MinecraftString;
+use crate::coding::varint::Varint;
+use crate::coding::Decodeable;
+use crate::connection::ConnectionState;
+use std::io;
+
+/// Packets the client may send while the connection is in the `Handshaking` state.
+#[derive(Debug)]
+pub enum ServerboundHandshakingPacket {
+    /// 0x00
+    Handshake(Handshake),
+}
+
+impl Decodeable<ServerboundHandshakingPacket, io::Error> for Packet {
+    fn decode(&mut self) -> Result<ServerboundHandshakingPacket, io::Error> {
+        let packet_id = self.packet_id.0;
+        let mut data = ReadCursor::new(&self.data);
+
+        match packet_id {
+            0x00 => {
+                let packet = ServerboundHandshakingPacket::Handshake(data.decode()?);
+
+                if !data.is_empty() {
+                    warn!("The handshake packet sent by the client contains more data than expected. Rest of data: {:?}", data);
+                }
+
+                Ok(packet)
+            }
+            _ => Err(super::unexpected_packet(
+                ConnectionState::Handshaking,
+                packet_id,
+            )),
+        }
+    }
+}
+
+/// C->S Handshake
+#[derive(Debug, PartialEq, Decodeable)]
+pub struct Handshake {
+    pub protocol_version: Varint,
+    #[max_length = 255]
+    pub server_address: MinecraftString,
+    pub server_port: UnsignedShort,
+    pub next_state: Varint,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Handshake, ServerboundHandshakingPacket};
+    use crate::coding::varint::Varint;
+    use crate::coding::Decodeable;
+    use crate::packet::Packet;
+
+    fn handshake_bytes() -> Vec<u8> {
+        vec![
+            0x94, 0x03, // protocol version 404
+            9, 108, 111, 99, 97, 108, 104, 111, 115, 116, // "localhost"
+            99, 221, // port 25565
+            0x01, // next state: status
+        ]
+    }
+
+    #[test]
+    fn test_decode_handshake() {
+        let mut packet = Packet::from_id_and_data(Varint(0x00), handshake_bytes());
+
+        let ServerboundHandshakingPacket::Handshake(handshake) = packet.decode().unwrap();
+
+        assert_eq!(
+            Handshake {
+                protocol_version: Varint(404),
+                server_address: "localhost".to_owned(),
+                server_port: 25565,
+                next_state: Varint(1),
+            },
+            handshake
+        );
+    }
+
+    #[test]
+    fn test_decode_unexpected_packet() {
+        let mut packet = Packet::from_id_and_data(Varint(0x01), vec![]);
+
+        let actual: Result<ServerboundHandshakingPacket, _> = packet.decode();
+
+        assert_eq!(
+            "Packet 0x1 is not expected in state Handshaking.",
+            actual.unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use super::{ClientboundPacket, Packet};
+use crate::coding::buffer::{BufMut, ReadCursor};
+use crate::coding::byte_array::ByteArray;
+use crate::coding::string::MinecraftString;
+use crate::coding::uuid::HyphenatedUuid;
+use crate::coding::varint::Varint;
+use crate::coding::{Decodeable, Encodeable};
+use crate::connection::ConnectionState;
+use std::io;
+
+/// Packets the client may send while the connection is in the `Login` state.
+#[derive(Debug, PartialEq)]
+pub enum ServerboundLoginPacket {
+    /// 0x00
+    LoginStart(LoginStart),
+    /// 0x01
+    EncryptionResponse(EncryptionResponse),
+}
+
+impl Decodeable<ServerboundLoginPacket, io::Error> for Packet {
+    fn decode(&mut self) -> Result<ServerboundLoginPacket, io::Error> {
+        let packet_id = self.packet_id.0;
+        let mut data = ReadCursor::new(&self.data);
+
+        match packet_id {
+            0x00 => Ok(ServerboundLoginPacket::LoginStart(data.decode()?)),
+            0x01 => Ok(ServerboundLoginPacket::EncryptionResponse(data.decode()?)),
+            _ => Err(super::unexpected_packet(ConnectionState::Login, packet_id)),
+        }
+    }
+}
+
+/// Packets the server may send while the connection is in the `Login` state.
+#[derive(Debug)]
+pub enum ClientboundLoginPacket {
+    /// 0x00
+    Disconnect(Disconnect),
+    /// 0x01
+    EncryptionRequest(EncryptionRequest),
+    /// 0x02
+    LoginSuccess(LoginSuccess),
+    /// 0x03
+    SetCompression(SetCompression),
+}
+
+impl ClientboundPacket for ClientboundLoginPacket {
+    fn packet_id(&self) -> Varint {
+        match self {
+            ClientboundLoginPacket::Disconnect(_) => Varint(0x00),
+            ClientboundLoginPacket::EncryptionRequest(_) => Varint(0x01),
+            ClientboundLoginPacket::LoginSuccess(_) => Varint(0x02),
+            ClientboundLoginPacket::SetCompression(_) => Varint(0x03),
+        }
+    }
+}
+
+impl Encodeable for ClientboundLoginPacket {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        match self {
+            ClientboundLoginPacket::Disconnect(packet) => packet.encode_into(buffer),
+            ClientboundLoginPacket::EncryptionRequest(packet) => packet.encode_into(buffer),
+            ClientboundLoginPacket::LoginSuccess(packet) => packet.encode_into(buffer),
+            ClientboundLoginPacket::SetCompression(packet) => packet.encode_into(buffer),
+        }
+    }
+
+    fn byte_length(&self) -> usize {
+        match self {
+            ClientboundLoginPacket::Disconnect(packet) => packet.byte_length(),
+            ClientboundLoginPacket::EncryptionRequest(packet) => packet.byte_length(),
+            ClientboundLoginPacket::LoginSuccess(packet) => packet.byte_length(),
+            ClientboundLoginPacket::SetCompression(packet) => packet.byte_length(),
+        }
+    }
+}
+
+/// C->S Login Start
+#[derive(Debug, PartialEq, Decodeable)]
+pub struct LoginStart {
+    #[max_length = 16]
+    pub username: MinecraftString,
+}
+
+/// C->S Encryption Response
+#[derive(Debug, PartialEq, Decodeable)]
+pub struct EncryptionResponse {
+    /// the shared secret, encrypted with the public key of the server
+    pub shared_secret: ByteArray,
+    /// the verify token of the Encryption Request, encrypted with the public key of the server
+    pub verify_token: ByteArray,
+}
+
+/// S->C Disconnect
+#[derive(Debug, PartialEq, Encodeable)]
+pub struct Disconnect {
+    /// the reason as JSON chat component
+    pub reason: MinecraftString,
+}
+
+/// S->C Encryption Request
+#[derive(Debug, PartialEq, Encodeable)]
+pub struct EncryptionRequest {
+    /// empty since 1.7
+    pub server_id: MinecraftString,
+    /// the public key of the server in the ASN.1 DER format
+    pub public_key: ByteArray,
+    pub verify_token: ByteArray,
+}
+
+/// S->C Login Success
+#[derive(Debug, PartialEq, Encodeable)]
+pub struct LoginSuccess {
+    pub uuid: HyphenatedUuid,
+    pub username: MinecraftString,
+}
+
+/// S->C Set Compression
+#[derive(Debug, PartialEq, Encodeable)]
+pub struct SetCompression {
+    /// the minimum size of a packet before it is compressed, negative values disable compression
+    pub threshold: Varint,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        EncryptionRequest, EncryptionResponse, LoginStart, LoginSuccess, ServerboundLoginPacket,
+    };
+    use crate::coding::byte_array::ByteArray;
+    use crate::coding::uuid::{HyphenatedUuid, Uuid};
+    use crate::coding::varint::Varint;
+    use crate::coding::{Decodeable, Encodeable};
+    use crate::packet::Packet;
+
+    #[test]
+    fn test_decode_login_start() {
+        let mut packet =
+            Packet::from_id_and_data(Varint(0x00), vec![6, 106, 101, 110, 115, 49, 111]);
+
+        let login_start = packet.decode().unwrap();
+
+        assert_eq!(
+            ServerboundLoginPacket::LoginStart(LoginStart {
+                username: "jens1o".to_owned()
+            }),
+            login_start
+        );
+    }
+
+    #[test]
+    fn test_decode_encryption_response() {
+        let mut packet = Packet::from_id_and_data(Varint(0x01), vec![2, 1, 2, 4, 3, 4, 5, 6]);
+
+        let encryption_response = packet.decode().unwrap();
+
+        assert_eq!(
+            ServerboundLoginPacket::EncryptionResponse(EncryptionResponse {
+                shared_secret: ByteArray(vec![1, 2]),
+                verify_token: ByteArray(vec![3, 4, 5, 6]),
+            }),
+            encryption_response
+        );
+    }
+
+    #[test]
+    fn test_encode_encryption_request() {
+        let encryption_request = EncryptionRequest {
+            server_id: String::new(),
+            public_key: ByteArray(vec![48, 129, 159]),
+            verify_token: ByteArray(vec![1, 2, 3, 4]),
+        };
+
+        assert_eq!(
+            vec![0, 3, 48, 129, 159, 4, 1, 2, 3, 4],
+            encryption_request.encode()
+        );
+    }
+
+    #[test]
+    fn test_decode_login_start_with_too_long_name() {
+        let mut data = vec![17];
+        data.extend(vec![97; 17]);
+
+        let mut packet = Packet::from_id_and_data(Varint(0x00), data);
+
+        let actual: Result<ServerboundLoginPacket, _> = packet.decode();
+
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_encode_login_success() {
+        let login_success = LoginSuccess {
+            uuid: HyphenatedUuid(Uuid(0x8e38_3e9f_608e_4556_97c9_6131_2c74_1ea0)),
+            username: "jens1o".to_owned(),
+        };
+
+        let mut expected = vec![36];
+        expected.extend(b"8e383e9f-608e-4556-97c9-61312c741ea0");
+        expected.extend(vec![6, 106, 101, 110, 115, 49, 111]);
+
+        assert_eq!(expected, login_success.encode());
+    }
+}
@@ -0,0 +1,575 @@
+use super::{ClientboundPacket, Packet};
+use crate::chunk::Chunk;
+use crate::client_settings::ClientSettings;
+use crate::client_status::ClientStatus;
+use crate::coding::boolean::MinecraftBoolean;
+use crate::coding::buffer::{BufMut, ReadCursor};
+use crate::coding::byte_array::ByteArray;
+use crate::coding::double::MinecraftDouble;
+use crate::coding::float::MinecraftFloat;
+use crate::coding::gamemode::Gamemode;
+use crate::coding::int::MinecraftInt;
+use crate::coding::level_type::LevelType;
+use crate::coding::long::Long;
+use crate::coding::nbt::Nbt;
+use crate::coding::signed_byte::MinecraftSignedByte;
+use crate::coding::string::MinecraftString;
+use crate::coding::unsigned_byte::MinecraftUnsignedByte;
+use crate::coding::varint::Varint;
+use crate::coding::{Decodeable, Encodeable};
+use crate::difficulty::Difficulty;
+use crate::dimension::Dimension;
+use crate::location::Location;
+use crate::plugin_message::PluginMessage;
+use std::io;
+
+/// Packets the client may send while the connection is in the `Play` state.
+///
+/// Packets that aren't understood (yet) are kept as `Unknown` so they can be skipped.
+#[derive(Debug)]
+pub enum ServerboundPlayPacket {
+    /// 0x00
+    TeleportConfirm(TeleportConfirm),
+    /// 0x03
+    ClientStatus(ClientStatus),
+    /// 0x04
+    ClientSettings(ClientSettings),
+    /// 0x0A
+    PluginMessage(PluginMessage),
+    /// 0x0E
+    KeepAlive(KeepAlive),
+    /// 0x0F
+    PlayerOnGround(PlayerOnGround),
+    /// 0x10
+    PlayerPosition(PlayerPosition),
+    /// 0x11
+    PlayerPositionAndLook(PlayerPositionAndLook),
+    /// 0x12
+    PlayerLook(PlayerLook),
+    Unknown { packet_id: Varint },
+}
+
+impl Decodeable<ServerboundPlayPacket, io::Error> for Packet {
+    fn decode(&mut self) -> Result<ServerboundPlayPacket, io::Error> {
+        let packet_id = self.packet_id;
+        let mut data = ReadCursor::new(&self.data);
+
+        Ok(match packet_id.0 {
+            0x00 => ServerboundPlayPacket::TeleportConfirm(data.decode()?),
+            0x03 => ServerboundPlayPacket::ClientStatus(data.decode()?),
+            0x04 => ServerboundPlayPacket::ClientSettings(data.decode()?),
+            0x0A => ServerboundPlayPacket::PluginMessage(data.decode()?),
+            0x0E => ServerboundPlayPacket::KeepAlive(data.decode()?),
+            0x0F => ServerboundPlayPacket::PlayerOnGround(data.decode()?),
+            0x10 => ServerboundPlayPacket::PlayerPosition(data.decode()?),
+            0x11 => ServerboundPlayPacket::PlayerPositionAndLook(data.decode()?),
+            0x12 => ServerboundPlayPacket::PlayerLook(data.decode()?),
+            _ => ServerboundPlayPacket::Unknown { packet_id },
+        })
+    }
+}
+
+/// Packets the server may send while the connection is in the `Play` state.
+#[derive(Debug)]
+pub enum ClientboundPlayPacket {
+    /// 0x19
+    PluginMessage(PluginMessage),
+    /// 0x1B
+    Disconnect(Disconnect),
+    /// 0x1F
+    UnloadChunk(UnloadChunk),
+    /// 0x21
+    KeepAlive(ClientboundKeepAlive),
+    /// 0x22
+    ChunkData(ChunkData),
+    /// 0x25
+    JoinGame(JoinGame),
+    /// 0x2E
+    PlayerAbilities(PlayerAbilities),
+    /// 0x32
+    PlayerPositionAndLook(ClientboundPlayerPositionAndLook),
+    /// 0x49
+    SpawnPosition(SpawnPosition),
+}
+
+impl ClientboundPacket for ClientboundPlayPacket {
+    fn packet_id(&self) -> Varint {
+        Varint(match self {
+            ClientboundPlayPacket::PluginMessage(_) => 0x19,
+            ClientboundPlayPacket::Disconnect(_) => 0x1B,
+            ClientboundPlayPacket::UnloadChunk(_) => 0x1F,
+            ClientboundPlayPacket::KeepAlive(_) => 0x21,
+            ClientboundPlayPacket::ChunkData(_) => 0x22,
+            ClientboundPlayPacket::JoinGame(_) => 0x25,
+            ClientboundPlayPacket::PlayerAbilities(_) => 0x2E,
+            ClientboundPlayPacket::PlayerPositionAndLook(_) => 0x32,
+            ClientboundPlayPacket::SpawnPosition(_) => 0x49,
+        })
+    }
+}
+
+impl Encodeable for ClientboundPlayPacket {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        match self {
+            ClientboundPlayPacket::PluginMessage(packet) => packet.encode_into(buffer),
+            ClientboundPlayPacket::Disconnect(packet) => packet.encode_into(buffer),
+            ClientboundPlayPacket::UnloadChunk(packet) => packet.encode_into(buffer),
+            ClientboundPlayPacket::KeepAlive(packet) => packet.encode_into(buffer),
+            ClientboundPlayPacket::ChunkData(packet) => packet.encode_into(buffer),
+            ClientboundPlayPacket::JoinGame(packet) => packet.encode_into(buffer),
+            ClientboundPlayPacket::PlayerAbilities(packet) => packet.encode_into(buffer),
+            ClientboundPlayPacket::PlayerPositionAndLook(packet) => packet.encode_into(buffer),
+            ClientboundPlayPacket::SpawnPosition(packet) => packet.encode_into(buffer),
+        }
+    }
+
+    fn byte_length(&self) -> usize {
+        match self {
+            ClientboundPlayPacket::PluginMessage(packet) => packet.byte_length(),
+            ClientboundPlayPacket::Disconnect(packet) => packet.byte_length(),
+            ClientboundPlayPacket::UnloadChunk(packet) => packet.byte_length(),
+            ClientboundPlayPacket::KeepAlive(packet) => packet.byte_length(),
+            ClientboundPlayPacket::ChunkData(packet) => packet.byte_length(),
+            ClientboundPlayPacket::JoinGame(packet) => packet.byte_length(),
+            ClientboundPlayPacket::PlayerAbilities(packet) => packet.byte_length(),
+            ClientboundPlayPacket::PlayerPositionAndLook(packet) => packet.byte_length(),
+            ClientboundPlayPacket::SpawnPosition(packet) => packet.byte_length(),
+        }
+    }
+}
+
+/// C->S Teleport Confirm
+#[derive(Debug, PartialEq, Decodeable)]
+pub struct TeleportConfirm {
+    /// the id given by the Player Position And Look packet
+    pub teleport_id: Varint,
+}
+
+/// C->S Keep Alive
+///
+/// The answer of the client to a Keep Alive sent by the server.
+#[derive(Debug, PartialEq, Decodeable)]
+pub struct KeepAlive {
+    pub keep_alive_id: Long,
+}
+
+/// C->S Player
+///
+/// Sent by the client to indicate whether it is on the ground, without moving.
+#[derive(Debug, PartialEq, Decodeable)]
+pub struct PlayerOnGround {
+    pub on_ground: MinecraftBoolean,
+}
+
+/// C->S Player Position
+#[derive(Debug, PartialEq, Decodeable)]
+pub struct PlayerPosition {
+    pub x: MinecraftDouble,
+    /// the absolute position of the feet of the player
+    pub feet_y: MinecraftDouble,
+    pub z: MinecraftDouble,
+    pub on_ground: MinecraftBoolean,
+}
+
+/// C->S Player Position And Look
+#[derive(Debug, PartialEq, Decodeable)]
+pub struct PlayerPositionAndLook {
+    pub x: MinecraftDouble,
+    /// the absolute position of the feet of the player
+    pub feet_y: MinecraftDouble,
+    pub z: MinecraftDouble,
+    pub yaw: MinecraftFloat,
+    pub pitch: MinecraftFloat,
+    pub on_ground: MinecraftBoolean,
+}
+
+/// C->S Player Look
+#[derive(Debug, PartialEq, Decodeable)]
+pub struct PlayerLook {
+    pub yaw: MinecraftFloat,
+    pub pitch: MinecraftFloat,
+    pub on_ground: MinecraftBoolean,
+}
+
+/// S->C Disconnect
+#[derive(Debug, PartialEq, Encodeable)]
+pub struct Disconnect {
+    /// the reason as JSON chat component
+    pub reason: MinecraftString,
+}
+
+/// S->C Unload Chunk
+///
+/// Tells the client to forget a chunk that is out of view now.
+#[derive(Debug, PartialEq, Encodeable)]
+pub struct UnloadChunk {
+    pub chunk_x: MinecraftInt,
+    pub chunk_z: MinecraftInt,
+}
+
+/// S->C Keep Alive
+///
+/// The client needs to answer with the same id, otherwise it is disconnected.
+#[derive(Debug, PartialEq, Encodeable)]
+pub struct ClientboundKeepAlive {
+    pub keep_alive_id: Long,
+}
+
+/// S->C Chunk Data
+///
+/// Sends the blocks of a chunk, see `Chunk` for the format of the data.
+#[derive(Debug, PartialEq)]
+pub struct ChunkData {
+    pub chunk_x: MinecraftInt,
+    pub chunk_z: MinecraftInt,
+    /// whether the biomes are sent too, which is needed for chunks the client does not know yet
+    pub full_chunk: MinecraftBoolean,
+    /// bit field of the sections contained in the data, starting with the lowest
+    pub primary_bit_mask: Varint,
+    pub data: ByteArray,
+    pub block_entities: Vec<Nbt>,
+}
+
+impl ChunkData {
+    /// Builds the packet sending the whole chunk, with sky light if the dimension has any.
+    pub fn full_chunk(chunk: &Chunk, dimension: Dimension) -> ChunkData {
+        let mut data = vec![];
+        chunk.encode_data_into(&mut data, dimension.has_sky_light());
+
+        ChunkData {
+            chunk_x: chunk.x,
+            chunk_z: chunk.z,
+            full_chunk: true,
+            primary_bit_mask: Varint(chunk.primary_bit_mask()),
+            data: ByteArray(data),
+            block_entities: chunk
+                .block_entities
+                .iter()
+                .map(|block_entity| Nbt::new("", block_entity.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl Encodeable for ChunkData {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        self.chunk_x.encode_into(buffer);
+        self.chunk_z.encode_into(buffer);
+        self.full_chunk.encode_into(buffer);
+        self.primary_bit_mask.encode_into(buffer);
+        self.data.encode_into(buffer);
+        Varint(self.block_entities.len() as i32).encode_into(buffer);
+
+        for block_entity in &self.block_entities {
+            block_entity.encode_into(buffer);
+        }
+    }
+
+    fn byte_length(&self) -> usize {
+        4 + 4
+            + 1
+            + self.primary_bit_mask.encoded_length()
+            + self.data.byte_length()
+            + Varint(self.block_entities.len() as i32).encoded_length()
+            + self
+                .block_entities
+                .iter()
+                .map(Encodeable::byte_length)
+                .sum::<usize>()
+    }
+}
+
+/// S->C Join Game
+///
+/// Informs the client about the general overview on the world they will be joining.
+#[derive(Debug, PartialEq, Encodeable)]
+pub struct JoinGame {
+    pub entity_id: MinecraftInt,
+    pub gamemode: Gamemode,
+    pub dimension: Dimension,
+    pub difficulty: Difficulty,
+    /// ignored by the client, was once used to draw the player list
+    pub max_players: MinecraftUnsignedByte,
+    pub level_type: LevelType,
+    pub reduced_debug_info: MinecraftBoolean,
+}
+
+/// S->C Player Abilities
+#[derive(Debug, PartialEq, Encodeable)]
+pub struct PlayerAbilities {
+    /// bit field: invulnerable (0x01), flying (0x02), allow flying (0x04), creative mode (0x08)
+    pub flags: MinecraftSignedByte,
+    pub flying_speed: MinecraftFloat,
+    pub field_of_view_modifier: MinecraftFloat,
+}
+
+/// S->C Player Position And Look
+///
+/// Teleports the player, who needs to confirm it with a Teleport Confirm packet.
+#[derive(Debug, PartialEq, Encodeable)]
+pub struct ClientboundPlayerPositionAndLook {
+    pub x: MinecraftDouble,
+    pub y: MinecraftDouble,
+    pub z: MinecraftDouble,
+    pub yaw: MinecraftFloat,
+    pub pitch: MinecraftFloat,
+    /// bit field telling which of the values are relative instead of absolute
+    pub flags: MinecraftSignedByte,
+    pub teleport_id: Varint,
+}
+
+/// S->C Spawn Position
+///
+/// Sets where the client will respawn and where their compass points to.
+#[derive(Debug, PartialEq, Encodeable)]
+pub struct SpawnPosition {
+    pub location: Location,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ChunkData, ClientboundKeepAlive, ClientboundPlayPacket, ClientboundPlayerPositionAndLook,
+        Disconnect, JoinGame, KeepAlive, PlayerAbilities, PlayerLook, PlayerPosition,
+        ServerboundPlayPacket, SpawnPosition, TeleportConfirm, UnloadChunk,
+    };
+    use crate::chunk::Chunk;
+    use crate::coding::gamemode::Gamemode;
+    use crate::coding::level_type::LevelType;
+    use crate::coding::nbt::Compound;
+    use crate::coding::varint::Varint;
+    use crate::coding::{Decodeable, Encodeable};
+    use crate::difficulty::Difficulty;
+    use crate::dimension::Dimension;
+    use crate::location::Location;
+    use crate::packet::{ClientboundPacket, Packet};
+    use crate::plugin_message::{PluginMessage, PluginMessageOrigin};
+
+    fn decode(packet_id: i32, data: Vec<u8>) -> ServerboundPlayPacket {
+        Packet::from_id_and_data(Varint(packet_id), data)
+            .decode()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_decode_teleport_confirm() {
+        match decode(0x00, vec![0x80, 0x01]) {
+            ServerboundPlayPacket::TeleportConfirm(packet) => assert_eq!(
+                TeleportConfirm {
+                    teleport_id: Varint(128)
+                },
+                packet
+            ),
+            packet => panic!("Decoded wrong packet {:?}", packet),
+        }
+    }
+
+    #[test]
+    fn test_decode_player_position() {
+        let mut data = vec![0x40, 0x59, 0, 0, 0, 0, 0, 0]; // 100.0
+        data.extend(vec![0x40, 0x50, 0x40, 0, 0, 0, 0, 0]); // 65.0
+        data.extend(vec![0xC0, 0x24, 0, 0, 0, 0, 0, 0]); // -10.0
+        data.push(0x01);
+
+        match decode(0x10, data) {
+            ServerboundPlayPacket::PlayerPosition(packet) => assert_eq!(
+                PlayerPosition {
+                    x: 100.0,
+                    feet_y: 65.0,
+                    z: -10.0,
+                    on_ground: true,
+                },
+                packet
+            ),
+            packet => panic!("Decoded wrong packet {:?}", packet),
+        }
+    }
+
+    #[test]
+    fn test_decode_player_look() {
+        match decode(0x12, vec![0x41, 0x48, 0, 0, 0xBF, 0x80, 0, 0, 0x00]) {
+            ServerboundPlayPacket::PlayerLook(packet) => assert_eq!(
+                PlayerLook {
+                    yaw: 12.5,
+                    pitch: -1.0,
+                    on_ground: false,
+                },
+                packet
+            ),
+            packet => panic!("Decoded wrong packet {:?}", packet),
+        }
+    }
+
+    #[test]
+    fn test_decode_keep_alive() {
+        match decode(0x0E, vec![0, 0, 0, 0, 0, 0, 0x01, 0x02]) {
+            ServerboundPlayPacket::KeepAlive(packet) => assert_eq!(
+                KeepAlive {
+                    keep_alive_id: 0x0102
+                },
+                packet
+            ),
+            packet => panic!("Decoded wrong packet {:?}", packet),
+        }
+    }
+
+    #[test]
+    fn test_decode_unknown_packet() {
+        match decode(0x7F, vec![0x01, 0x02]) {
+            ServerboundPlayPacket::Unknown { packet_id } => assert_eq!(0x7F, packet_id),
+            packet => panic!("Decoded wrong packet {:?}", packet),
+        }
+    }
+
+    #[test]
+    fn test_encode_plugin_message_packet() {
+        let packet = ClientboundPlayPacket::PluginMessage(PluginMessage::new(
+            "brand".to_owned(),
+            b"oxi".to_vec(),
+            PluginMessageOrigin::Server,
+        ));
+
+        let mut expected = vec![15];
+        expected.extend(b"minecraft:brand");
+        expected.extend(b"oxi");
+
+        assert_eq!(Varint(0x19), packet.packet_id());
+        assert_eq!(expected, packet.encode());
+    }
+
+    #[test]
+    fn test_encode_disconnect() {
+        let packet = ClientboundPlayPacket::Disconnect(Disconnect {
+            reason: r#"{"text":"Bye"}"#.to_owned(),
+        });
+
+        let mut expected = vec![14];
+        expected.extend(br#"{"text":"Bye"}"#);
+
+        assert_eq!(Varint(0x1B), packet.packet_id());
+        assert_eq!(expected, packet.encode());
+    }
+
+    #[test]
+    fn test_encode_unload_chunk() {
+        let packet = ClientboundPlayPacket::UnloadChunk(UnloadChunk {
+            chunk_x: -1,
+            chunk_z: 2,
+        });
+
+        assert_eq!(Varint(0x1F), packet.packet_id());
+        assert_eq!(
+            vec![0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x02],
+            packet.encode()
+        );
+    }
+
+    #[test]
+    fn test_encode_keep_alive() {
+        let packet = ClientboundPlayPacket::KeepAlive(ClientboundKeepAlive { keep_alive_id: -2 });
+
+        assert_eq!(Varint(0x21), packet.packet_id());
+        assert_eq!(
+            vec![0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE],
+            packet.encode()
+        );
+    }
+
+    #[test]
+    fn test_encode_chunk_data() {
+        let mut chunk = Chunk::new(-1, 2);
+        chunk.set_block(0, 0, 0, 33);
+        chunk
+            .block_entities
+            .push(r#"{id:"minecraft:sign",x:0,y:1,z:0}"#.parse::<Compound>().unwrap());
+
+        let packet = ChunkData::full_chunk(&chunk, Dimension::Nether);
+        assert_eq!(Varint(0x01), packet.primary_bit_mask);
+
+        let mut section = vec![];
+        chunk.section(0).unwrap().encode_into(&mut section, false);
+        assert_eq!(section, packet.data.0[..section.len()].to_vec());
+        assert_eq!(section.len() + 256 * 4, packet.data.0.len());
+
+        let encoded = ClientboundPlayPacket::ChunkData(packet).encode();
+        let data_length = section.len() + 256 * 4;
+
+        let mut expected = vec![0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x02]; // x and z
+        expected.push(0x01); // full chunk
+        expected.push(0x01); // primary bit mask
+        expected.extend(Varint(data_length as i32).encode());
+        assert_eq!(expected, encoded[..expected.len()].to_vec());
+
+        // one block entity, which is a compound with an empty name
+        let block_entities = &encoded[expected.len() + data_length..];
+        assert_eq!(vec![0x01, 0x0A, 0x00, 0x00], block_entities[..4].to_vec());
+        assert_eq!(0x00, *block_entities.last().unwrap());
+    }
+
+    #[test]
+    fn test_encode_join_game() {
+        let join_game = JoinGame {
+            entity_id: 0x0102_0304,
+            gamemode: Gamemode::Creative,
+            dimension: Dimension::Nether,
+            difficulty: Difficulty::Peaceful,
+            max_players: 20,
+            level_type: LevelType::Flat,
+            reduced_debug_info: false,
+        };
+
+        let mut expected = vec![0x01, 0x02, 0x03, 0x04]; // entity id
+        expected.push(0x01); // gamemode
+        expected.extend(vec![0xFF, 0xFF, 0xFF, 0xFF]); // dimension
+        expected.extend(vec![0x00, 20]);
+        expected.extend(vec![4, 102, 108, 97, 116]); // "flat"
+        expected.push(0x00);
+
+        assert_eq!(expected, join_game.encode());
+    }
+
+    #[test]
+    fn test_encode_player_abilities() {
+        let player_abilities = PlayerAbilities {
+            flags: 0b1101,
+            flying_speed: 1.0,
+            field_of_view_modifier: -12.5,
+        };
+
+        assert_eq!(
+            vec![0x0D, 0x3F, 0x80, 0, 0, 0xC1, 0x48, 0, 0],
+            player_abilities.encode()
+        );
+    }
+
+    #[test]
+    fn test_encode_player_position_and_look() {
+        let player_position_and_look = ClientboundPlayerPositionAndLook {
+            x: 100.0,
+            y: 65.0,
+            z: -10.0,
+            yaw: 12.5,
+            pitch: -1.0,
+            flags: 0,
+            teleport_id: Varint(128),
+        };
+
+        let mut expected = vec![0x40, 0x59, 0, 0, 0, 0, 0, 0];
+        expected.extend(vec![0x40, 0x50, 0x40, 0, 0, 0, 0, 0]);
+        expected.extend(vec![0xC0, 0x24, 0, 0, 0, 0, 0, 0]);
+        expected.extend(vec![0x41, 0x48, 0, 0, 0xBF, 0x80, 0, 0]);
+        expected.extend(vec![0x00, 0x80, 0x01]);
+
+        assert_eq!(expected, player_position_and_look.encode());
+    }
+
+    #[test]
+    fn test_encode_spawn_position() {
+        let packet = ClientboundPlayPacket::SpawnPosition(SpawnPosition {
+            location: Location { x: 1, y: 2, z: 3 },
+        });
+
+        assert_eq!(Varint(0x49), packet.packet_id());
+        assert_eq!(vec![0, 0, 0, 0x40, 0x08, 0, 0, 0x03], packet.encode());
+    }
+}
@@ -0,0 +1,121 @@
+use super::{ClientboundPacket, Packet};
+use crate::coding::buffer::{BufMut, ReadCursor};
+use crate::coding::long::Long;
+use crate::coding::string::MinecraftString;
+use crate::coding::varint::Varint;
+use crate::coding::{Decodeable, Encodeable};
+use crate::connection::ConnectionState;
+use std::io;
+
+/// Packets the client may send while the connection is in the `Status` state.
+#[derive(Debug)]
+pub enum ServerboundStatusPacket {
+    /// 0x00
+    Request,
+    /// 0x01
+    Ping(Ping),
+}
+
+impl Decodeable<ServerboundStatusPacket, io::Error> for Packet {
+    fn decode(&mut self) -> Result<ServerboundStatusPacket, io::Error> {
+        let packet_id = self.packet_id.0;
+        let mut data = ReadCursor::new(&self.data);
+
+        match packet_id {
+            0x00 => Ok(ServerboundStatusPacket::Request),
+            0x01 => Ok(ServerboundStatusPacket::Ping(data.decode()?)),
+            _ => Err(super::unexpected_packet(ConnectionState::Status, packet_id)),
+        }
+    }
+}
+
+/// Packets the server may send while the connection is in the `Status` state.
+#[derive(Debug)]
+pub enum ClientboundStatusPacket {
+    /// 0x00
+    Response(Response),
+    /// 0x01
+    Pong(Pong),
+}
+
+impl ClientboundPacket for ClientboundStatusPacket {
+    fn packet_id(&self) -> Varint {
+        match self {
+            ClientboundStatusPacket::Response(_) => Varint(0x00),
+            ClientboundStatusPacket::Pong(_) => Varint(0x01),
+        }
+    }
+}
+
+impl Encodeable for ClientboundStatusPacket {
+    fn encode_into<B: BufMut>(&self, buffer: &mut B) {
+        match self {
+            ClientboundStatusPacket::Response(packet) => packet.encode_into(buffer),
+            ClientboundStatusPacket::Pong(packet) => packet.encode_into(buffer),
+        }
+    }
+
+    fn byte_length(&self) -> usize {
+        match self {
+            ClientboundStatusPacket::Response(packet) => packet.byte_length(),
+            ClientboundStatusPacket::Pong(packet) => packet.byte_length(),
+        }
+    }
+}
+
+/// C->S Ping
+#[derive(Debug, PartialEq, Decodeable)]
+pub struct Ping {
+    pub payload: Long,
+}
+
+/// S->C Response
+#[derive(Debug, PartialEq, Encodeable)]
+pub struct Response {
+    /// the server list ping response, serialized as json
+    pub json_response: MinecraftString,
+}
+
+/// S->C Pong
+#[derive(Debug, PartialEq, Encodeable)]
+pub struct Pong {
+    /// the payload of the ping sent by the client
+    pub payload: Long,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClientboundStatusPacket, Ping, Pong, ServerboundStatusPacket};
+    use crate::coding::varint::Varint;
+    use crate::coding::{Decodeable, Encodeable};
+    use crate::packet::{ClientboundPacket, Packet};
+
+    #[test]
+    fn test_decode_request() {
+        let mut packet = Packet::from_id_and_data(Varint(0x00), vec![]);
+
+        match packet.decode().unwrap() {
+            ServerboundStatusPacket::Request => {}
+            packet => panic!("Decoded wrong packet {:?}", packet),
+        }
+    }
+
+    #[test]
+    fn test_decode_ping() {
+        let mut packet =
+            Packet::from_id_and_data(Varint(0x01), vec![0, 0, 0, 0, 0, 0, 0x30, 0x39]);
+
+        match packet.decode().unwrap() {
+            ServerboundStatusPacket::Ping(ping) => assert_eq!(Ping { payload: 12345 }, ping),
+            packet => panic!("Decoded wrong packet {:?}", packet),
+        }
+    }
+
+    #[test]
+    fn test_encode_pong() {
+        let packet = ClientboundStatusPacket::Pong(Pong { payload: 12345 });
+
+        assert_eq!(Varint(0x01), packet.packet_id());
+        assert_eq!(vec![0, 0, 0, 0, 0, 0, 0x30, 0x39], packet.encode());
+    }
+}
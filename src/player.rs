@@ -1,35 +1,57 @@
+use crate::chunk::Chunk;
 use crate::client_settings::ClientSettings;
-use crate::client_status::ClientStatus;
+use crate::coding::boolean::MinecraftBoolean;
+use crate::coding::double::MinecraftDouble;
 use crate::coding::float::MinecraftFloat;
+use crate::coding::long::Long;
 use crate::coding::signed_byte::MinecraftSignedByte;
+use crate::coding::uuid::{HyphenatedUuid, Uuid};
 use crate::coding::varint::Varint;
-use crate::coding::Decodeable;
 use crate::connection::{Connection, ConnectionState};
 use crate::entity::{get_new_eid, get_new_teleport_id};
+use crate::keep_alive::{KeepAlive, KEEP_ALIVE_TIMEOUT};
 use crate::location::Location;
-use crate::packet::{Packet, PacketData};
+use crate::packet::login::{ClientboundLoginPacket, LoginSuccess};
+use crate::packet::play::{
+    ChunkData, ClientboundKeepAlive, ClientboundPlayPacket, ClientboundPlayerPositionAndLook,
+    JoinGame, KeepAlive as KeepAlivePacket, PlayerAbilities, PlayerLook, PlayerOnGround,
+    PlayerPosition, PlayerPositionAndLook, ServerboundPlayPacket, SpawnPosition, TeleportConfirm,
+    UnloadChunk,
+};
+use crate::packet::{ClientboundPacket, ServerboundPacket};
 use crate::plugin_message::{PluginMessage, PluginMessageOrigin};
 use crate::world::World;
-use serde::ser::SerializeStruct;
-use serde::{Serialize, Serializer};
-use std::collections::VecDeque;
+use std::collections::HashSet;
 use std::f64;
 use std::io;
+use std::time::{Duration, Instant};
 
 pub struct Player {
     connection: Connection,
     username: String,
-    // TODO: Create own struct for this
-    uuid: String,
+    uuid: Uuid,
     client_settings: Option<ClientSettings>,
     entitity_id: usize,
     spawn_location: Location,
     current_location: Location,
-    current_world: World,
+    yaw: MinecraftFloat,
+    pitch: MinecraftFloat,
+    /// whether the client last reported the player to be on the ground
+    on_ground: MinecraftBoolean,
+    /// the teleport the client still needs to confirm
+    pending_teleport_id: Option<Varint>,
+    keep_alive: KeepAlive,
+    /// the round trip time of the last answered Keep Alive
+    latency: Option<Duration>,
+    /// the chunk coordinates of the chunks the client has loaded
+    sent_chunks: HashSet<(i32, i32)>,
 }
 
+/// How long to wait for a packet before checking whether a Keep Alive is due.
+const PACKET_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 impl Player {
-    pub fn from_basic_data(connection: Connection, username: String, uuid: String) -> Player {
+    pub fn from_basic_data(connection: Connection, username: String, uuid: Uuid) -> Player {
         Player {
             connection,
             username,
@@ -38,7 +60,13 @@ impl Player {
             entitity_id: get_new_eid(),
             spawn_location: Location::default(),
             current_location: Location::default(),
-            current_world: World::default(),
+            yaw: 0.0,
+            pitch: 0.0,
+            on_ground: false,
+            pending_teleport_id: None,
+            keep_alive: KeepAlive::new(Instant::now()),
+            latency: None,
+            sent_chunks: HashSet::new(),
         }
     }
 
@@ -46,10 +74,20 @@ impl Player {
         &self.spawn_location
     }
 
+    /// Returns the round trip time measured with the last answered Keep Alive.
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency
+    }
+
+    /// The world of the server, which is shared with all other players.
+    fn world(&self) -> &World {
+        &self.connection.server.world
+    }
+
     /// Helper function for sending a package to this player.
     #[inline(always)]
-    fn send_packet(&mut self, packet: &mut Packet) -> io::Result<()> {
-        packet.send(&mut self.connection.tcp_stream)
+    fn send_packet<P: ClientboundPacket>(&mut self, packet: P) -> io::Result<()> {
+        self.connection.send_packet(packet)
     }
 
     /// S->C Login Success Packet
@@ -64,12 +102,10 @@ impl Player {
             self.connection.connection_id
         );
 
-        let mut login_success_packet = Packet::from_id_and_data(
-            Varint(0x02),
-            PacketData::Data(super::build_package_data!(self.uuid, self.username)),
-        );
-
-        self.send_packet(&mut login_success_packet)?;
+        self.send_packet(ClientboundLoginPacket::LoginSuccess(LoginSuccess {
+            uuid: HyphenatedUuid(self.uuid),
+            username: self.username.clone(),
+        }))?;
 
         // set the connection state to `Play` as we sent this package.
         self.connection.state = ConnectionState::Play;
@@ -81,26 +117,27 @@ impl Player {
     ///
     /// Informs the client about the general overview on the world they will be joining.
     pub fn send_join_game(&mut self) -> io::Result<()> {
-        let mut join_game_packet = Packet::from_id_and_data(
-            Varint(0x25),
-            PacketData::Data(super::build_package_data!(
-                self.entitity_id as i32,
-                self.current_world.gamemode,
-                self.current_world.dimension,
-                self.current_world.difficulty,
-                20_u8, // max players TODO: Make this global
-                self.current_world.level_type,
-                false // reduced debug info?
-            )),
-        );
+        let join_game = {
+            let world = self.world();
+
+            JoinGame {
+                entity_id: self.entitity_id as i32,
+                gamemode: world.gamemode,
+                dimension: world.dimension,
+                difficulty: world.difficulty,
+                max_players: self.connection.server.config.max_players.min(255) as u8,
+                level_type: world.level_type,
+                reduced_debug_info: false,
+            }
+        };
 
-        self.send_packet(&mut join_game_packet)
+        self.send_packet(ClientboundPlayPacket::JoinGame(join_game))
     }
 
     pub fn broadcast_server_name(&mut self) -> io::Result<()> {
         let plugin_message = PluginMessage::new(
             "minecraft:brand".to_owned(),
-            VecDeque::from(b"oxicraft".to_vec()),
+            b"oxicraft".to_vec(),
             PluginMessageOrigin::Server,
         );
 
@@ -108,12 +145,7 @@ impl Player {
     }
 
     pub fn send_plugin_message(&mut self, plugin_message: PluginMessage) -> io::Result<()> {
-        let mut packet = Packet::from_id_and_data(
-            Varint(0x19),
-            PacketData::Data(super::build_package_data!(plugin_message)),
-        );
-
-        self.send_packet(&mut packet)
+        self.send_packet(ClientboundPlayPacket::PluginMessage(plugin_message))
     }
 
     /// S->C Spawn Location
@@ -123,12 +155,9 @@ impl Player {
         self.spawn_location = spawn_location;
 
         // Send spawn location to client
-        let mut packet = Packet::from_id_and_data(
-            Varint(0x49),
-            PacketData::Data(super::build_package_data!(self.spawn_location)),
-        );
-
-        self.send_packet(&mut packet)
+        self.send_packet(ClientboundPlayPacket::SpawnPosition(SpawnPosition {
+            location: spawn_location,
+        }))
     }
 
     /// S->C Player Abilities
@@ -138,62 +167,80 @@ impl Player {
         flying_speed: MinecraftFloat,
         walking_speed: MinecraftFloat,
     ) -> io::Result<()> {
-        let mut packet = Packet::from_id_and_data(
-            Varint(0x2E),
-            PacketData::Data(super::build_package_data!(
-                flags,
-                flying_speed,
-                walking_speed
-            )),
-        );
-
         trace!(
             "Sending player abilities to connection {}…",
             self.connection.connection_id
         );
 
-        self.send_packet(&mut packet)
+        self.send_packet(ClientboundPlayPacket::PlayerAbilities(PlayerAbilities {
+            flags,
+            flying_speed,
+            field_of_view_modifier: walking_speed,
+        }))
     }
 
-    /// C->S Client settings
-    pub fn read_client_settings(&mut self) -> io::Result<()> {
-        let client_settings_packet = self.connection.read_data_packet()?;
-
-        // FIXME: Accept this, even when not send in ("right") order
-        assert_eq!(client_settings_packet.packet_id, 0x04);
+    /// S->C Chunk Data
+    pub fn send_chunk(&mut self, chunk: &Chunk) -> io::Result<()> {
+        let dimension = self.world().dimension;
 
-        if let PacketData::Data(mut packet_data) = client_settings_packet.data {
-            let client_settings: ClientSettings = packet_data.decode()?;
-
-            info!("Received client settings.");
+        self.send_packet(ClientboundPlayPacket::ChunkData(ChunkData::full_chunk(
+            chunk, dimension,
+        )))
+    }
 
-            debug!("Client settings: {:?}", client_settings);
+    /// S->C Unload Chunk
+    pub fn unload_chunk(&mut self, chunk_x: i32, chunk_z: i32) -> io::Result<()> {
+        self.send_packet(ClientboundPlayPacket::UnloadChunk(UnloadChunk {
+            chunk_x,
+            chunk_z,
+        }))
+    }
 
-            self.client_settings = Some(client_settings);
-            Ok(())
-        } else {
-            Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Client settings packet does not contain any data?!",
-            ))
+    /// Sends the chunks within the view distance around the location that the client does
+    /// not have yet, the closest first, and unloads those that are out of view now.
+    pub fn send_chunks_around(&mut self, location: &Location) -> io::Result<()> {
+        let view_distance = i32::from(self.connection.server.config.view_distance);
+        let (center_x, center_z) = Chunk::coordinates_of(location);
+        let in_view = |&(x, z): &(i32, i32)| {
+            (x - center_x).abs() <= view_distance && (z - center_z).abs() <= view_distance
+        };
+
+        let out_of_view: Vec<(i32, i32)> = self
+            .sent_chunks
+            .iter()
+            .filter(|chunk| !in_view(chunk))
+            .cloned()
+            .collect();
+
+        for (x, z) in out_of_view {
+            self.unload_chunk(x, z)?;
+            self.sent_chunks.remove(&(x, z));
         }
-    }
 
-    pub fn receive_plugin_message(&mut self) -> io::Result<PluginMessage> {
-        let plugin_message_packet = self.connection.read_data_packet()?;
+        let mut offsets: Vec<(i32, i32)> = (-view_distance..=view_distance)
+            .flat_map(|x| (-view_distance..=view_distance).map(move |z| (x, z)))
+            .collect();
+        offsets.sort_by_key(|(x, z)| x * x + z * z);
 
-        assert_eq!(plugin_message_packet.packet_id, 0x0A);
+        for (x, z) in offsets {
+            let (x, z) = (center_x + x, center_z + z);
 
-        if let PacketData::Data(mut packet_data) = plugin_message_packet.data {
-            let plugin_message: PluginMessage = packet_data.decode()?;
+            if self.sent_chunks.contains(&(x, z)) {
+                continue;
+            }
 
-            Ok(plugin_message)
-        } else {
-            Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Client settings packet does not contain any data?!",
-            ))
+            let chunk = self.world().load_chunk(x, z);
+            self.send_chunk(&chunk)?;
+            self.sent_chunks.insert((x, z));
         }
+
+        trace!(
+            "Sent the chunks around {:?} to connection {}.",
+            location,
+            self.connection.connection_id
+        );
+
+        Ok(())
     }
 
     pub fn set_location(
@@ -205,81 +252,223 @@ impl Player {
     ) -> io::Result<Varint> {
         let teleport_id = get_new_teleport_id();
 
-        let mut packet = Packet::from_id_and_data(
-            Varint(0x32),
-            PacketData::Data(super::build_package_data!(
+        self.send_packet(ClientboundPlayPacket::PlayerPositionAndLook(
+            ClientboundPlayerPositionAndLook {
                 // need to be converted to MinecraftDouble.
-                f64::from(new_location.x),
-                f64::from(new_location.y),
-                f64::from(new_location.z),
+                x: f64::from(new_location.x),
+                y: f64::from(new_location.y),
+                z: f64::from(new_location.z),
                 yaw,
                 pitch,
                 flags,
-                teleport_id
-            )),
-        );
-
-        self.send_packet(&mut packet)?;
+                teleport_id,
+            },
+        ))?;
 
         Ok(teleport_id)
     }
 
-    pub fn expect_teleport_confirm(&mut self, teleport_id: Varint) -> io::Result<()> {
-        let teleport_confirm_package = self.connection.read_data_packet()?;
+    /// Spawns the player that just logged in and handles their packets until the connection is closed.
+    ///
+    /// The player is disconnected with the error as reason if anything goes wrong.
+    pub fn play(&mut self) -> io::Result<()> {
+        match self.spawn_and_handle_packets() {
+            Ok(()) => Ok(()),
+            Err(error) => Err(self.connection.abort(error)),
+        }
+    }
+
+    fn spawn_and_handle_packets(&mut self) -> io::Result<()> {
+        self.send_login_success()?;
+        self.send_join_game()?;
+        self.broadcast_server_name()?;
+        let spawn_location = self.world().spawn_location();
+        self.set_spawn_location(spawn_location)?;
+        // TODO: Find better fitting values
+        self.set_player_abilities(0b1101 /* flying and creative */, 0.05, 0.1)?;
+
+        // the terrain needs to be there before the player is, so they do not fall
+        self.send_chunks_around(&spawn_location)?;
+
+        // Tell client they're ready to spawn.
+        let teleport_id = self.set_location(&spawn_location, 0.0, 0.0, 0b0)?;
+        self.pending_teleport_id = Some(teleport_id);
+        self.current_location = spawn_location;
+
+        // a client that stops sending in the middle of a packet is treated like a dead one
+        self.connection
+            .stream
+            .set_read_timeout(Some(KEEP_ALIVE_TIMEOUT))?;
+
+        loop {
+            if let Some(keep_alive_id) = self.keep_alive.poll(Instant::now())? {
+                self.send_keep_alive(keep_alive_id)?;
+            }
+
+            if !self.connection.stream.wait_for_data(PACKET_POLL_INTERVAL)? {
+                continue;
+            }
 
-        if teleport_confirm_package.packet_id != 0x00 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Client didn't send an teleport confirm package.",
-            ));
+            match self.connection.read_packet()? {
+                ServerboundPacket::Play(packet) => self.handle_packet(packet)?,
+                packet => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Received {:?} while playing.", packet),
+                    ));
+                }
+            }
         }
+    }
+
+    /// Routes a packet that was sent by the client while playing to its handler.
+    pub fn handle_packet(&mut self, packet: ServerboundPlayPacket) -> io::Result<()> {
+        match packet {
+            ServerboundPlayPacket::TeleportConfirm(TeleportConfirm { teleport_id }) => {
+                self.confirm_teleport(teleport_id);
+            }
+            ServerboundPlayPacket::ClientStatus(client_status) => {
+                debug!("Client status: {:?}", client_status);
+            }
+            ServerboundPlayPacket::ClientSettings(mut client_settings) => {
+                client_settings.locale = client_settings.locale.to_lowercase();
 
-        if let PacketData::Data(mut packet_data) = teleport_confirm_package.data {
-            let given_teleport_id: Varint = packet_data.decode()?;
+                info!("Received client settings.");
+                debug!("Client settings: {:?}", client_settings);
 
-            if teleport_id == given_teleport_id {
-                debug!("Teleport {} was confirmed by client!", teleport_id);
-                return Ok(());
+                self.client_settings = Some(client_settings);
+            }
+            ServerboundPlayPacket::PluginMessage(plugin_message) => {
+                self.handle_plugin_message(plugin_message)?;
+            }
+            ServerboundPlayPacket::KeepAlive(KeepAlivePacket { keep_alive_id }) => {
+                self.latency = Some(self.keep_alive.answer(keep_alive_id, Instant::now())?);
+
+                trace!(
+                    "Latency of {}: {:?}",
+                    self.connection.connection_id,
+                    self.latency()
+                );
+            }
+            ServerboundPlayPacket::PlayerOnGround(PlayerOnGround { on_ground }) => {
+                self.set_on_ground(on_ground);
+            }
+            ServerboundPlayPacket::PlayerPosition(PlayerPosition {
+                x,
+                feet_y,
+                z,
+                on_ground,
+            }) => {
+                self.move_to(x, feet_y, z)?;
+                self.set_on_ground(on_ground);
+            }
+            ServerboundPlayPacket::PlayerPositionAndLook(PlayerPositionAndLook {
+                x,
+                feet_y,
+                z,
+                yaw,
+                pitch,
+                on_ground,
+            }) => {
+                self.move_to(x, feet_y, z)?;
+                self.yaw = yaw;
+                self.pitch = pitch;
+                self.set_on_ground(on_ground);
+            }
+            ServerboundPlayPacket::PlayerLook(PlayerLook {
+                yaw,
+                pitch,
+                on_ground,
+            }) => {
+                self.yaw = yaw;
+                self.pitch = pitch;
+                self.set_on_ground(on_ground);
+            }
+            ServerboundPlayPacket::Unknown { packet_id } => {
+                trace!(
+                    "Ignoring unknown packet {:#X} of {}.",
+                    packet_id.0,
+                    self.connection.connection_id
+                );
             }
         }
 
-        Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "Teleport-ID by client didn't matched.",
-        ))
+        Ok(())
     }
 
-    pub fn receive_client_status(&mut self) -> io::Result<ClientStatus> {
-        assert_eq!(self.connection.state, ConnectionState::Play);
+    /// S->C Keep Alive
+    fn send_keep_alive(&mut self, keep_alive_id: Long) -> io::Result<()> {
+        self.send_packet(ClientboundPlayPacket::KeepAlive(ClientboundKeepAlive {
+            keep_alive_id,
+        }))
+    }
 
-        let client_status_packet = self.connection.read_data_packet()?;
+    fn handle_plugin_message(&mut self, plugin_message: PluginMessage) -> io::Result<()> {
+        debug!("Received plugin message: {:?}", plugin_message);
 
-        if client_status_packet.packet_id != 0x03 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Client didn't send a client status package.",
-            ));
+        if plugin_message.channel() == "minecraft:brand" {
+            info!("Client is called \"{}\".", plugin_message.data_stringify()?);
+        } else {
+            warn!(
+                "Unknown plugin message channel {}!",
+                plugin_message.channel()
+            );
         }
 
-        if let PacketData::Data(mut packet_data) = client_status_packet.data {
-            let client_status: ClientStatus = packet_data.decode()?;
+        Ok(())
+    }
 
-            Ok(client_status)
+    /// C->S Teleport Confirm
+    fn confirm_teleport(&mut self, teleport_id: Varint) {
+        if self.pending_teleport_id == Some(teleport_id) {
+            debug!("Teleport {} was confirmed by client!", teleport_id);
+            self.pending_teleport_id = None;
         } else {
-            unreachable!();
+            warn!(
+                "Client {} confirmed unknown teleport {}.",
+                self.connection.connection_id, teleport_id
+            );
         }
     }
-}
 
-/// Implemented to match https://wiki.vg/Server_List_Ping#Response (sample values)
-impl Serialize for Player {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let mut state = serializer.serialize_struct("Player", 2)?;
-        state.serialize_field("name", &self.username)?;
-        state.serialize_field("id", &self.uuid)?;
-        state.end()
+    /// Moves the player, sending the chunks that came into view when they enter another chunk.
+    fn move_to(
+        &mut self,
+        x: MinecraftDouble,
+        feet_y: MinecraftDouble,
+        z: MinecraftDouble,
+    ) -> io::Result<()> {
+        // movements are ignored until the client confirmed the last teleport
+        if self.pending_teleport_id.is_some() {
+            return Ok(());
+        }
+
+        let location = Location {
+            x: x.floor() as i32,
+            y: feet_y.floor() as i16,
+            z: z.floor() as i32,
+        };
+        let entered_chunk =
+            Chunk::coordinates_of(&location) != Chunk::coordinates_of(&self.current_location);
+
+        self.current_location = location;
+
+        if entered_chunk {
+            self.send_chunks_around(&location)?;
+        }
+
+        Ok(())
+    }
+
+    fn set_on_ground(&mut self, on_ground: MinecraftBoolean) {
+        if on_ground != self.on_ground {
+            trace!(
+                "{} is {} the ground.",
+                self.connection.connection_id,
+                if on_ground { "on" } else { "off" }
+            );
+        }
+
+        self.on_ground = on_ground;
     }
 }
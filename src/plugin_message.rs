@@ -1,5 +1,5 @@
+use crate::coding::buffer::ReadCursor;
 use crate::coding::string::{MinecraftString, ReadString};
-use std::collections::VecDeque;
 use std::io;
 
 #[derive(Debug)]
@@ -9,14 +9,14 @@ pub struct PluginMessage {
     /// It is guarenteed to start with a namespace(so `thing` is saved as `minecraft:thing`).
     channel: MinecraftString,
     /// Holding the raw data
-    data: VecDeque<u8>,
+    data: Vec<u8>,
     origin: PluginMessageOrigin,
 }
 
 impl PluginMessage {
     pub fn new(
         channel: MinecraftString,
-        data: VecDeque<u8>,
+        data: Vec<u8>,
         origin: PluginMessageOrigin,
     ) -> PluginMessage {
         let mut channel = channel;
@@ -37,12 +37,12 @@ impl PluginMessage {
     }
 
     #[inline(always)]
-    pub fn data(&self) -> &VecDeque<u8> {
+    pub fn data(&self) -> &Vec<u8> {
         &self.data
     }
 
-    pub fn data_stringify(&mut self) -> io::Result<MinecraftString> {
-        self.data.read_string(32767)
+    pub fn data_stringify(&self) -> io::Result<MinecraftString> {
+        Ok(ReadCursor::new(&self.data).read_string(32767)?)
     }
 
     #[inline(always)]
@@ -55,15 +55,13 @@ impl PluginMessage {
         self.channel.starts_with("minecraft:")
     }
 
+    /// Returns the namespace of the channel, which is `minecraft` if none is given.
     #[inline(always)]
     pub fn get_channel_namespace(&self) -> &str {
-        self.channel
-            .split_at(
-                self.channel
-                    .find(':')
-                    .expect("Channel does not contain namespace!"),
-            )
-            .0
+        match self.channel.find(':') {
+            Some(index) => &self.channel[..index],
+            None => "minecraft",
+        }
     }
 }
 
@@ -0,0 +1,186 @@
+use crate::coding::uuid::Uuid;
+use crate::config::ServerConfig;
+use crate::connection::ConnectionId;
+use crate::encryption::KeyPair;
+use crate::session::{MojangSessionVerifier, SessionVerifier};
+use crate::world::World;
+use std::collections::HashMap;
+use std::io;
+use std::sync::RwLock;
+
+/// State that is shared between all connections handled by this server.
+pub struct Server {
+    pub config: ServerConfig,
+    /// the key pair used to encrypt connections, only generated in online mode
+    key_pair: Option<KeyPair>,
+    /// authenticates players in online mode
+    pub session_verifier: Box<dyn SessionVerifier>,
+    /// the icon shown in the server list as data URI
+    pub favicon: Option<String>,
+    /// the world all players are in
+    pub world: World,
+    online_players: RwLock<HashMap<ConnectionId, OnlinePlayer>>,
+}
+
+/// Publicly visible information about a player that is currently logged in.
+///
+/// Serializes to an entry of the player sample in the server list.
+#[derive(Clone, Debug, Serialize)]
+pub struct OnlinePlayer {
+    #[serde(rename = "name")]
+    pub username: String,
+    #[serde(rename = "id")]
+    pub uuid: Uuid,
+}
+
+/// Removes the player of a connection from the server when dropped, so they are removed
+/// even if the thread handling them panics.
+pub struct PlayerRegistration<'a> {
+    server: &'a Server,
+    connection_id: ConnectionId,
+}
+
+impl Drop for PlayerRegistration<'_> {
+    fn drop(&mut self) {
+        self.server.remove_player(self.connection_id);
+    }
+}
+
+impl Server {
+    pub fn new(config: ServerConfig) -> io::Result<Server> {
+        let key_pair = if config.online_mode {
+            Some(KeyPair::generate()?)
+        } else {
+            None
+        };
+
+        let world = World::from_config(&config);
+
+        Ok(Server {
+            config,
+            key_pair,
+            session_verifier: Box::new(MojangSessionVerifier),
+            favicon: None,
+            world,
+            online_players: RwLock::new(HashMap::new()),
+        })
+    }
+
+    pub fn key_pair(&self) -> io::Result<&KeyPair> {
+        self.key_pair.as_ref().ok_or_else(|| {
+            io::Error::other(
+                "The server has no key pair to encrypt connections, is online mode enabled?",
+            )
+        })
+    }
+
+    /// Registers a player that successfully logged in on the given connection.
+    pub fn add_player(&self, connection_id: ConnectionId, player: OnlinePlayer) {
+        self.online_players
+            .write()
+            .unwrap()
+            .insert(connection_id, player);
+    }
+
+    /// Registers a player like `add_player`, returning a guard that removes them again once
+    /// it is dropped.
+    pub fn register_player(
+        &self,
+        connection_id: ConnectionId,
+        player: OnlinePlayer,
+    ) -> PlayerRegistration<'_> {
+        self.add_player(connection_id, player);
+
+        PlayerRegistration {
+            server: self,
+            connection_id,
+        }
+    }
+
+    /// Removes the player of the given connection, returning it if there was one.
+    pub fn remove_player(&self, connection_id: ConnectionId) -> Option<OnlinePlayer> {
+        self.online_players.write().unwrap().remove(&connection_id)
+    }
+
+    /// Returns a snapshot of all players that are currently online.
+    pub fn online_players(&self) -> Vec<OnlinePlayer> {
+        self.online_players
+            .read()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OnlinePlayer, Server};
+    use crate::coding::uuid::Uuid;
+    use crate::config::ServerConfig;
+    use crate::connection::ConnectionId;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_players_are_shared_between_threads() {
+        let config = ServerConfig {
+            online_mode: false,
+            ..Default::default()
+        };
+        let server = Arc::new(Server::new(config).unwrap());
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let server = Arc::clone(&server);
+
+                thread::spawn(move || {
+                    server.add_player(
+                        ConnectionId(i),
+                        OnlinePlayer {
+                            username: format!("player{}", i),
+                            uuid: Uuid::offline_player(&format!("player{}", i)),
+                        },
+                    );
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(4, server.online_players().len());
+
+        assert!(server.remove_player(ConnectionId(2)).is_some());
+        assert!(server.remove_player(ConnectionId(2)).is_none());
+        assert_eq!(3, server.online_players().len());
+    }
+
+    #[test]
+    fn test_players_are_removed_when_their_thread_panics() {
+        let config = ServerConfig {
+            online_mode: false,
+            ..Default::default()
+        };
+        let server = Arc::new(Server::new(config).unwrap());
+        let player_server = Arc::clone(&server);
+
+        let result = thread::spawn(move || {
+            let _registration = player_server.register_player(
+                ConnectionId(1),
+                OnlinePlayer {
+                    username: "player".to_owned(),
+                    uuid: Uuid::offline_player("player"),
+                },
+            );
+
+            assert_eq!(1, player_server.online_players().len());
+            panic!("the player crashed");
+        })
+        .join();
+
+        assert!(result.is_err());
+        assert_eq!(0, server.online_players().len());
+    }
+}
@@ -0,0 +1,101 @@
+use crate::coding::uuid::Uuid;
+use std::io;
+
+/// The endpoint of Mojang's session server which tells whether a client joined a server.
+const HAS_JOINED_URL: &str = "https://sessionserver.mojang.com/session/minecraft/hasJoined";
+
+/// The profile of an authenticated player, as returned by the session server.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct GameProfile {
+    /// the uuid of the player, without hyphens
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub properties: Vec<ProfileProperty>,
+}
+
+/// A property of a profile, e.g. the skin of the player.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ProfileProperty {
+    pub name: String,
+    pub value: String,
+    pub signature: Option<String>,
+}
+
+impl GameProfile {
+    pub fn uuid(&self) -> io::Result<Uuid> {
+        self.id.parse()
+    }
+}
+
+/// Verifies that a client logging in in online mode owns the account it claims to.
+pub trait SessionVerifier: Send + Sync {
+    /// Asks whether `username` announced joining the server identified by `server_hash`.
+    /// Returns the profile of the player if so.
+    fn has_joined(&self, username: &str, server_hash: &str) -> io::Result<Option<GameProfile>>;
+}
+
+/// Asks Mojang's session server, like vanilla servers do.
+pub struct MojangSessionVerifier;
+
+impl SessionVerifier for MojangSessionVerifier {
+    fn has_joined(&self, username: &str, server_hash: &str) -> io::Result<Option<GameProfile>> {
+        let response = ureq::get(HAS_JOINED_URL)
+            .query("username", username)
+            .query("serverId", server_hash)
+            .call()
+            .map_err(io::Error::other)?;
+
+        // the session server answers with "204 No Content" if the client did not join
+        if response.status() == 204 {
+            return Ok(None);
+        }
+
+        let body = response.into_string()?;
+
+        Ok(Some(serde_json::from_str(&body)?))
+    }
+}
+
+/// Accepts a fixed set of profiles without asking anyone, to test the login offline.
+#[cfg(test)]
+pub struct MockSessionVerifier {
+    pub profiles: Vec<GameProfile>,
+}
+
+#[cfg(test)]
+impl SessionVerifier for MockSessionVerifier {
+    fn has_joined(&self, username: &str, _server_hash: &str) -> io::Result<Option<GameProfile>> {
+        Ok(self
+            .profiles
+            .iter()
+            .find(|profile| profile.name == username)
+            .cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GameProfile;
+    use crate::coding::uuid::Uuid;
+
+    #[test]
+    fn test_deserialize_game_profile() {
+        let json = r#"{
+            "id": "8e383e9f608e455697c961312c741ea0",
+            "name": "jens1o",
+            "properties": [
+                {"name": "textures", "value": "e30=", "signature": "c2lnbmF0dXJl"}
+            ]
+        }"#;
+
+        let profile: GameProfile = serde_json::from_str(json).unwrap();
+
+        assert_eq!("jens1o", profile.name);
+        assert_eq!(1, profile.properties.len());
+        assert_eq!(
+            Uuid(0x8e38_3e9f_608e_4556_97c9_6131_2c74_1ea0),
+            profile.uuid().unwrap()
+        );
+    }
+}
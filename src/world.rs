@@ -1,21 +1,135 @@
+mod anvil;
+pub mod generator;
+
+use self::anvil::AnvilLoader;
+use self::generator::WorldGenerator;
+use crate::chunk::Chunk;
 use crate::coding::{gamemode::Gamemode, level_type::LevelType};
+use crate::config::ServerConfig;
 use crate::difficulty::Difficulty;
 use crate::dimension::Dimension;
+use crate::location::Location;
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
+/// The world shared by all players.
+///
+/// Only reading from the region files is locked, chunks are generated by the players
+/// needing them at the same time.
 pub struct World {
     pub gamemode: Gamemode,
     pub dimension: Dimension,
     pub difficulty: Difficulty,
     pub level_type: LevelType,
+    /// the vanilla world in the directory of the `level-name`, if there is one
+    anvil: Option<Mutex<AnvilLoader>>,
+    /// the chunks of the vanilla world that could not be loaded, which are sent empty
+    unreadable_chunks: Mutex<HashSet<(i32, i32)>>,
+    generator: Box<dyn WorldGenerator>,
 }
 
-impl Default for World {
-    fn default() -> World {
+impl World {
+    pub fn from_config(config: &ServerConfig) -> World {
         World {
-            gamemode: Gamemode::Creative,
+            gamemode: config.gamemode,
             dimension: Dimension::Overworld,
-            difficulty: Difficulty::Peaceful,
-            level_type: LevelType::Flat,
+            difficulty: config.difficulty,
+            level_type: config.level_type,
+            anvil: AnvilLoader::open(&config.level_name).map(Mutex::new),
+            unreadable_chunks: Mutex::new(HashSet::new()),
+            generator: generator::from_config(config),
+        }
+    }
+
+    /// Where players spawn, as stored in the `level.dat` of a vanilla world or otherwise
+    /// chosen by the generator.
+    pub fn spawn_location(&self) -> Location {
+        if let Some(anvil) = &self.anvil {
+            match lock(anvil).spawn_location() {
+                Ok(Some(location)) => return location,
+                Ok(None) => {}
+                Err(error) => warn!("Could not read the spawn of the world: {}", error),
+            }
+        }
+
+        self.generator.spawn_location()
+    }
+
+    /// Returns the chunk at the given chunk coordinates, which is loaded from the vanilla
+    /// world if it is stored there and generated otherwise.
+    ///
+    /// Chunks of the vanilla world that can not be loaded are empty instead of generated,
+    /// so the world is not changed by terrain that does not fit in.
+    pub fn load_chunk(&self, x: i32, z: i32) -> Chunk {
+        if let Some(anvil) = &self.anvil {
+            if lock(&self.unreadable_chunks).contains(&(x, z)) {
+                return Chunk::new(x, z);
+            }
+
+            let result = lock(anvil).load_chunk(x, z);
+
+            match result {
+                Ok(Some(chunk)) => return chunk,
+                Ok(None) => {}
+                Err(error) => {
+                    warn!(
+                        "Could not load chunk {}, {}, it stays empty: {}",
+                        x, z, error
+                    );
+                    lock(&self.unreadable_chunks).insert((x, z));
+
+                    return Chunk::new(x, z);
+                }
+            }
         }
+
+        self.generator.generate_chunk(x, z)
+    }
+}
+
+/// Locks the mutex even if a thread panicked while holding it, as the world is still
+/// usable then: at worst a region file is opened again.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::World;
+    use crate::chunk::Chunk;
+    use crate::config::ServerConfig;
+    use crate::world::generator;
+    use std::fs;
+
+    #[test]
+    fn test_unreadable_chunks_are_empty() {
+        let directory =
+            std::env::temp_dir().join(format!("oxicraft-test-{}-unreadable", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(directory.join("region")).unwrap();
+
+        // chunk 0, 0 is stored in the second sector, with an unknown compression
+        let mut region = vec![0; 3 * 4096];
+        region[2] = 2;
+        region[3] = 1;
+        region[8192..8197].copy_from_slice(&[0, 0, 0, 1, 3]);
+        fs::write(directory.join("region").join("r.0.0.mca"), region).unwrap();
+
+        let config = ServerConfig {
+            level_name: directory.to_str().unwrap().to_owned(),
+            ..Default::default()
+        };
+        let world = World::from_config(&config);
+
+        assert_eq!(Chunk::new(0, 0), world.load_chunk(0, 0));
+        assert!(world.unreadable_chunks.lock().unwrap().contains(&(0, 0)));
+        assert_eq!(Chunk::new(0, 0), world.load_chunk(0, 0));
+        // chunks that are not stored are generated
+        assert_eq!(
+            generator::from_config(&config).generate_chunk(1, 0),
+            world.load_chunk(1, 0)
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
     }
 }
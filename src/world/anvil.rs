@@ -0,0 +1,741 @@
+//! Worlds saved by vanilla in the Anvil format, with the chunks stored in region files.
+//!
+//! See https://minecraft.gamepedia.com/Region_file_format and
+//! https://minecraft.gamepedia.com/Chunk_format for the format of 1.13.
+use crate::block::BlockRegistry;
+use crate::chunk::{
+    BlockState, Chunk, ChunkSection, NibbleArray, PackedArray, BLOCKS_PER_SECTION,
+    SECTIONS_PER_CHUNK, SECTION_WIDTH,
+};
+use crate::coding::buffer::ReadBytes;
+use crate::coding::error::ProtocolError;
+use crate::coding::nbt::{de, Compound, Nbt, Tag};
+use crate::location::Location;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Region files are split into sectors of this many bytes, the first of which holds the
+/// locations of the chunks.
+const SECTOR_SIZE: u64 = 4096;
+/// Each region holds 32x32 chunks.
+const REGION_WIDTH: i32 = 32;
+const CHUNKS_PER_REGION: usize = (REGION_WIDTH * REGION_WIDTH) as usize;
+/// How many regions are kept open at most. The view distance of a player spans at most
+/// 2x2 regions, so this is enough for a few players far apart from each other.
+const MAX_OPEN_REGIONS: usize = 16;
+
+const COMPRESSION_GZIP: u8 = 1;
+const COMPRESSION_ZLIB: u8 = 2;
+
+/// Vanilla stores chunks that are still being generated as well, only these are complete.
+const COMPLETE_STATUSES: [&str; 2] = ["postprocessed", "fullchunk"];
+
+/// Palettes use at least 4 bits per block, like in the Chunk Data packet.
+const MIN_BITS_PER_BLOCK: u8 = 4;
+
+/// A region file, e.g. `region/r.0.-1.mca`, which stores the chunks of a 32x32 area.
+pub struct RegionFile<R> {
+    reader: R,
+    /// the first sector and the number of sectors of each chunk, 0 for missing chunks
+    locations: Vec<(u32, u8)>,
+}
+
+impl<R: Read + Seek> RegionFile<R> {
+    /// Reads the locations of the chunks from the header of the region.
+    pub fn new(mut reader: R) -> Result<RegionFile<R>, ProtocolError> {
+        let mut locations = Vec::with_capacity(CHUNKS_PER_REGION);
+
+        for _ in 0..CHUNKS_PER_REGION {
+            let [first, second, third, sectors] = reader.read_fixed("region header")?;
+
+            locations.push((u32::from_be_bytes([0, first, second, third]), sectors));
+        }
+
+        Ok(RegionFile { reader, locations })
+    }
+
+    /// Reads the chunk at the given chunk coordinates, which are taken modulo 32, or
+    /// `None` if it was not generated yet.
+    pub fn read_chunk(&mut self, x: i32, z: i32) -> Result<Option<Nbt>, ProtocolError> {
+        let index =
+            (z.rem_euclid(REGION_WIDTH) * REGION_WIDTH + x.rem_euclid(REGION_WIDTH)) as usize;
+        let (sector, sectors) = self.locations[index];
+
+        if sector == 0 || sectors == 0 {
+            return Ok(None);
+        }
+
+        self.reader
+            .seek(SeekFrom::Start(u64::from(sector) * SECTOR_SIZE))
+            .map_err(|error| ProtocolError::from_io(error, "chunk"))?;
+
+        let length = u32::from_be_bytes(self.reader.read_fixed("chunk length")?) as usize;
+        let max_length = usize::from(sectors) * SECTOR_SIZE as usize - 4;
+
+        if length == 0 || length > max_length {
+            return Err(ProtocolError::TooLong {
+                type_name: "chunk",
+                length,
+                max_length,
+            });
+        }
+
+        let compression = self.reader.read_u8("chunk compression")?;
+        let data = (&mut self.reader).take(length as u64 - 1);
+
+        match compression {
+            COMPRESSION_GZIP => Nbt::from_gzip_reader(data).map(Some),
+            COMPRESSION_ZLIB => Nbt::from_zlib_reader(data).map(Some),
+            _ => Err(ProtocolError::invalid_value(
+                "chunk compression",
+                compression,
+            )),
+        }
+    }
+}
+
+/// The contents of `level.dat` that are used, vanilla stores many more.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Level {
+    data: LevelData,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LevelData {
+    spawn_x: i32,
+    spawn_y: i32,
+    spawn_z: i32,
+}
+
+/// A region file of a world on disk.
+type RegionOnDisk = RegionFile<BufReader<File>>;
+
+/// Loads the chunks of a vanilla world, opening its region files once they are needed.
+pub struct AnvilLoader {
+    directory: PathBuf,
+    /// the regions that were opened, or `None` if they do not exist, the least recently
+    /// used first
+    regions: Vec<((i32, i32), Option<RegionOnDisk>)>,
+}
+
+impl AnvilLoader {
+    /// Returns a loader for the world in the given directory, e.g. `world`, or `None` if
+    /// there is no world with regions.
+    pub fn open<P: AsRef<Path>>(directory: P) -> Option<AnvilLoader> {
+        let directory = directory.as_ref();
+
+        if !directory.join("region").is_dir() {
+            return None;
+        }
+
+        Some(AnvilLoader {
+            directory: directory.to_owned(),
+            regions: Vec::new(),
+        })
+    }
+
+    /// Reads the spawn point from the `level.dat` of the world, if it has one.
+    pub fn spawn_location(&self) -> Result<Option<Location>, ProtocolError> {
+        let file = match File::open(self.directory.join("level.dat")) {
+            Ok(file) => file,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(ProtocolError::Io(error)),
+        };
+
+        let nbt = Nbt::from_gzip_reader(BufReader::new(file))?;
+        let Level { data } = de::from_compound(nbt.root)?;
+
+        Ok(Some(Location {
+            x: data.spawn_x,
+            y: data.spawn_y as i16,
+            z: data.spawn_z,
+        }))
+    }
+
+    /// Loads the chunk at the given chunk coordinates, or returns `None` if vanilla did not
+    /// generate it completely yet.
+    pub fn load_chunk(&mut self, x: i32, z: i32) -> Result<Option<Chunk>, ProtocolError> {
+        let region = (x.div_euclid(REGION_WIDTH), z.div_euclid(REGION_WIDTH));
+
+        let nbt = match self.region(region)? {
+            Some(region) => region.read_chunk(x, z)?,
+            None => None,
+        };
+
+        let chunk = match nbt {
+            Some(nbt) => chunk_from_nbt(&nbt)?,
+            None => return Ok(None),
+        };
+
+        match chunk {
+            Some(ref chunk) if (chunk.x, chunk.z) != (x, z) => Err(ProtocolError::invalid_value(
+                "chunk position",
+                format!("{}, {} instead of {}, {}", chunk.x, chunk.z, x, z),
+            )),
+            chunk => Ok(chunk),
+        }
+    }
+
+    /// Returns the region at the given region coordinates, or `None` if it does not exist.
+    ///
+    /// The region is opened if it is not open yet, closing the least recently used one if
+    /// too many are open, so the server does not run out of file descriptors.
+    fn region(&mut self, region: (i32, i32)) -> Result<Option<&mut RegionOnDisk>, ProtocolError> {
+        match self.regions.iter().position(|&(open, _)| open == region) {
+            Some(index) => {
+                let open = self.regions.remove(index);
+                self.regions.push(open);
+            }
+            None => {
+                let path = self
+                    .directory
+                    .join("region")
+                    .join(format!("r.{}.{}.mca", region.0, region.1));
+
+                let file = match File::open(&path) {
+                    Ok(file) => Some(RegionFile::new(BufReader::new(file))?),
+                    Err(ref error) if error.kind() == io::ErrorKind::NotFound => None,
+                    Err(error) => return Err(ProtocolError::Io(error)),
+                };
+
+                debug!(
+                    "Opened region {:?} of {}.",
+                    region,
+                    self.directory.display()
+                );
+
+                if self.regions.len() == MAX_OPEN_REGIONS {
+                    let (closed, _) = self.regions.remove(0);
+                    debug!(
+                        "Closed region {:?} of {}.",
+                        closed,
+                        self.directory.display()
+                    );
+                }
+
+                self.regions.push((region, file));
+            }
+        }
+
+        Ok(self.regions.last_mut().and_then(|(_, file)| file.as_mut()))
+    }
+}
+
+/// Converts a chunk as stored by vanilla, returning `None` if it is not complete yet.
+///
+/// Blocks that are not in the block registry are an error, as the chunk was saved by
+/// another version or a modded server then.
+pub fn chunk_from_nbt(nbt: &Nbt) -> Result<Option<Chunk>, ProtocolError> {
+    let level = compound(&nbt.root, "Level")?;
+
+    if !COMPLETE_STATUSES.contains(&string(level, "Status")?) {
+        return Ok(None);
+    }
+
+    let mut chunk = Chunk::new(int(level, "xPos")?, int(level, "zPos")?);
+
+    for section in list(level, "Sections")? {
+        let section = match section {
+            Tag::Compound(section) => section,
+            tag => return Err(unexpected_tag("Sections", tag)),
+        };
+
+        let y = match section.get("Y") {
+            Some(&Tag::Byte(y)) => y,
+            Some(tag) => return Err(unexpected_tag("Y", tag)),
+            None => return Err(missing_tag("Y")),
+        };
+
+        // vanilla stores the light of the sections below and above the world as well
+        if y < 0 || y as usize >= SECTIONS_PER_CHUNK || section.get("Palette").is_none() {
+            continue;
+        }
+
+        *chunk.section_mut(y as usize) = read_section(section)?;
+    }
+
+    if let Some(tag) = level.get("Biomes") {
+        match tag {
+            Tag::IntArray(biomes) if biomes.len() == SECTION_WIDTH * SECTION_WIDTH => {
+                for (index, &biome) in biomes.iter().enumerate() {
+                    chunk.set_biome(index % SECTION_WIDTH, index / SECTION_WIDTH, biome);
+                }
+            }
+            // the biomes of older chunks may be missing, which keeps the default ones
+            Tag::IntArray(_) => {}
+            tag => return Err(unexpected_tag("Biomes", tag)),
+        }
+    }
+
+    if level.get("TileEntities").is_some() {
+        for block_entity in list(level, "TileEntities")? {
+            match block_entity {
+                Tag::Compound(block_entity) => chunk.block_entities.push(block_entity.clone()),
+                tag => return Err(unexpected_tag("TileEntities", tag)),
+            }
+        }
+    }
+
+    Ok(Some(chunk))
+}
+
+fn read_section(section: &Compound) -> Result<ChunkSection, ProtocolError> {
+    let palette = list(section, "Palette")?
+        .iter()
+        .map(|entry| match entry {
+            Tag::Compound(entry) => palette_entry(entry),
+            tag => Err(unexpected_tag("Palette", tag)),
+        })
+        .collect::<Result<Vec<BlockState>, _>>()?;
+
+    let bits = (usize::BITS - (palette.len().max(1) - 1).leading_zeros()) as u8;
+    let longs = match section.get("BlockStates") {
+        Some(Tag::LongArray(longs)) => longs.iter().map(|&long| long as u64).collect(),
+        Some(tag) => return Err(unexpected_tag("BlockStates", tag)),
+        None => return Err(missing_tag("BlockStates")),
+    };
+    let indices = PackedArray::from_longs(bits.max(MIN_BITS_PER_BLOCK), BLOCKS_PER_SECTION, longs)?;
+
+    let blocks = indices
+        .iter()
+        .map(|index| {
+            palette
+                .get(index as usize)
+                .cloned()
+                .ok_or_else(|| ProtocolError::invalid_value("palette index", index))
+        })
+        .collect::<Result<Vec<BlockState>, _>>()?;
+
+    let mut chunk_section = ChunkSection::from_blocks(blocks);
+
+    if let Some(light) = light(section, "BlockLight")? {
+        chunk_section.block_light = light;
+    }
+
+    if let Some(light) = light(section, "SkyLight")? {
+        chunk_section.sky_light = light;
+    }
+
+    Ok(chunk_section)
+}
+
+/// Returns the state of an entry of a palette, like `{Name: "minecraft:grass_block",
+/// Properties: {snowy: "false"}}`.
+fn palette_entry(entry: &Compound) -> Result<BlockState, ProtocolError> {
+    let name = string(entry, "Name")?;
+
+    let properties = match entry.get("Properties") {
+        Some(Tag::Compound(properties)) => properties
+            .iter()
+            .map(|(name, value)| match value {
+                Tag::String(value) => Ok((name, value.as_str())),
+                tag => Err(unexpected_tag("Properties", tag)),
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        Some(tag) => return Err(unexpected_tag("Properties", tag)),
+        None => vec![],
+    };
+
+    BlockRegistry::vanilla()
+        .block(name)
+        .ok_or_else(|| ProtocolError::invalid_value("block", name))
+        .and_then(|block| block.state(properties))
+}
+
+fn light(section: &Compound, name: &'static str) -> Result<Option<NibbleArray>, ProtocolError> {
+    match section.get(name) {
+        Some(Tag::ByteArray(bytes)) => {
+            let bytes = bytes.iter().map(|&byte| byte as u8).collect();
+
+            NibbleArray::from_bytes(bytes).map(Some)
+        }
+        Some(tag) => Err(unexpected_tag(name, tag)),
+        None => Ok(None),
+    }
+}
+
+fn missing_tag(name: &'static str) -> ProtocolError {
+    ProtocolError::invalid_value("chunk", format!("without {}", name))
+}
+
+fn unexpected_tag(name: &'static str, tag: &Tag) -> ProtocolError {
+    ProtocolError::invalid_value("chunk", format!("with {} of {}", name, tag.type_name()))
+}
+
+fn compound<'a>(compound: &'a Compound, name: &'static str) -> Result<&'a Compound, ProtocolError> {
+    match compound.get(name) {
+        Some(Tag::Compound(value)) => Ok(value),
+        Some(tag) => Err(unexpected_tag(name, tag)),
+        None => Err(missing_tag(name)),
+    }
+}
+
+fn list<'a>(compound: &'a Compound, name: &'static str) -> Result<&'a [Tag], ProtocolError> {
+    match compound.get(name) {
+        Some(Tag::List(value)) => Ok(value),
+        Some(tag) => Err(unexpected_tag(name, tag)),
+        None => Err(missing_tag(name)),
+    }
+}
+
+fn string<'a>(compound: &'a Compound, name: &'static str) -> Result<&'a str, ProtocolError> {
+    match compound.get(name) {
+        Some(Tag::String(value)) => Ok(value),
+        Some(tag) => Err(unexpected_tag(name, tag)),
+        None => Err(missing_tag(name)),
+    }
+}
+
+fn int(compound: &Compound, name: &'static str) -> Result<i32, ProtocolError> {
+    match compound.get(name) {
+        Some(&Tag::Int(value)) => Ok(value),
+        Some(tag) => Err(unexpected_tag(name, tag)),
+        None => Err(missing_tag(name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        chunk_from_nbt, AnvilLoader, RegionFile, COMPRESSION_GZIP, COMPRESSION_ZLIB,
+        MAX_OPEN_REGIONS,
+    };
+    use crate::chunk::{PackedArray, AIR, BLOCKS_PER_SECTION};
+    use crate::coding::error::ProtocolError;
+    use crate::coding::nbt::{Compound, Nbt, Tag};
+    use crate::location::Location;
+    use std::fs;
+    use std::io::Cursor;
+
+    /// A chunk with a section of air with grass at 0, 0, 0, stone next to it and a chest.
+    fn chunk_nbt(x: i32, z: i32, status: &str) -> Nbt {
+        chunk_nbt_with_block(x, z, status, "minecraft:stone")
+    }
+
+    /// Like `chunk_nbt`, with the given block instead of stone.
+    fn chunk_nbt_with_block(x: i32, z: i32, status: &str, block: &str) -> Nbt {
+        let palette = vec![
+            Tag::Compound(
+                vec![("Name", Tag::from("minecraft:air"))]
+                    .into_iter()
+                    .collect(),
+            ),
+            Tag::Compound(
+                vec![
+                    ("Name", Tag::from("minecraft:grass_block")),
+                    (
+                        "Properties",
+                        Tag::Compound(vec![("snowy", "true")].into_iter().collect()),
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+            Tag::Compound(vec![("Name", Tag::from(block))].into_iter().collect()),
+        ];
+
+        let mut block_states = PackedArray::new(4, BLOCKS_PER_SECTION);
+        block_states.set(0, 1);
+        block_states.set(1, 2);
+
+        let mut section = Compound::new();
+        section.insert("Y", 0i8);
+        section.insert("Palette", Tag::List(palette));
+        section.insert(
+            "BlockStates",
+            Tag::LongArray(
+                block_states
+                    .longs()
+                    .iter()
+                    .map(|&long| long as i64)
+                    .collect(),
+            ),
+        );
+        section.insert("BlockLight", Tag::ByteArray(vec![0x21; 2048]));
+        section.insert("SkyLight", Tag::ByteArray(vec![0; 2048]));
+
+        // only light, above the world
+        let mut light_section = Compound::new();
+        light_section.insert("Y", 16i8);
+        light_section.insert("SkyLight", Tag::ByteArray(vec![-1; 2048]));
+
+        let chest: Compound = vec![
+            ("id", Tag::from("minecraft:chest")),
+            ("x", Tag::from(x * 16)),
+            ("y", Tag::from(0)),
+            ("z", Tag::from(z * 16)),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut level = Compound::new();
+        level.insert("xPos", x);
+        level.insert("zPos", z);
+        level.insert("Status", status);
+        level.insert(
+            "Sections",
+            Tag::List(vec![Tag::Compound(section), Tag::Compound(light_section)]),
+        );
+        level.insert("Biomes", Tag::IntArray(vec![2; 256]));
+        level.insert("TileEntities", Tag::List(vec![Tag::Compound(chest)]));
+
+        let mut root = Compound::new();
+        root.insert("DataVersion", 1631);
+        root.insert("Level", level);
+
+        Nbt::new("", root)
+    }
+
+    /// Builds a region file with the given chunks, each in its own sector after the header.
+    fn region(chunks: &[(i32, i32, u8, &Nbt)]) -> Vec<u8> {
+        let mut header = vec![0; 8192];
+        let mut sectors = vec![];
+
+        for (index, &(x, z, compression, nbt)) in chunks.iter().enumerate() {
+            let mut data = vec![];
+
+            match compression {
+                COMPRESSION_GZIP => nbt.write_gzip(&mut data).unwrap(),
+                _ => nbt.write_zlib(&mut data).unwrap(),
+            }
+
+            let location = ((z & 31) * 32 + (x & 31)) as usize * 4;
+            header[location + 2] = (index + 2) as u8;
+            header[location + 3] = 1;
+
+            let mut sector = (data.len() as u32 + 1).to_be_bytes().to_vec();
+            sector.push(compression);
+            sector.extend(data);
+            sector.resize(4096, 0);
+            sectors.extend(sector);
+        }
+
+        header.extend(sectors);
+        header
+    }
+
+    #[test]
+    fn test_chunk_from_nbt() {
+        let chunk = chunk_from_nbt(&chunk_nbt(-3, 7, "postprocessed"))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!((-3, 7), (chunk.x, chunk.z));
+        assert_eq!(8, chunk.block(0, 0, 0));
+        assert_eq!(1, chunk.block(1, 0, 0));
+        assert_eq!(AIR, chunk.block(0, 1, 0));
+        assert_eq!(1, chunk.primary_bit_mask());
+
+        let section = chunk.section(0).unwrap();
+        assert_eq!(1, section.block_light.get(0));
+        assert_eq!(2, section.block_light.get(1));
+        assert_eq!(0, section.sky_light.get(0));
+
+        assert_eq!(2, chunk.biome(15, 15));
+        assert_eq!(1, chunk.block_entities.len());
+        assert_eq!(
+            Some(&Tag::from("minecraft:chest")),
+            chunk.block_entities[0].get("id")
+        );
+    }
+
+    #[test]
+    fn test_unknown_block() {
+        let nbt = chunk_nbt_with_block(0, 0, "fullchunk", "minecraft:unknown");
+
+        assert_eq!(
+            Err(ProtocolError::invalid_value("block", "minecraft:unknown")),
+            chunk_from_nbt(&nbt)
+        );
+    }
+
+    #[test]
+    fn test_incomplete_chunk() {
+        assert_eq!(None, chunk_from_nbt(&chunk_nbt(0, 0, "carved")).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_chunks() {
+        let mut nbt = chunk_nbt(0, 0, "fullchunk");
+        nbt.root = Compound::new();
+
+        assert_eq!(
+            Err(ProtocolError::invalid_value("chunk", "without Level")),
+            chunk_from_nbt(&nbt)
+        );
+
+        let mut nbt = chunk_nbt(0, 0, "fullchunk");
+        if let Some(Tag::Compound(level)) = nbt.root.get("Level").cloned() {
+            let mut level = level;
+            level.insert("xPos", 1i64);
+            nbt.root.insert("Level", level);
+        }
+
+        assert_eq!(
+            Err(ProtocolError::invalid_value(
+                "chunk",
+                "with xPos of TAG_Long"
+            )),
+            chunk_from_nbt(&nbt)
+        );
+    }
+
+    #[test]
+    fn test_read_region() {
+        let first = chunk_nbt(0, 0, "postprocessed");
+        let second = chunk_nbt(-1, -32, "postprocessed");
+        let bytes = region(&[
+            (0, 0, COMPRESSION_ZLIB, &first),
+            (-1, -32, COMPRESSION_GZIP, &second),
+        ]);
+
+        let mut region = RegionFile::new(Cursor::new(bytes)).unwrap();
+
+        assert_eq!(Some(&second), region.read_chunk(31, 0).unwrap().as_ref());
+        assert_eq!(Some(first), region.read_chunk(0, 0).unwrap());
+        assert_eq!(None, region.read_chunk(5, 5).unwrap());
+    }
+
+    #[test]
+    fn test_read_invalid_region() {
+        assert_eq!(
+            Err(ProtocolError::unexpected_eof("region header")),
+            RegionFile::new(Cursor::new(vec![0; 100])).map(|_| ())
+        );
+
+        let nbt = chunk_nbt(0, 0, "postprocessed");
+        let mut bytes = region(&[(0, 0, COMPRESSION_ZLIB, &nbt)]);
+        bytes[8196] = 3;
+
+        assert_eq!(
+            Err(ProtocolError::invalid_value("chunk compression", 3)),
+            RegionFile::new(Cursor::new(bytes))
+                .unwrap()
+                .read_chunk(0, 0)
+        );
+
+        let mut bytes = region(&[(0, 0, COMPRESSION_ZLIB, &nbt)]);
+        bytes[8192..8196].copy_from_slice(&5000u32.to_be_bytes());
+
+        assert_eq!(
+            Err(ProtocolError::TooLong {
+                type_name: "chunk",
+                length: 5000,
+                max_length: 4092,
+            }),
+            RegionFile::new(Cursor::new(bytes))
+                .unwrap()
+                .read_chunk(0, 0)
+        );
+    }
+
+    #[test]
+    fn test_anvil_loader() {
+        let directory =
+            std::env::temp_dir().join(format!("oxicraft-test-{}-world", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+
+        assert!(AnvilLoader::open(&directory).is_none());
+
+        fs::create_dir_all(directory.join("region")).unwrap();
+
+        let inside = chunk_nbt(33, -2, "postprocessed");
+        // stored at the position of another chunk
+        let misplaced = chunk_nbt(0, 0, "postprocessed");
+        fs::write(
+            directory.join("region").join("r.1.-1.mca"),
+            region(&[
+                (33, -2, COMPRESSION_ZLIB, &inside),
+                (34, -2, COMPRESSION_ZLIB, &misplaced),
+            ]),
+        )
+        .unwrap();
+
+        let mut data = Compound::new();
+        data.insert("SpawnX", 100);
+        data.insert("SpawnY", 70);
+        data.insert("SpawnZ", -20);
+        let mut root = Compound::new();
+        root.insert("Data", data);
+        Nbt::new("", root)
+            .write_gzip(fs::File::create(directory.join("level.dat")).unwrap())
+            .unwrap();
+
+        let mut loader = AnvilLoader::open(&directory).unwrap();
+
+        assert_eq!(
+            Some(Location {
+                x: 100,
+                y: 70,
+                z: -20
+            }),
+            loader.spawn_location().unwrap()
+        );
+        assert_eq!(
+            Some((33, -2)),
+            loader
+                .load_chunk(33, -2)
+                .unwrap()
+                .map(|chunk| (chunk.x, chunk.z))
+        );
+        assert_eq!(None, loader.load_chunk(35, -2).unwrap());
+        // the region does not exist
+        assert_eq!(None, loader.load_chunk(0, 0).unwrap());
+        assert_eq!(
+            Err(ProtocolError::invalid_value(
+                "chunk position",
+                "0, 0 instead of 34, -2"
+            )),
+            loader.load_chunk(34, -2)
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_least_recently_used_regions_are_closed() {
+        let directory =
+            std::env::temp_dir().join(format!("oxicraft-test-{}-regions", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(directory.join("region")).unwrap();
+
+        let nbt = chunk_nbt(0, 0, "postprocessed");
+        fs::write(
+            directory.join("region").join("r.0.0.mca"),
+            region(&[(0, 0, COMPRESSION_ZLIB, &nbt)]),
+        )
+        .unwrap();
+
+        let mut loader = AnvilLoader::open(&directory).unwrap();
+        let is_open =
+            |loader: &AnvilLoader, region| loader.regions.iter().any(|&(open, _)| open == region);
+
+        assert!(loader.load_chunk(0, 0).unwrap().is_some());
+
+        for x in 1..MAX_OPEN_REGIONS as i32 {
+            loader.load_chunk(x * 32, 0).unwrap();
+        }
+
+        // using the first region again keeps it open instead of the second one
+        assert!(loader.load_chunk(0, 0).unwrap().is_some());
+        loader.load_chunk(0, 32).unwrap();
+
+        assert_eq!(MAX_OPEN_REGIONS, loader.regions.len());
+        assert!(is_open(&loader, (0, 0)));
+        assert!(!is_open(&loader, (1, 0)));
+
+        for z in 2..MAX_OPEN_REGIONS as i32 + 2 {
+            loader.load_chunk(0, z * 32).unwrap();
+        }
+
+        assert_eq!(MAX_OPEN_REGIONS, loader.regions.len());
+        assert!(!is_open(&loader, (0, 0)));
+        // closed regions are opened again
+        assert!(loader.load_chunk(0, 0).unwrap().is_some());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,38 @@
+//! Generators create the chunks of a world that are not stored anywhere yet.
+mod flat;
+mod noise;
+
+pub use self::flat::{FlatGenerator, FlatPreset};
+pub use self::noise::{NoiseGenerator, TerrainSettings};
+
+use crate::chunk::Chunk;
+use crate::coding::level_type::LevelType;
+use crate::config::ServerConfig;
+use crate::location::Location;
+
+pub trait WorldGenerator: Send + Sync {
+    /// Generates the chunk at the given chunk coordinates, with its sky light computed.
+    fn generate_chunk(&self, x: i32, z: i32) -> Chunk;
+
+    /// Where players spawn, which has to be on the ground of the generated chunks.
+    fn spawn_location(&self) -> Location;
+}
+
+/// Returns the generator for the level type of the config.
+pub fn from_config(config: &ServerConfig) -> Box<dyn WorldGenerator> {
+    let settings = match config.level_type {
+        LevelType::Flat => {
+            let preset = config
+                .generator_settings
+                .parse()
+                .expect("the preset was checked when loading the config");
+
+            return Box::new(FlatGenerator::new(preset));
+        }
+        LevelType::Default | LevelType::Default1_1 => TerrainSettings::default(),
+        LevelType::LargeBiomes => TerrainSettings::large_biomes(),
+        LevelType::Amplified => TerrainSettings::amplified(),
+    };
+
+    Box::new(NoiseGenerator::new(config.level_seed, settings))
+}
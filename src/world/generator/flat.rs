@@ -0,0 +1,251 @@
+use super::WorldGenerator;
+use crate::block::BlockRegistry;
+use crate::chunk::{biome_by_name, Biome, BlockState, Chunk, AIR, CHUNK_HEIGHT, SECTION_WIDTH};
+use crate::coding::error::ProtocolError;
+use crate::location::Location;
+use std::str::FromStr;
+
+/// The preset vanilla uses for superflat worlds if none is given.
+const DEFAULT_PRESET: &str =
+    "minecraft:bedrock,2*minecraft:dirt,minecraft:grass_block;minecraft:plains";
+
+/// The layers and biome of a superflat world, as given in the `generator-settings` of
+/// the config, e.g. `minecraft:bedrock,2*minecraft:dirt,minecraft:grass_block;minecraft:plains`.
+///
+/// The layers are listed from the bottom up, each optionally prefixed with how often it is
+/// repeated. A third part with the structures is accepted, but they are not generated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatPreset {
+    /// one block state for every y from 0 up
+    layers: Vec<BlockState>,
+    biome: Biome,
+}
+
+impl Default for FlatPreset {
+    fn default() -> FlatPreset {
+        DEFAULT_PRESET.parse().expect("the default preset is valid")
+    }
+}
+
+impl FromStr for FlatPreset {
+    type Err = ProtocolError;
+
+    /// Parses a preset, where an empty one is the default preset like in vanilla.
+    fn from_str(preset: &str) -> Result<FlatPreset, ProtocolError> {
+        if preset.trim().is_empty() {
+            return Ok(FlatPreset::default());
+        }
+
+        let mut parts = preset.split(';');
+        let layers = parse_layers(parts.next().unwrap_or_default())?;
+
+        let biome = match parts.next() {
+            Some(name) => biome_by_name(name.trim())
+                .ok_or_else(|| ProtocolError::invalid_value("biome", name))?,
+            None => biome_by_name("plains").unwrap(),
+        };
+
+        Ok(FlatPreset { layers, biome })
+    }
+}
+
+fn parse_layers(layers: &str) -> Result<Vec<BlockState>, ProtocolError> {
+    let blocks = BlockRegistry::vanilla();
+    let mut states = vec![];
+
+    for layer in split_layers(layers) {
+        let (count, block) = match layer.find('*') {
+            Some(position) => {
+                let count = &layer[..position];
+                let count = count
+                    .trim()
+                    .parse()
+                    .map_err(|_| ProtocolError::invalid_value("layer count", count))?;
+
+                (count, &layer[position + 1..])
+            }
+            None => (1, layer),
+        };
+
+        let state = blocks.parse(block.trim())?;
+        let length = states.len().saturating_add(count);
+
+        if length > CHUNK_HEIGHT {
+            return Err(ProtocolError::TooLong {
+                type_name: "superflat layers",
+                length,
+                max_length: CHUNK_HEIGHT,
+            });
+        }
+
+        states.extend(std::iter::repeat_n(state, count));
+    }
+
+    if states.is_empty() {
+        return Err(ProtocolError::invalid_value("superflat layers", layers));
+    }
+
+    Ok(states)
+}
+
+/// Splits the layers at commas, but not at those between the properties of a block.
+fn split_layers(layers: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut in_properties = false;
+
+    for (index, character) in layers.char_indices() {
+        match character {
+            '[' => in_properties = true,
+            ']' => in_properties = false,
+            ',' if !in_properties => {
+                parts.push(&layers[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    parts.push(&layers[start..]);
+    parts
+}
+
+/// Generates the same layers everywhere.
+pub struct FlatGenerator {
+    preset: FlatPreset,
+}
+
+impl FlatGenerator {
+    pub fn new(preset: FlatPreset) -> FlatGenerator {
+        FlatGenerator { preset }
+    }
+}
+
+impl WorldGenerator for FlatGenerator {
+    fn generate_chunk(&self, x: i32, z: i32) -> Chunk {
+        let mut chunk = Chunk::new(x, z);
+
+        for (y, &block) in self.preset.layers.iter().enumerate() {
+            if block == AIR {
+                continue;
+            }
+
+            for x in 0..SECTION_WIDTH {
+                for z in 0..SECTION_WIDTH {
+                    chunk.set_block(x, y, z, block);
+                }
+            }
+        }
+
+        for x in 0..SECTION_WIDTH {
+            for z in 0..SECTION_WIDTH {
+                chunk.set_biome(x, z, self.preset.biome);
+            }
+        }
+
+        chunk.compute_sky_light();
+
+        chunk
+    }
+
+    /// On top of the highest layer, like vanilla.
+    fn spawn_location(&self) -> Location {
+        Location {
+            x: 0,
+            y: self.preset.layers.len() as i16,
+            z: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FlatGenerator, FlatPreset};
+    use crate::chunk::AIR;
+    use crate::coding::error::ProtocolError;
+    use crate::location::Location;
+    use crate::world::generator::WorldGenerator;
+
+    #[test]
+    fn test_parse_presets() {
+        let mappings = vec![
+            ("", vec![33, 10, 10, 9], 1),
+            (
+                "minecraft:bedrock,2*minecraft:dirt,minecraft:grass_block;minecraft:plains;village",
+                vec![33, 10, 10, 9],
+                1,
+            ),
+            ("bedrock, 3*stone ,sand;desert", vec![33, 1, 1, 1, 66], 2),
+            ("minecraft:air,minecraft:glass", vec![0, 230], 1),
+            (
+                "oak_log[axis=x],dispenser[facing=up,triggered=true]",
+                vec![72, 241],
+                1,
+            ),
+        ];
+
+        for (preset, layers, biome) in mappings {
+            assert_eq!(
+                Ok(FlatPreset { layers, biome }),
+                preset.parse::<FlatPreset>(),
+                "{}",
+                preset
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_presets() {
+        let mappings = vec![
+            (
+                "minecraft:unknown",
+                ProtocolError::invalid_value("block", "minecraft:unknown"),
+            ),
+            (
+                "two*minecraft:dirt",
+                ProtocolError::invalid_value("layer count", "two"),
+            ),
+            (
+                "0*minecraft:dirt",
+                ProtocolError::invalid_value("superflat layers", "0*minecraft:dirt"),
+            ),
+            (
+                "minecraft:stone;minecraft:unknown",
+                ProtocolError::invalid_value("biome", "minecraft:unknown"),
+            ),
+            (
+                "257*minecraft:stone",
+                ProtocolError::TooLong {
+                    type_name: "superflat layers",
+                    length: 257,
+                    max_length: 256,
+                },
+            ),
+        ];
+
+        for (preset, error) in mappings {
+            assert_eq!(Err(error), preset.parse::<FlatPreset>(), "{}", preset);
+        }
+    }
+
+    #[test]
+    fn test_generate_chunk() {
+        let generator =
+            FlatGenerator::new("bedrock,2*dirt,air,grass_block;desert".parse().unwrap());
+        let chunk = generator.generate_chunk(-3, 7);
+
+        assert_eq!((-3, 7), (chunk.x, chunk.z));
+
+        for &(x, z) in &[(0, 0), (15, 15), (4, 9)] {
+            assert_eq!(33, chunk.block(x, 0, z));
+            assert_eq!(10, chunk.block(x, 2, z));
+            assert_eq!(AIR, chunk.block(x, 3, z));
+            assert_eq!(9, chunk.block(x, 4, z));
+            assert_eq!(AIR, chunk.block(x, 5, z));
+            assert_eq!(2, chunk.biome(x, z));
+        }
+
+        assert_eq!(1, chunk.primary_bit_mask());
+        assert_eq!(Location { x: 0, y: 5, z: 0 }, generator.spawn_location());
+    }
+}
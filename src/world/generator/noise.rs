@@ -0,0 +1,643 @@
+mod perlin;
+
+use self::perlin::Octaves;
+use super::WorldGenerator;
+use crate::block::BlockRegistry;
+use crate::chunk::{Biome, BlockState, Chunk, AIR, CHUNK_HEIGHT, PLAINS, SECTION_WIDTH};
+use crate::location::Location;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// The lowest y that is not filled with water in oceans, like vanilla.
+const SEA_LEVEL: usize = 63;
+/// Caves below this y are filled with lava.
+const LAVA_LEVEL: usize = 10;
+/// Caves are only carved below this y, as terrain rarely reaches above it.
+const MAX_CAVE_HEIGHT: usize = 128;
+/// The distance in blocks between the points the cave noise is sampled at, the blocks in
+/// between are interpolated.
+const CAVE_CELL_SIZE: usize = 4;
+/// How close to zero both cave noises have to be for a block to be carved, which makes
+/// caves long tunnels where the zero surfaces of both noises cross.
+const CAVE_THRESHOLD: f64 = 0.004;
+
+/// The biomes of 1.13.2 that are generated.
+const OCEAN: Biome = 0;
+const DESERT: Biome = 2;
+const MOUNTAINS: Biome = 3;
+const FOREST: Biome = 4;
+const TAIGA: Biome = 5;
+const BEACH: Biome = 16;
+const DEEP_OCEAN: Biome = 24;
+
+/// How the terrain is shaped, which differs between the level types.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerrainSettings {
+    /// the height of the terrain where it is neither raised nor lowered
+    pub base_height: f64,
+    /// how far the terrain reaches above and below the base height
+    pub height_variation: f64,
+    /// how much larger biomes and continents are than in the default world
+    pub biome_scale: f64,
+}
+
+impl Default for TerrainSettings {
+    fn default() -> TerrainSettings {
+        TerrainSettings {
+            base_height: 66.0,
+            height_variation: 40.0,
+            biome_scale: 1.0,
+        }
+    }
+}
+
+impl TerrainSettings {
+    /// Mountains reach up to the build limit.
+    pub fn amplified() -> TerrainSettings {
+        TerrainSettings {
+            base_height: 72.0,
+            height_variation: 96.0,
+            ..Default::default()
+        }
+    }
+
+    /// Biomes and continents are four times larger.
+    pub fn large_biomes() -> TerrainSettings {
+        TerrainSettings {
+            biome_scale: 4.0,
+            ..Default::default()
+        }
+    }
+}
+
+/// The block states the generator places.
+struct Blocks {
+    stone: BlockState,
+    dirt: BlockState,
+    grass_block: BlockState,
+    sand: BlockState,
+    sandstone: BlockState,
+    gravel: BlockState,
+    water: BlockState,
+    lava: BlockState,
+    bedrock: BlockState,
+    coal_ore: BlockState,
+    iron_ore: BlockState,
+    gold_ore: BlockState,
+    lapis_ore: BlockState,
+    oak_log: BlockState,
+    oak_leaves: BlockState,
+    birch_log: BlockState,
+    birch_leaves: BlockState,
+    spruce_log: BlockState,
+    spruce_leaves: BlockState,
+}
+
+impl Blocks {
+    fn vanilla() -> Blocks {
+        let blocks = BlockRegistry::vanilla();
+        let state = |name: &str| {
+            blocks
+                .parse(name)
+                .unwrap_or_else(|_| panic!("{} is a vanilla block", name))
+        };
+
+        Blocks {
+            stone: state("minecraft:stone"),
+            dirt: state("minecraft:dirt"),
+            grass_block: state("minecraft:grass_block"),
+            sand: state("minecraft:sand"),
+            sandstone: state("minecraft:sandstone"),
+            gravel: state("minecraft:gravel"),
+            water: state("minecraft:water"),
+            lava: state("minecraft:lava"),
+            bedrock: state("minecraft:bedrock"),
+            coal_ore: state("minecraft:coal_ore"),
+            iron_ore: state("minecraft:iron_ore"),
+            gold_ore: state("minecraft:gold_ore"),
+            lapis_ore: state("minecraft:lapis_ore"),
+            oak_log: state("minecraft:oak_log"),
+            // nothing decays the leaves yet, but they should not once something does
+            oak_leaves: state("minecraft:oak_leaves[persistent=true]"),
+            birch_log: state("minecraft:birch_log"),
+            birch_leaves: state("minecraft:birch_leaves[persistent=true]"),
+            spruce_log: state("minecraft:spruce_log"),
+            spruce_leaves: state("minecraft:spruce_leaves[persistent=true]"),
+        }
+    }
+}
+
+/// The height and biome of a column of blocks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Column {
+    /// the y of the highest block that is not air or water
+    height: usize,
+    biome: Biome,
+}
+
+/// Generates hills, mountains and oceans with caves, ores and trees from height noise,
+/// which is the same for the same seed.
+///
+/// The noise is seeded with ChaCha8 rather than `StdRng`, whose algorithm may change
+/// between releases of `rand`, so a seed keeps its world on every platform and version.
+pub struct NoiseGenerator {
+    seed: i64,
+    settings: TerrainSettings,
+    blocks: Blocks,
+    /// where the land rises above the sea, in large features
+    continents: Octaves,
+    /// the hills on top of the continents
+    hills: Octaves,
+    temperature: Octaves,
+    humidity: Octaves,
+    caves: [Octaves; 2],
+}
+
+impl NoiseGenerator {
+    pub fn new(seed: i64, settings: TerrainSettings) -> NoiseGenerator {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed as u64);
+        let biome_scale = settings.biome_scale;
+
+        NoiseGenerator {
+            seed,
+            settings,
+            blocks: Blocks::vanilla(),
+            continents: Octaves::new(&mut rng, 4, 512.0 * biome_scale),
+            hills: Octaves::new(&mut rng, 5, 96.0),
+            temperature: Octaves::new(&mut rng, 3, 384.0 * biome_scale),
+            humidity: Octaves::new(&mut rng, 3, 256.0 * biome_scale),
+            caves: [
+                Octaves::new(&mut rng, 2, 48.0),
+                Octaves::new(&mut rng, 2, 48.0),
+            ],
+        }
+    }
+
+    /// Returns the column at the given world coordinates.
+    fn column(&self, x: i32, z: i32) -> Column {
+        let (x, z) = (f64::from(x), f64::from(z));
+
+        let land = (self.continents.sample_2d(x, z) * 2.5).clamp(-1.0, 1.0);
+        // the higher the land, the rougher it gets
+        let roughness = 0.25 + 0.75 * land.max(0.0);
+        let height = self.settings.base_height
+            + land * self.settings.height_variation * 0.6
+            + self.hills.sample_2d(x, z) * self.settings.height_variation * roughness;
+        let height = height.clamp(4.0, (CHUNK_HEIGHT - 16) as f64) as usize;
+
+        let biome = if height + 20 < SEA_LEVEL {
+            DEEP_OCEAN
+        } else if height + 1 < SEA_LEVEL {
+            OCEAN
+        } else if height <= SEA_LEVEL + 1 {
+            BEACH
+        } else if height > SEA_LEVEL + 40 {
+            MOUNTAINS
+        } else {
+            let temperature = self.temperature.sample_2d(x, z) * 2.0;
+            let humidity = self.humidity.sample_2d(x, z) * 2.0;
+
+            if temperature > 0.35 && humidity < 0.0 {
+                DESERT
+            } else if temperature < -0.35 {
+                TAIGA
+            } else if humidity > 0.1 {
+                FOREST
+            } else {
+                PLAINS
+            }
+        };
+
+        Column { height, biome }
+    }
+
+    /// Fills a column with stone up to its height, covered by the surface of its biome,
+    /// and with water up to the sea level.
+    fn fill_column<R: Rng>(
+        &self,
+        chunk: &mut Chunk,
+        x: usize,
+        z: usize,
+        column: Column,
+        rng: &mut R,
+    ) {
+        let blocks = &self.blocks;
+        let (top, filler) = match column.biome {
+            DESERT | BEACH => (blocks.sand, blocks.sand),
+            OCEAN | DEEP_OCEAN if column.height + 8 < SEA_LEVEL => (blocks.gravel, blocks.gravel),
+            OCEAN | DEEP_OCEAN => (blocks.sand, blocks.sand),
+            _ => (blocks.grass_block, blocks.dirt),
+        };
+
+        for y in 0..=column.height.max(SEA_LEVEL - 1) {
+            let block = if y == 0 || (y < 5 && y <= rng.gen_range(0..5)) {
+                blocks.bedrock
+            } else if y > column.height {
+                blocks.water
+            } else if y == column.height {
+                top
+            } else if y + 4 > column.height {
+                filler
+            } else if y + 7 > column.height && filler == blocks.sand {
+                blocks.sandstone
+            } else {
+                blocks.stone
+            };
+
+            chunk.set_block(x, y, z, block);
+        }
+
+        chunk.set_biome(x, z, column.biome);
+    }
+
+    /// Carves tunnels where two 3D noises are close to zero at the same time.
+    ///
+    /// The noise is only sampled every few blocks and interpolated in between, which is
+    /// much faster and barely noticeable.
+    fn carve_caves(&self, chunk: &mut Chunk, columns: &[Column]) {
+        let cells = SECTION_WIDTH / CAVE_CELL_SIZE + 1;
+        let vertical_cells = MAX_CAVE_HEIGHT / CAVE_CELL_SIZE + 1;
+        let (chunk_x, chunk_z) = (
+            chunk.x * SECTION_WIDTH as i32,
+            chunk.z * SECTION_WIDTH as i32,
+        );
+
+        let samples: Vec<Vec<f64>> = self
+            .caves
+            .iter()
+            .map(|noise| {
+                let mut samples = Vec::with_capacity(cells * cells * vertical_cells);
+
+                for cell_x in 0..cells {
+                    for cell_z in 0..cells {
+                        for cell_y in 0..vertical_cells {
+                            let x = f64::from(chunk_x + (cell_x * CAVE_CELL_SIZE) as i32);
+                            let y = (cell_y * CAVE_CELL_SIZE) as f64;
+                            let z = f64::from(chunk_z + (cell_z * CAVE_CELL_SIZE) as i32);
+
+                            // caves are flatter than they are wide
+                            samples.push(noise.sample(x, y * 2.0, z));
+                        }
+                    }
+                }
+
+                samples
+            })
+            .collect();
+
+        let sample = |noise: usize, x: usize, y: usize, z: usize| {
+            let cell =
+                |x: usize, y: usize, z: usize| samples[noise][(x * cells + z) * vertical_cells + y];
+            let (cell_x, cell_y, cell_z) =
+                (x / CAVE_CELL_SIZE, y / CAVE_CELL_SIZE, z / CAVE_CELL_SIZE);
+            let size = CAVE_CELL_SIZE as f64;
+            let (tx, ty, tz) = (
+                (x % CAVE_CELL_SIZE) as f64 / size,
+                (y % CAVE_CELL_SIZE) as f64 / size,
+                (z % CAVE_CELL_SIZE) as f64 / size,
+            );
+            let lerp = |t: f64, a: f64, b: f64| a + t * (b - a);
+
+            lerp(
+                ty,
+                lerp(
+                    tz,
+                    lerp(
+                        tx,
+                        cell(cell_x, cell_y, cell_z),
+                        cell(cell_x + 1, cell_y, cell_z),
+                    ),
+                    lerp(
+                        tx,
+                        cell(cell_x, cell_y, cell_z + 1),
+                        cell(cell_x + 1, cell_y, cell_z + 1),
+                    ),
+                ),
+                lerp(
+                    tz,
+                    lerp(
+                        tx,
+                        cell(cell_x, cell_y + 1, cell_z),
+                        cell(cell_x + 1, cell_y + 1, cell_z),
+                    ),
+                    lerp(
+                        tx,
+                        cell(cell_x, cell_y + 1, cell_z + 1),
+                        cell(cell_x + 1, cell_y + 1, cell_z + 1),
+                    ),
+                ),
+            )
+        };
+
+        for x in 0..SECTION_WIDTH {
+            for z in 0..SECTION_WIDTH {
+                let column = columns[z * SECTION_WIDTH + x];
+                // caves stay below the surface, and far enough below the sea to not flood
+                let max_y = if column.height < SEA_LEVEL {
+                    column.height.saturating_sub(4)
+                } else {
+                    column.height - 1
+                };
+
+                for y in 5..=max_y.min(MAX_CAVE_HEIGHT - 1) {
+                    let (first, second) = (sample(0, x, y, z), sample(1, x, y, z));
+
+                    if first * first + second * second < CAVE_THRESHOLD {
+                        let block = if y < LAVA_LEVEL {
+                            self.blocks.lava
+                        } else {
+                            AIR
+                        };
+
+                        chunk.set_block(x, y, z, block);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Places veins of ores into the stone.
+    fn place_ores<R: Rng>(&self, chunk: &mut Chunk, rng: &mut R) {
+        let ores = [
+            // ore, veins per chunk, blocks per vein, highest y
+            (self.blocks.coal_ore, 20, 12, 128),
+            (self.blocks.iron_ore, 20, 8, 64),
+            (self.blocks.gold_ore, 2, 8, 32),
+            (self.blocks.lapis_ore, 1, 6, 32),
+        ];
+
+        for &(ore, veins, size, max_y) in &ores {
+            for _ in 0..veins {
+                let (mut x, mut y, mut z): (usize, usize, usize) = (
+                    rng.gen_range(0..SECTION_WIDTH),
+                    rng.gen_range(1..max_y),
+                    rng.gen_range(0..SECTION_WIDTH),
+                );
+
+                for _ in 0..size {
+                    if chunk.block(x, y, z) == self.blocks.stone {
+                        chunk.set_block(x, y, z, ore);
+                    }
+
+                    // wander to a neighbour, staying inside of the chunk
+                    match rng.gen_range(0..3) {
+                        0 => x = step(x, rng.gen(), SECTION_WIDTH),
+                        1 => y = step(y, rng.gen(), max_y),
+                        _ => z = step(z, rng.gen(), SECTION_WIDTH),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Plants trees on the grass, depending on the biome of the chunk.
+    ///
+    /// The trees are kept away from the borders of the chunk, so they are not cut off by
+    /// the neighbouring chunks that are generated separately.
+    fn plant_trees<R: Rng>(&self, chunk: &mut Chunk, columns: &[Column], rng: &mut R) {
+        let biome = columns[columns.len() / 2 + SECTION_WIDTH / 2].biome;
+        let trees = match biome {
+            FOREST => 6,
+            TAIGA => 4,
+            PLAINS | MOUNTAINS if rng.gen_range(0..4) == 0 => 1,
+            _ => 0,
+        };
+
+        for _ in 0..trees {
+            let x = rng.gen_range(2..SECTION_WIDTH - 2);
+            let z = rng.gen_range(2..SECTION_WIDTH - 2);
+            let ground = columns[z * SECTION_WIDTH + x].height;
+
+            if chunk.block(x, ground, z) != self.blocks.grass_block || ground + 10 >= CHUNK_HEIGHT {
+                continue;
+            }
+
+            let trunk_height = rng.gen_range(4..7);
+            let (log, leaves) = match biome {
+                TAIGA => (self.blocks.spruce_log, self.blocks.spruce_leaves),
+                FOREST if rng.gen_range(0..5) == 0 => {
+                    (self.blocks.birch_log, self.blocks.birch_leaves)
+                }
+                _ => (self.blocks.oak_log, self.blocks.oak_leaves),
+            };
+
+            chunk.set_block(x, ground, z, self.blocks.dirt);
+
+            let top = ground + trunk_height;
+
+            for y in top - 3..=top + 1 {
+                let distance = top + 1 - y;
+                // spruces alternate between narrow and wide layers, the others are round
+                let radius = match biome {
+                    TAIGA => [0, 1, 2, 1, 2][distance],
+                    _ if distance <= 2 => 1,
+                    _ => 2,
+                };
+
+                for leaf_x in x - radius..=x + radius {
+                    for leaf_z in z - radius..=z + radius {
+                        let corner = radius > 0
+                            && leaf_x.abs_diff(x) == radius
+                            && leaf_z.abs_diff(z) == radius;
+
+                        if corner && (biome == TAIGA || distance == 0 || rng.gen()) {
+                            continue;
+                        }
+
+                        if chunk.block(leaf_x, y, leaf_z) == AIR {
+                            chunk.set_block(leaf_x, y, leaf_z, leaves);
+                        }
+                    }
+                }
+            }
+
+            for y in ground + 1..=top {
+                chunk.set_block(x, y, z, log);
+            }
+        }
+    }
+
+    /// The rng for the details of a chunk, so they do not depend on the order chunks are
+    /// generated in.
+    fn chunk_rng(&self, x: i32, z: i32) -> ChaCha8Rng {
+        ChaCha8Rng::seed_from_u64(
+            (self.seed as u64)
+                ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+                ^ (z as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F),
+        )
+    }
+}
+
+/// Moves one step up or down, unless that leaves `0..max`.
+fn step(value: usize, up: bool, max: usize) -> usize {
+    if up {
+        (value + 1).min(max - 1)
+    } else {
+        value.saturating_sub(1)
+    }
+}
+
+impl WorldGenerator for NoiseGenerator {
+    fn generate_chunk(&self, x: i32, z: i32) -> Chunk {
+        let mut chunk = Chunk::new(x, z);
+        let mut rng = self.chunk_rng(x, z);
+        let (world_x, world_z) = (x * SECTION_WIDTH as i32, z * SECTION_WIDTH as i32);
+
+        let mut columns = Vec::with_capacity(SECTION_WIDTH * SECTION_WIDTH);
+
+        for column_z in 0..SECTION_WIDTH {
+            for column_x in 0..SECTION_WIDTH {
+                columns.push(self.column(world_x + column_x as i32, world_z + column_z as i32));
+            }
+        }
+
+        for column_z in 0..SECTION_WIDTH {
+            for column_x in 0..SECTION_WIDTH {
+                let column = columns[column_z * SECTION_WIDTH + column_x];
+
+                self.fill_column(&mut chunk, column_x, column_z, column, &mut rng);
+            }
+        }
+
+        self.carve_caves(&mut chunk, &columns);
+        self.place_ores(&mut chunk, &mut rng);
+        self.plant_trees(&mut chunk, &columns, &mut rng);
+
+        chunk.compute_sky_light();
+
+        chunk
+    }
+
+    /// On the first land found going east from the origin.
+    fn spawn_location(&self) -> Location {
+        let x = (0..4096)
+            .step_by(SECTION_WIDTH)
+            .find(|&x| self.column(x, 0).height >= SEA_LEVEL)
+            .unwrap_or(0);
+
+        Location {
+            x,
+            y: self.column(x, 0).height.max(SEA_LEVEL - 1) as i16 + 1,
+            z: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Column, NoiseGenerator, TerrainSettings, DEEP_OCEAN, DESERT, FOREST, OCEAN, SEA_LEVEL,
+    };
+    use crate::block::BlockRegistry;
+    use crate::chunk::{Chunk, AIR, SECTION_WIDTH};
+    use crate::location::Location;
+    use crate::world::generator::WorldGenerator;
+
+    #[test]
+    fn test_same_seed_same_chunks() {
+        let first = NoiseGenerator::new(42, TerrainSettings::default());
+        let second = NoiseGenerator::new(42, TerrainSettings::default());
+        let other = NoiseGenerator::new(43, TerrainSettings::default());
+
+        assert_eq!(first.generate_chunk(3, -5), second.generate_chunk(3, -5));
+        assert_ne!(first.generate_chunk(3, -5), other.generate_chunk(3, -5));
+        assert_eq!(first.spawn_location(), second.spawn_location());
+    }
+
+    #[test]
+    fn test_known_heights() {
+        // a seed has to keep its world, so these only change on purpose
+        let generator = NoiseGenerator::new(42, TerrainSettings::default());
+
+        let mappings = vec![
+            ((0, 0), 73, DESERT),
+            ((100, -100), 77, FOREST),
+            ((-1000, 500), 75, FOREST),
+            ((4000, 4000), 61, OCEAN),
+        ];
+
+        for ((x, z), height, biome) in mappings {
+            assert_eq!(Column { height, biome }, generator.column(x, z));
+        }
+
+        assert_eq!(Location { x: 0, y: 74, z: 0 }, generator.spawn_location());
+    }
+
+    #[test]
+    fn test_chunk_layout() {
+        let blocks = BlockRegistry::vanilla();
+        let bedrock = blocks.parse("bedrock").unwrap();
+        let water = blocks.parse("water").unwrap();
+
+        let generator = NoiseGenerator::new(7, TerrainSettings::default());
+
+        for &(chunk_x, chunk_z) in &[(0, 0), (-20, 13), (40, 40)] {
+            let chunk = generator.generate_chunk(chunk_x, chunk_z);
+
+            for x in 0..SECTION_WIDTH {
+                for z in 0..SECTION_WIDTH {
+                    let column = generator.column(
+                        chunk_x * SECTION_WIDTH as i32 + x as i32,
+                        chunk_z * SECTION_WIDTH as i32 + z as i32,
+                    );
+
+                    assert_eq!(bedrock, chunk.block(x, 0, z));
+                    assert_eq!(column.biome, chunk.biome(x, z));
+
+                    if column.biome == OCEAN || column.biome == DEEP_OCEAN {
+                        assert_eq!(water, chunk.block(x, SEA_LEVEL - 1, z));
+                    }
+
+                    // only trees reach above the terrain and the sea
+                    let surface = column.height.max(SEA_LEVEL - 1);
+                    assert_eq!(AIR, chunk.block(x, surface + 8, z));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_level_types_differ() {
+        let highest = |settings: TerrainSettings| {
+            let generator = NoiseGenerator::new(1, settings);
+
+            (-64..64)
+                .flat_map(|x| (-64..64).map(move |z| (x * 16, z * 16)))
+                .map(|(x, z)| generator.column(x, z).height)
+                .max()
+                .unwrap()
+        };
+
+        assert!(highest(TerrainSettings::amplified()) > highest(TerrainSettings::default()) + 32);
+
+        let default = NoiseGenerator::new(1, TerrainSettings::default());
+        let large_biomes = NoiseGenerator::new(1, TerrainSettings::large_biomes());
+
+        assert_ne!(
+            default.generate_chunk(10, 10),
+            large_biomes.generate_chunk(10, 10)
+        );
+    }
+
+    #[test]
+    fn test_spawn_on_land() {
+        let water = BlockRegistry::vanilla().parse("water").unwrap();
+
+        for seed in 0..4 {
+            let generator = NoiseGenerator::new(seed, TerrainSettings::default());
+            let spawn = generator.spawn_location();
+            let (chunk_x, chunk_z) = Chunk::coordinates_of(&spawn);
+            let chunk = generator.generate_chunk(chunk_x, chunk_z);
+
+            let (x, z) = ((spawn.x & 15) as usize, (spawn.z & 15) as usize);
+            let ground = chunk.block(x, spawn.y as usize - 1, z);
+
+            assert!(
+                ground != AIR && ground != water,
+                "{} at {:?}",
+                ground,
+                spawn
+            );
+        }
+    }
+}
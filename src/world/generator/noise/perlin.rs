@@ -0,0 +1,180 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Ken Perlin's improved gradient noise, with the permutation shuffled by a seeded rng.
+pub struct Perlin {
+    permutation: [u8; 512],
+    /// added to every position, so noises with the same permutation still differ
+    offset: (f64, f64, f64),
+}
+
+impl Perlin {
+    pub fn new<R: Rng>(rng: &mut R) -> Perlin {
+        let mut values: Vec<u8> = (0..=255).collect();
+        values.shuffle(rng);
+
+        let mut permutation = [0; 512];
+
+        for (index, value) in permutation.iter_mut().enumerate() {
+            *value = values[index % 256];
+        }
+
+        Perlin {
+            permutation,
+            offset: (
+                rng.gen::<f64>() * 256.0,
+                rng.gen::<f64>() * 256.0,
+                rng.gen::<f64>() * 256.0,
+            ),
+        }
+    }
+
+    /// Returns the noise at the given position, between -1 and 1 and 0 at integer positions.
+    pub fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (x, y, z) = (x + self.offset.0, y + self.offset.1, z + self.offset.2);
+        let (floor_x, floor_y, floor_z) = (x.floor(), y.floor(), z.floor());
+        let (x, y, z) = (x - floor_x, y - floor_y, z - floor_z);
+        let (cell_x, cell_y, cell_z) = (
+            floor_x as i64 as usize & 255,
+            floor_y as i64 as usize & 255,
+            floor_z as i64 as usize & 255,
+        );
+
+        let p = &self.permutation;
+        let a = usize::from(p[cell_x]) + cell_y;
+        let aa = usize::from(p[a]) + cell_z;
+        let ab = usize::from(p[a + 1]) + cell_z;
+        let b = usize::from(p[cell_x + 1]) + cell_y;
+        let ba = usize::from(p[b]) + cell_z;
+        let bb = usize::from(p[b + 1]) + cell_z;
+
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, gradient(p[aa], x, y, z), gradient(p[ba], x - 1.0, y, z)),
+                lerp(
+                    u,
+                    gradient(p[ab], x, y - 1.0, z),
+                    gradient(p[bb], x - 1.0, y - 1.0, z),
+                ),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    gradient(p[aa + 1], x, y, z - 1.0),
+                    gradient(p[ba + 1], x - 1.0, y, z - 1.0),
+                ),
+                lerp(
+                    u,
+                    gradient(p[ab + 1], x, y - 1.0, z - 1.0),
+                    gradient(p[bb + 1], x - 1.0, y - 1.0, z - 1.0),
+                ),
+            ),
+        )
+    }
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// The dot product with one of 12 gradients, picked by the hash.
+fn gradient(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+    let hash = hash & 15;
+    let u = if hash < 8 { x } else { y };
+    let v = match hash {
+        0..=3 => y,
+        12 | 14 => x,
+        _ => z,
+    };
+
+    (if hash & 1 == 0 { u } else { -u }) + (if hash & 2 == 0 { v } else { -v })
+}
+
+/// Several layers of noise, each with twice the frequency and half the amplitude of the
+/// previous one, which adds detail to the shape of the first.
+pub struct Octaves {
+    octaves: Vec<Perlin>,
+    /// the size of the features of the first octave, in blocks
+    scale: f64,
+}
+
+impl Octaves {
+    pub fn new<R: Rng>(rng: &mut R, count: usize, scale: f64) -> Octaves {
+        Octaves {
+            octaves: (0..count).map(|_| Perlin::new(rng)).collect(),
+            scale,
+        }
+    }
+
+    /// Returns the sum of the octaves at the given position, between -1 and 1.
+    pub fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
+        let mut frequency = 1.0 / self.scale;
+        let mut amplitude = 1.0;
+        let mut sum = 0.0;
+        let mut total_amplitude = 0.0;
+
+        for octave in &self.octaves {
+            sum += octave.sample(x * frequency, y * frequency, z * frequency) * amplitude;
+            total_amplitude += amplitude;
+            frequency *= 2.0;
+            amplitude /= 2.0;
+        }
+
+        sum / total_amplitude
+    }
+
+    /// Returns the noise of a horizontal plane, e.g. for heights.
+    pub fn sample_2d(&self, x: f64, z: f64) -> f64 {
+        self.sample(x, 0.0, z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Octaves, Perlin};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_perlin_is_deterministic() {
+        let first = Perlin::new(&mut ChaCha8Rng::seed_from_u64(42));
+        let second = Perlin::new(&mut ChaCha8Rng::seed_from_u64(42));
+        let other = Perlin::new(&mut ChaCha8Rng::seed_from_u64(43));
+
+        for &(x, y, z) in &[(0.5, 1.25, -3.75), (100.1, -7.3, 12.9), (-0.01, 0.0, 0.0)] {
+            assert_eq!(first.sample(x, y, z), second.sample(x, y, z));
+            assert_ne!(first.sample(x, y, z), other.sample(x, y, z));
+        }
+    }
+
+    #[test]
+    fn test_perlin_is_continuous() {
+        let perlin = Perlin::new(&mut ChaCha8Rng::seed_from_u64(0));
+
+        for step in 0..1000 {
+            let x = f64::from(step) * 0.01;
+            let difference = perlin.sample(x + 0.001, 0.3, 0.7) - perlin.sample(x, 0.3, 0.7);
+
+            assert!(difference.abs() < 0.01, "jump of {} at {}", difference, x);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_octaves_in_range(seed: u64, x in -1e6..1e6f64, y in -256.0..256f64, z in -1e6..1e6f64) {
+            let octaves = Octaves::new(&mut ChaCha8Rng::seed_from_u64(seed), 4, 64.0);
+            let value = octaves.sample(x, y, z);
+
+            prop_assert!((-1.0..=1.0).contains(&value), "{}", value);
+        }
+    }
+}